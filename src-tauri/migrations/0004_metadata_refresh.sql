@@ -0,0 +1,11 @@
+-- Background refresh of stale metadata. `priority` lets the refresh
+-- scheduler enqueue behind scan-driven jobs (higher runs first); `status`
+-- records the provider's airing status so continuing shows can refresh
+-- on a shorter schedule than ended ones.
+
+ALTER TABLE metadata_jobs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE shows ADD COLUMN status TEXT;
+
+CREATE INDEX idx_metadata_jobs_due
+    ON metadata_jobs(priority DESC, next_attempt_at ASC);
@@ -0,0 +1,17 @@
+-- On-disk cache of provider HTTP responses (TMDB + IMDB). Keyed by the
+-- request and the language it was made in; `etag` / `last_modified`
+-- drive revalidation once `expires_at` has passed.
+
+CREATE TABLE http_cache (
+    cache_key     TEXT    NOT NULL,
+    language      TEXT    NOT NULL,
+    provider      TEXT    NOT NULL,
+    body          BLOB    NOT NULL,
+    etag          TEXT,
+    last_modified TEXT,
+    fetched_at    INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    expires_at    INTEGER NOT NULL,
+    PRIMARY KEY (cache_key, language)
+);
+
+CREATE INDEX idx_http_cache_provider ON http_cache(provider);
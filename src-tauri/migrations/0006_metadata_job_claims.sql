@@ -0,0 +1,7 @@
+-- Concurrent metadata worker. A task claims a job by stamping
+-- `claimed_at` in the same statement that selects it, so two tasks never
+-- pick up the same (kind, media_id). Claims are released when the task
+-- finishes; a claim older than the worker's timeout is treated as
+-- abandoned (crash, panic) and can be taken again.
+
+ALTER TABLE metadata_jobs ADD COLUMN claimed_at INTEGER;
@@ -0,0 +1,24 @@
+-- Append-only history of metadata provider attempts. One row per
+-- provider call made for a job, so a wrong or missing match can be traced
+-- back to the query, the candidates seen and the pick. Pruned by age
+-- (`metadata_job_log_days`); never updated.
+
+CREATE TABLE metadata_job_log (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind            TEXT NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id        INTEGER NOT NULL,
+    provider        TEXT NOT NULL,
+    query           TEXT,
+    candidate_count INTEGER,
+    chosen_id       TEXT,
+    outcome         TEXT NOT NULL,
+    error           TEXT,
+    duration_ms     INTEGER NOT NULL,
+    logged_at       INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+);
+
+CREATE INDEX idx_metadata_job_log_media
+    ON metadata_job_log(kind, media_id, logged_at DESC);
+
+CREATE INDEX idx_metadata_job_log_logged_at
+    ON metadata_job_log(logged_at);
@@ -0,0 +1,19 @@
+-- Top search candidates per provider for titles the worker couldn't
+-- match confidently. Replaced on every unmatched attempt, cleared once
+-- the title is linked. Read by Needs-review so a reviewer can confirm a
+-- match without searching again.
+
+CREATE TABLE review_candidates (
+    kind        TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id    INTEGER NOT NULL,
+    provider    TEXT    NOT NULL,
+    provider_id TEXT    NOT NULL,
+    rank        INTEGER NOT NULL,
+    title       TEXT    NOT NULL,
+    year        INTEGER,
+    poster_url  TEXT,
+    overview    TEXT,
+    score       REAL    NOT NULL,
+    created_at  INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    PRIMARY KEY (kind, media_id, provider, provider_id)
+);
@@ -0,0 +1,23 @@
+-- Learned title → provider links. Written whenever a user links or
+-- corrects a match; the worker checks it before searching, so the same
+-- scanned title turning up again (a re-scan, another library) goes
+-- straight to the right provider id.
+--
+-- `normalized_title` is `matching::normalize` of the scanned title.
+-- `year` is NULL when the scan found none; the unique index treats NULL
+-- as its own key so there is at most one year-less alias per title.
+
+CREATE TABLE title_aliases (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind             TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    normalized_title TEXT    NOT NULL,
+    year             INTEGER,
+    provider         TEXT    NOT NULL CHECK (provider IN ('tmdb', 'imdb')),
+    provider_id      TEXT    NOT NULL,
+    source_title     TEXT    NOT NULL,
+    created_at       INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    updated_at       INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+);
+
+CREATE UNIQUE INDEX idx_title_aliases_key
+    ON title_aliases(kind, normalized_title, COALESCE(year, -1));
@@ -0,0 +1,14 @@
+-- Provider titles and overviews per scrape language. A row means the
+-- title was fetched in that language; NULL fields are ones the provider
+-- had no translation for. Switching `scrape_language` reads from here
+-- and only re-fetches titles with no row for the new language.
+
+CREATE TABLE localized_metadata (
+    kind       TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id   INTEGER NOT NULL,
+    language   TEXT    NOT NULL,
+    title      TEXT,
+    overview   TEXT,
+    fetched_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    PRIMARY KEY (kind, media_id, language)
+);
@@ -0,0 +1,27 @@
+-- Movie collections (franchises), from TMDB's `belongs_to_collection`.
+-- `collection_parts` is the provider's full part list, owned or not;
+-- a part is owned when a movie is linked to the same provider id.
+-- `poster_path` is a file under <app_data>/posters, like movies'.
+
+CREATE TABLE collections (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider    TEXT    NOT NULL,
+    provider_id TEXT    NOT NULL,
+    name        TEXT    NOT NULL,
+    overview    TEXT,
+    poster_path TEXT,
+    updated_at  INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    UNIQUE (provider, provider_id)
+);
+
+CREATE TABLE collection_parts (
+    collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    provider_id   TEXT    NOT NULL,
+    title         TEXT    NOT NULL,
+    release_date  TEXT,
+    poster_url    TEXT,
+    overview      TEXT,
+    PRIMARY KEY (collection_id, provider_id)
+);
+
+CREATE INDEX idx_collection_parts_provider_id ON collection_parts(provider_id);
@@ -0,0 +1,32 @@
+-- Cast and crew, normalized out of the `top_cast` JSON blob so a person
+-- can be looked up across every title in the library. `people` is keyed
+-- by provider person id (TMDB numeric id, IMDB `nm…`); `photo_url` is
+-- the provider's remote image. `credits` is rewritten whole each time a
+-- title is (re)applied. Like `localized_metadata`, credits reference
+-- movies/shows by (kind, media_id) without a foreign key; lookups join
+-- through the title tables, so rows for deleted titles are never shown.
+
+CREATE TABLE people (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider    TEXT    NOT NULL CHECK (provider IN ('tmdb', 'imdb')),
+    provider_id TEXT    NOT NULL,
+    name        TEXT    NOT NULL,
+    photo_url   TEXT,
+    updated_at  INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    UNIQUE (provider, provider_id)
+);
+
+CREATE TABLE credits (
+    person_id    INTEGER NOT NULL REFERENCES people(id) ON DELETE CASCADE,
+    kind         TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id     INTEGER NOT NULL,
+    -- 'cast', 'director' or 'writer'.
+    role         TEXT    NOT NULL CHECK (role IN ('cast', 'director', 'writer')),
+    -- Provider's job label for crew ("Screenplay", "Novel", …).
+    job          TEXT,
+    character    TEXT,
+    credit_order INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (kind, media_id, person_id, role)
+);
+
+CREATE INDEX idx_credits_person ON credits(person_id);
@@ -0,0 +1,145 @@
+-- Normalized genres. `genres` holds one canonical row per genre;
+-- `genre_aliases` maps each provider's genre name onto it (TMDB says
+-- "Science Fiction", IMDB "Sci-Fi"; TMDB's TV genres like "Action &
+-- Adventure" map to two). Names not listed here are added by the apply
+-- path as new canonical genres. `movie_genres`/`show_genres` are
+-- rewritten on every apply; the JSON `genres` columns stay as the
+-- provider's raw list.
+
+CREATE TABLE genres (
+    id   INTEGER PRIMARY KEY AUTOINCREMENT,
+    slug TEXT    NOT NULL UNIQUE,
+    name TEXT    NOT NULL
+);
+
+CREATE TABLE genre_aliases (
+    provider      TEXT    NOT NULL CHECK (provider IN ('tmdb', 'imdb')),
+    provider_name TEXT    NOT NULL,
+    genre_id      INTEGER NOT NULL REFERENCES genres(id) ON DELETE CASCADE,
+    PRIMARY KEY (provider, provider_name, genre_id)
+);
+
+CREATE TABLE movie_genres (
+    movie_id INTEGER NOT NULL REFERENCES movies(id) ON DELETE CASCADE,
+    genre_id INTEGER NOT NULL REFERENCES genres(id) ON DELETE CASCADE,
+    PRIMARY KEY (movie_id, genre_id)
+);
+
+CREATE TABLE show_genres (
+    show_id  INTEGER NOT NULL REFERENCES shows(id) ON DELETE CASCADE,
+    genre_id INTEGER NOT NULL REFERENCES genres(id) ON DELETE CASCADE,
+    PRIMARY KEY (show_id, genre_id)
+);
+
+CREATE INDEX idx_movie_genres_genre ON movie_genres(genre_id);
+CREATE INDEX idx_show_genres_genre ON show_genres(genre_id);
+
+INSERT INTO genres (slug, name) VALUES
+    ('action', 'Action'),
+    ('adventure', 'Adventure'),
+    ('animation', 'Animation'),
+    ('biography', 'Biography'),
+    ('comedy', 'Comedy'),
+    ('crime', 'Crime'),
+    ('documentary', 'Documentary'),
+    ('drama', 'Drama'),
+    ('family', 'Family'),
+    ('fantasy', 'Fantasy'),
+    ('film-noir', 'Film Noir'),
+    ('game-show', 'Game Show'),
+    ('history', 'History'),
+    ('horror', 'Horror'),
+    ('kids', 'Kids'),
+    ('music', 'Music'),
+    ('musical', 'Musical'),
+    ('mystery', 'Mystery'),
+    ('news', 'News'),
+    ('politics', 'Politics'),
+    ('reality', 'Reality'),
+    ('romance', 'Romance'),
+    ('science-fiction', 'Science Fiction'),
+    ('short', 'Short'),
+    ('soap', 'Soap'),
+    ('sport', 'Sport'),
+    ('talk-show', 'Talk Show'),
+    ('thriller', 'Thriller'),
+    ('tv-movie', 'TV Movie'),
+    ('war', 'War'),
+    ('western', 'Western');
+
+INSERT INTO genre_aliases (provider, provider_name, genre_id)
+SELECT v.column1, v.column2, g.id
+FROM (VALUES
+    ('tmdb', 'Action', 'action'),
+    ('tmdb', 'Adventure', 'adventure'),
+    ('tmdb', 'Animation', 'animation'),
+    ('tmdb', 'Comedy', 'comedy'),
+    ('tmdb', 'Crime', 'crime'),
+    ('tmdb', 'Documentary', 'documentary'),
+    ('tmdb', 'Drama', 'drama'),
+    ('tmdb', 'Family', 'family'),
+    ('tmdb', 'Fantasy', 'fantasy'),
+    ('tmdb', 'History', 'history'),
+    ('tmdb', 'Horror', 'horror'),
+    ('tmdb', 'Music', 'music'),
+    ('tmdb', 'Mystery', 'mystery'),
+    ('tmdb', 'Romance', 'romance'),
+    ('tmdb', 'Science Fiction', 'science-fiction'),
+    ('tmdb', 'TV Movie', 'tv-movie'),
+    ('tmdb', 'Thriller', 'thriller'),
+    ('tmdb', 'War', 'war'),
+    ('tmdb', 'Western', 'western'),
+    ('tmdb', 'Action & Adventure', 'action'),
+    ('tmdb', 'Action & Adventure', 'adventure'),
+    ('tmdb', 'Kids', 'kids'),
+    ('tmdb', 'News', 'news'),
+    ('tmdb', 'Reality', 'reality'),
+    ('tmdb', 'Sci-Fi & Fantasy', 'science-fiction'),
+    ('tmdb', 'Sci-Fi & Fantasy', 'fantasy'),
+    ('tmdb', 'Soap', 'soap'),
+    ('tmdb', 'Talk', 'talk-show'),
+    ('tmdb', 'War & Politics', 'war'),
+    ('tmdb', 'War & Politics', 'politics'),
+    ('imdb', 'Action', 'action'),
+    ('imdb', 'Adventure', 'adventure'),
+    ('imdb', 'Animation', 'animation'),
+    ('imdb', 'Biography', 'biography'),
+    ('imdb', 'Comedy', 'comedy'),
+    ('imdb', 'Crime', 'crime'),
+    ('imdb', 'Documentary', 'documentary'),
+    ('imdb', 'Drama', 'drama'),
+    ('imdb', 'Family', 'family'),
+    ('imdb', 'Fantasy', 'fantasy'),
+    ('imdb', 'Film-Noir', 'film-noir'),
+    ('imdb', 'Game-Show', 'game-show'),
+    ('imdb', 'History', 'history'),
+    ('imdb', 'Horror', 'horror'),
+    ('imdb', 'Music', 'music'),
+    ('imdb', 'Musical', 'musical'),
+    ('imdb', 'Mystery', 'mystery'),
+    ('imdb', 'News', 'news'),
+    ('imdb', 'Reality-TV', 'reality'),
+    ('imdb', 'Romance', 'romance'),
+    ('imdb', 'Sci-Fi', 'science-fiction'),
+    ('imdb', 'Short', 'short'),
+    ('imdb', 'Sport', 'sport'),
+    ('imdb', 'Talk-Show', 'talk-show'),
+    ('imdb', 'Thriller', 'thriller'),
+    ('imdb', 'War', 'war'),
+    ('imdb', 'Western', 'western')
+) v
+JOIN genres g ON g.slug = v.column3;
+
+-- Backfill from the JSON columns. Names with no alias are picked up on
+-- the title's next refresh.
+INSERT OR IGNORE INTO movie_genres (movie_id, genre_id)
+SELECT m.id, a.genre_id
+FROM movies m, json_each(m.genres) j
+JOIN genre_aliases a ON a.provider = m.provider AND a.provider_name = j.value
+WHERE m.genres IS NOT NULL AND json_valid(m.genres);
+
+INSERT OR IGNORE INTO show_genres (show_id, genre_id)
+SELECT s.id, a.genre_id
+FROM shows s, json_each(s.genres) j
+JOIN genre_aliases a ON a.provider = s.provider AND a.provider_name = j.value
+WHERE s.genres IS NOT NULL AND json_valid(s.genres);
@@ -0,0 +1,35 @@
+-- Age certifications per title and country, as returned by TMDB
+-- (`release_dates` / `content_ratings`) and IMDB (`certificates`). Every
+-- country the provider lists is kept; the `certification_country`
+-- setting picks which one applies. `min_age` is the age the
+-- certification implies (NULL for "not rated" or unrecognised ones).
+
+CREATE TABLE certifications (
+    kind          TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id      INTEGER NOT NULL,
+    country       TEXT    NOT NULL,
+    certification TEXT    NOT NULL,
+    min_age       INTEGER,
+    fetched_at    INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    PRIMARY KEY (kind, media_id, country)
+);
+
+-- Parental controls. At most one row; no row means off. Kept out of
+-- `app_settings` so neither the generic settings command nor the admin
+-- grid can read the PIN hash or switch the controls off without it.
+-- Titles whose certification implies an age above `max_age` are hidden
+-- and can't be played; `hide_unrated` extends that to titles with no
+-- usable certification. `country` is the `certification_country` the
+-- limit was set in, copied here so editing the setting alone can't
+-- loosen the controls.
+
+CREATE TABLE parental_controls (
+    id           INTEGER PRIMARY KEY CHECK (id = 1),
+    country      TEXT    NOT NULL,
+    max_rating   TEXT    NOT NULL,
+    max_age      INTEGER NOT NULL,
+    hide_unrated INTEGER NOT NULL DEFAULT 0,
+    pin_salt     TEXT    NOT NULL,
+    pin_hash     TEXT    NOT NULL,
+    updated_at   INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+);
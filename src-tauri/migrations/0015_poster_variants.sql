@@ -0,0 +1,14 @@
+-- Resized copies of the synced poster, as filenames in the posters
+-- directory like `poster_path`. Each is fetched from the provider at the
+-- width it's shown at, so grids don't load full-size images. NULL when
+-- the variant hasn't been downloaded (titles synced before this, failed
+-- downloads) or the poster is a manual upload; the UI falls back to
+-- `poster_path`.
+
+ALTER TABLE movies ADD COLUMN poster_thumb_path TEXT;
+ALTER TABLE movies ADD COLUMN poster_card_path  TEXT;
+ALTER TABLE movies ADD COLUMN poster_hero_path  TEXT;
+
+ALTER TABLE shows ADD COLUMN poster_thumb_path TEXT;
+ALTER TABLE shows ADD COLUMN poster_card_path  TEXT;
+ALTER TABLE shows ADD COLUMN poster_hero_path  TEXT;
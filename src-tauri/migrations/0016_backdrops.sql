@@ -0,0 +1,10 @@
+-- Wide artwork for the detail page header, stored like `poster_path`:
+-- a filename in the posters directory when synced, an absolute path
+-- there when uploaded. `backdrop_origin` is 'tmdb', 'imdb' or 'manual';
+-- sync never replaces a manual backdrop.
+
+ALTER TABLE movies ADD COLUMN backdrop_path   TEXT;
+ALTER TABLE movies ADD COLUMN backdrop_origin TEXT;
+
+ALTER TABLE shows ADD COLUMN backdrop_path   TEXT;
+ALTER TABLE shows ADD COLUMN backdrop_origin TEXT;
@@ -0,0 +1,20 @@
+-- Airing details per show. `networks` is a JSON array of names like
+-- `genres`; `end_year` is set once a show has ended. `show_air_dates`
+-- holds the provider's air dates for the season on air (TMDB only), so
+-- the calendar can list what's coming and what aired but isn't in the
+-- library yet. Rewritten on every sync.
+
+ALTER TABLE shows ADD COLUMN end_year              INTEGER;
+ALTER TABLE shows ADD COLUMN networks              TEXT;
+ALTER TABLE shows ADD COLUMN next_episode_air_date TEXT;
+
+CREATE TABLE show_air_dates (
+    show_id  INTEGER NOT NULL REFERENCES shows(id) ON DELETE CASCADE,
+    season   INTEGER NOT NULL,
+    episode  INTEGER NOT NULL,
+    title    TEXT,
+    air_date TEXT    NOT NULL,
+    PRIMARY KEY (show_id, season, episode)
+);
+
+CREATE INDEX idx_show_air_dates_air_date ON show_air_dates(air_date);
@@ -0,0 +1,24 @@
+-- Ratings per title and source ('tmdb', 'imdb', …), side by side. Each
+-- provider writes its own row, also for titles another provider is
+-- linked to, so one no longer overwrites the other. `value` is on the
+-- source's own `scale`; `votes` is NULL when the source doesn't say.
+-- `movies.rating` / `shows.rating` stay as the linked provider's value.
+
+CREATE TABLE ratings (
+    kind       TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id   INTEGER NOT NULL,
+    source     TEXT    NOT NULL,
+    value      REAL    NOT NULL,
+    scale      REAL    NOT NULL,
+    votes      INTEGER,
+    fetched_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    PRIMARY KEY (kind, media_id, source)
+);
+
+INSERT INTO ratings (kind, media_id, source, value, scale)
+SELECT 'movie', id, provider, rating, 10 FROM movies
+WHERE provider IS NOT NULL AND rating IS NOT NULL;
+
+INSERT INTO ratings (kind, media_id, source, value, scale)
+SELECT 'show', id, provider, rating, 10 FROM shows
+WHERE provider IS NOT NULL AND rating IS NOT NULL;
@@ -0,0 +1,25 @@
+-- Every id a title is known by, per source ('tmdb', 'imdb', 'tvdb',
+-- 'wikidata'). `provider`/`provider_id` still names the one provider
+-- the title is linked to; this holds that id plus the other providers'
+-- ids learned from TMDB's `external_ids` and from finding an
+-- IMDB-linked title on TMDB, so the worker can fetch by id when
+-- `metadata_mode` switches providers. Cleared on link and unlink.
+
+CREATE TABLE external_ids (
+    kind        TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id    INTEGER NOT NULL,
+    source      TEXT    NOT NULL,
+    external_id TEXT    NOT NULL,
+    updated_at  INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    PRIMARY KEY (kind, media_id, source)
+);
+
+CREATE INDEX idx_external_ids_source ON external_ids(kind, source, external_id);
+
+INSERT INTO external_ids (kind, media_id, source, external_id)
+SELECT 'movie', id, provider, provider_id FROM movies
+WHERE provider IS NOT NULL AND provider_id IS NOT NULL;
+
+INSERT INTO external_ids (kind, media_id, source, external_id)
+SELECT 'show', id, provider, provider_id FROM shows
+WHERE provider IS NOT NULL AND provider_id IS NOT NULL;
@@ -0,0 +1,48 @@
+-- Hand-entered metadata. Manual edits store their cast and genres under
+-- the 'manual' provider, so `people` and `genre_aliases` accept it. SQLite
+-- can't alter a CHECK, so both tables are rebuilt; `credits` is rebuilt
+-- with `people` so dropping the old table can't cascade into it.
+
+CREATE TABLE people_new (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider    TEXT    NOT NULL CHECK (provider IN ('tmdb', 'imdb', 'manual')),
+    provider_id TEXT    NOT NULL,
+    name        TEXT    NOT NULL,
+    photo_url   TEXT,
+    updated_at  INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    UNIQUE (provider, provider_id)
+);
+INSERT INTO people_new (id, provider, provider_id, name, photo_url, updated_at)
+    SELECT id, provider, provider_id, name, photo_url, updated_at FROM people;
+
+CREATE TABLE credits_new (
+    person_id    INTEGER NOT NULL REFERENCES people_new(id) ON DELETE CASCADE,
+    kind         TEXT    NOT NULL CHECK (kind IN ('show', 'movie')),
+    media_id     INTEGER NOT NULL,
+    -- 'cast', 'director' or 'writer'.
+    role         TEXT    NOT NULL CHECK (role IN ('cast', 'director', 'writer')),
+    -- Provider's job label for crew ("Screenplay", "Novel", …).
+    job          TEXT,
+    character    TEXT,
+    credit_order INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (kind, media_id, person_id, role)
+);
+INSERT INTO credits_new (person_id, kind, media_id, role, job, character, credit_order)
+    SELECT person_id, kind, media_id, role, job, character, credit_order FROM credits;
+
+DROP TABLE credits;
+DROP TABLE people;
+ALTER TABLE people_new RENAME TO people;
+ALTER TABLE credits_new RENAME TO credits;
+CREATE INDEX idx_credits_person ON credits(person_id);
+
+CREATE TABLE genre_aliases_new (
+    provider      TEXT    NOT NULL CHECK (provider IN ('tmdb', 'imdb', 'manual')),
+    provider_name TEXT    NOT NULL,
+    genre_id      INTEGER NOT NULL REFERENCES genres(id) ON DELETE CASCADE,
+    PRIMARY KEY (provider, provider_name, genre_id)
+);
+INSERT INTO genre_aliases_new (provider, provider_name, genre_id)
+    SELECT provider, provider_name, genre_id FROM genre_aliases;
+DROP TABLE genre_aliases;
+ALTER TABLE genre_aliases_new RENAME TO genre_aliases;
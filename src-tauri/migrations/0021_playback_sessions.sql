@@ -0,0 +1,22 @@
+-- One row per mpv run, appended when the player exits. `watch_history`
+-- keeps only the latest position per item; this keeps every session so
+-- history can be listed by date and plays counted. Positions are in
+-- seconds into the file; `duration_seconds` is the file's length as mpv
+-- reported it. `exit_status` is mpv's exit code, NULL when it was killed
+-- by a signal or couldn't be reaped. Rows outlive their title.
+
+CREATE TABLE playback_sessions (
+    id                     INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_kind             TEXT    NOT NULL CHECK (media_kind IN ('movie', 'episode')),
+    media_id               INTEGER NOT NULL,
+    started_at             INTEGER NOT NULL,
+    ended_at               INTEGER NOT NULL,
+    start_position_seconds INTEGER NOT NULL,
+    end_position_seconds   INTEGER NOT NULL,
+    duration_seconds       INTEGER,
+    completed              INTEGER NOT NULL DEFAULT 0,
+    exit_status            INTEGER
+);
+
+CREATE INDEX idx_playback_sessions_started ON playback_sessions(started_at);
+CREATE INDEX idx_playback_sessions_media ON playback_sessions(media_kind, media_id);
@@ -0,0 +1,8 @@
+-- Jobs the user forced ("Refresh metadata", a relink) and jobs queued by
+-- the stale-metadata refresh scheduler must see current provider data,
+-- so they revalidate cached responses even before `expires_at`. The
+-- index serves the periodic prune of long-expired cache entries.
+
+ALTER TABLE metadata_jobs ADD COLUMN revalidate INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_http_cache_expires ON http_cache(expires_at);
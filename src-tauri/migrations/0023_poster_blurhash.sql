@@ -0,0 +1,9 @@
+-- Blurhash of the poster, painted as a placeholder while the poster or
+-- one of its variants loads. Computed with the variants, which are now
+-- resized locally from the poster on disk (synced and manual alike)
+-- instead of downloaded per size. NULL until the variants are rendered
+-- or when the poster couldn't be decoded.
+
+ALTER TABLE movies ADD COLUMN poster_blurhash TEXT;
+
+ALTER TABLE shows ADD COLUMN poster_blurhash TEXT;
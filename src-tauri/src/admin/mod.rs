@@ -55,7 +55,8 @@ impl Table {
                 "id", "library_id", "title", "year", "folder_path", "fingerprint",
                 "poster_path", "poster_origin", "overview", "added_at",
                 "provider", "provider_id", "rating", "genres", "top_cast",
-                "first_air_date", "metadata_synced_at", "metadata_locked",
+                "first_air_date", "metadata_synced_at", "metadata_locked", "status",
+                "end_year", "networks", "next_episode_air_date",
             ],
             Table::Movies => &[
                 "id", "library_id", "title", "year", "path", "poster_path",
@@ -73,7 +74,7 @@ impl Table {
             ],
             Table::MetadataJobs => &[
                 "kind", "media_id", "enqueued_at", "attempts", "last_error",
-                "next_attempt_at",
+                "next_attempt_at", "priority", "claimed_at",
             ],
             Table::AppSettings => &["key", "value"],
         }
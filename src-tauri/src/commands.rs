@@ -4,6 +4,8 @@ use tauri::{AppHandle, Manager, State};
 
 use crate::db::Db;
 use crate::error::{AppError, AppResult};
+use crate::metadata::manual::MetadataPatch;
+use crate::metadata::preview;
 use crate::models::{
     ContinueWatchingItem, Episode, Library, LibraryKind, MergeOutcome, Movie, ScanReport, Season,
     Show,
@@ -61,11 +63,22 @@ pub async fn scan_libraries(app: AppHandle, db: State<'_, Db>) -> AppResult<Scan
     Ok(report)
 }
 
+/// `genres`: canonical genre slugs; only titles tagged with all of them
+/// are returned. `rating_source`: the `ratings` source shown as `rating`
+/// (the linked provider's when omitted); `sort`: `"rating"` for best
+/// rated first, by title otherwise.
 #[tauri::command]
-pub async fn list_movies(app: AppHandle, db: State<'_, Db>) -> AppResult<Vec<Movie>> {
-    let mut items = queries::list_movies(&db).await?;
+pub async fn list_movies(
+    app: AppHandle,
+    db: State<'_, Db>,
+    genres: Option<Vec<String>>,
+    rating_source: Option<String>,
+    sort: Option<String>,
+) -> AppResult<Vec<Movie>> {
+    let view = rating_view(rating_source, sort);
+    let mut items = queries::list_movies(&db, &genres.unwrap_or_default(), &view).await?;
     for movie in items.iter_mut() {
-        movie.poster_path = resolve_poster_path(&app, movie.poster_path.take());
+        resolve_movie_posters(&app, movie);
     }
     Ok(items)
 }
@@ -73,15 +86,22 @@ pub async fn list_movies(app: AppHandle, db: State<'_, Db>) -> AppResult<Vec<Mov
 #[tauri::command]
 pub async fn get_movie(app: AppHandle, db: State<'_, Db>, id: i64) -> AppResult<Movie> {
     let mut movie = queries::get_movie(&db, id).await?;
-    movie.poster_path = resolve_poster_path(&app, movie.poster_path.take());
+    resolve_movie_posters(&app, &mut movie);
     Ok(movie)
 }
 
 #[tauri::command]
-pub async fn list_shows(app: AppHandle, db: State<'_, Db>) -> AppResult<Vec<Show>> {
-    let mut items = queries::list_shows(&db).await?;
+pub async fn list_shows(
+    app: AppHandle,
+    db: State<'_, Db>,
+    genres: Option<Vec<String>>,
+    rating_source: Option<String>,
+    sort: Option<String>,
+) -> AppResult<Vec<Show>> {
+    let view = rating_view(rating_source, sort);
+    let mut items = queries::list_shows(&db, &genres.unwrap_or_default(), &view).await?;
     for show in items.iter_mut() {
-        show.poster_path = resolve_poster_path(&app, show.poster_path.take());
+        resolve_show_posters(&app, show);
     }
     Ok(items)
 }
@@ -89,10 +109,51 @@ pub async fn list_shows(app: AppHandle, db: State<'_, Db>) -> AppResult<Vec<Show
 #[tauri::command]
 pub async fn get_show(app: AppHandle, db: State<'_, Db>, id: i64) -> AppResult<Show> {
     let mut show = queries::get_show(&db, id).await?;
-    show.poster_path = resolve_poster_path(&app, show.poster_path.take());
+    resolve_show_posters(&app, &mut show);
     Ok(show)
 }
 
+/// Genres with at least one movie or show, with per-kind counts.
+#[tauri::command]
+pub async fn list_genres(
+    db: State<'_, Db>,
+) -> AppResult<Vec<crate::metadata::genres::GenreCount>> {
+    crate::metadata::genres::counts(&db).await
+}
+
+fn rating_view(source: Option<String>, sort: Option<String>) -> queries::RatingView {
+    queries::RatingView {
+        source: source.filter(|source| !source.is_empty()),
+        sort_by_rating: sort.as_deref() == Some("rating"),
+    }
+}
+
+/// Every source's rating for a movie or show (`kind`).
+#[tauri::command]
+pub async fn list_title_ratings(
+    db: State<'_, Db>,
+    kind: String,
+    id: i64,
+) -> AppResult<Vec<crate::metadata::ratings::Rating>> {
+    crate::metadata::ratings::for_title(&db, &kind, id).await
+}
+
+/// Every id known for a movie or show (`kind`), by source.
+#[tauri::command]
+pub async fn list_external_ids(
+    db: State<'_, Db>,
+    kind: String,
+    id: i64,
+) -> AppResult<Vec<crate::metadata::external_ids::ExternalId>> {
+    crate::metadata::external_ids::for_title(&db, &kind, id).await
+}
+
+/// Rating sources present in the library.
+#[tauri::command]
+pub async fn list_rating_sources(db: State<'_, Db>) -> AppResult<Vec<String>> {
+    crate::metadata::ratings::sources(&db).await
+}
+
 #[tauri::command]
 pub async fn get_seasons(db: State<'_, Db>, show_id: i64) -> AppResult<Vec<Season>> {
     queries::list_seasons(&db, show_id).await
@@ -112,10 +173,10 @@ pub async fn continue_watching(
     for item in items.iter_mut() {
         match item {
             ContinueWatchingItem::Movie { movie } => {
-                movie.poster_path = resolve_poster_path(&app, movie.poster_path.take());
+                resolve_movie_posters(&app, movie);
             }
             ContinueWatchingItem::Episode { show, .. } => {
-                show.poster_path = resolve_poster_path(&app, show.poster_path.take());
+                resolve_show_posters(&app, show);
             }
         }
     }
@@ -148,6 +209,7 @@ pub async fn play_movie(
     id: i64,
     resume: Option<i64>,
 ) -> AppResult<player::PlayResult> {
+    crate::parental::ensure_playable(&db, "movie", id).await?;
     let movie = queries::get_movie(&db, id).await?;
     let start = resume.unwrap_or(movie.progress_seconds);
     player::play(&db, "movie", id, &movie.path, start).await
@@ -159,23 +221,224 @@ pub async fn play_episode(
     id: i64,
     resume: Option<i64>,
 ) -> AppResult<player::PlayResult> {
+    crate::parental::ensure_playable(&db, "episode", id).await?;
     let ep = queries::get_episode(&db, id).await?;
     let start = resume.unwrap_or(ep.progress_seconds);
     player::play(&db, "episode", id, &ep.path, start).await
 }
 
+/// Playback sessions started between two `YYYY-MM-DD` dates, inclusive
+/// (either end open when omitted), newest first.
+#[tauri::command]
+pub async fn list_playback_history(
+    db: State<'_, Db>,
+    from: Option<String>,
+    to: Option<String>,
+) -> AppResult<Vec<crate::history::PlaybackSession>> {
+    crate::history::list(&db, from.as_deref(), to.as_deref()).await
+}
+
+/// Plays per movie and episode over the same kind of range.
+#[tauri::command]
+pub async fn list_play_counts(
+    db: State<'_, Db>,
+    from: Option<String>,
+    to: Option<String>,
+) -> AppResult<Vec<crate::history::PlayCount>> {
+    crate::history::play_counts(&db, from.as_deref(), to.as_deref()).await
+}
+
+/// Hours watched, top series, genres, binges and yearly totals for the
+/// sessions `list_playback_history` would return over the same range.
+#[tauri::command]
+pub async fn viewing_stats(
+    db: State<'_, Db>,
+    from: Option<String>,
+    to: Option<String>,
+) -> AppResult<crate::stats::ViewingStats> {
+    crate::stats::viewing_stats(&db, from.as_deref(), to.as_deref()).await
+}
+
+/// Deletes poster files nothing references, clears references to
+/// missing or corrupt ones and queues those titles to download again
+/// (locked ones download their poster right away).
+#[tauri::command]
+pub async fn run_poster_maintenance(
+    app: AppHandle,
+    db: State<'_, Db>,
+    http: State<'_, crate::metadata::http::ProviderHttp>,
+) -> AppResult<crate::metadata::poster_cache::PosterCacheReport> {
+    let posters_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|error| AppError::Other(format!("app_data_dir: {error}")))?
+        .join("posters");
+    let report = crate::metadata::poster_cache::run(&db, &http, &posters_dir).await?;
+    if report.requeued > 0 {
+        wake_worker(&app);
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_parental_controls(
+    db: State<'_, Db>,
+) -> AppResult<crate::parental::ParentalControls> {
+    crate::parental::get(&db).await
+}
+
+/// Turns parental controls on or changes them; `current_pin` is needed
+/// once they're on. Titles synced before certifications were stored are
+/// queued for a refresh so the limit applies to them too.
+#[tauri::command]
+pub async fn set_parental_controls(
+    app: AppHandle,
+    db: State<'_, Db>,
+    current_pin: Option<String>,
+    pin: String,
+    max_rating: String,
+    hide_unrated: bool,
+) -> AppResult<crate::parental::ParentalControls> {
+    let controls = crate::parental::set(
+        &db,
+        current_pin.as_deref(),
+        &pin,
+        &max_rating,
+        hide_unrated,
+    )
+    .await?;
+    if crate::metadata::certifications::enqueue_missing(&db).await? > 0 {
+        wake_worker(&app);
+    }
+    Ok(controls)
+}
+
+#[tauri::command]
+pub async fn disable_parental_controls(db: State<'_, Db>, pin: String) -> AppResult<()> {
+    crate::parental::disable(&db, &pin).await
+}
+
+/// Certifications known for the `certification_country`, youngest
+/// first, for the parental controls picker.
+#[tauri::command]
+pub async fn list_certifications(db: State<'_, Db>) -> AppResult<Vec<String>> {
+    let country = crate::metadata::certifications::country(&db).await?;
+    Ok(crate::metadata::certifications::known_ratings(&country)
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+/// Upcoming episodes of library shows and recently aired ones that
+/// aren't in the library yet.
+#[tauri::command]
+pub async fn show_calendar(
+    app: AppHandle,
+    db: State<'_, Db>,
+    recent_days: Option<i64>,
+) -> AppResult<crate::metadata::airing::Calendar> {
+    use crate::metadata::airing;
+
+    let recent_days = recent_days.unwrap_or(airing::DEFAULT_RECENT_DAYS);
+    let mut calendar = airing::calendar(&db, None, recent_days).await?;
+    for entry in calendar.upcoming.iter_mut().chain(calendar.missing.iter_mut()) {
+        entry.poster_path = resolve_poster_path(&app, entry.poster_path.take());
+    }
+    Ok(calendar)
+}
+
+/// Collections with at least one owned movie; each lists every part,
+/// owned or missing, in release order.
+#[tauri::command]
+pub async fn list_collections(
+    app: AppHandle,
+    db: State<'_, Db>,
+) -> AppResult<Vec<crate::metadata::collections::Collection>> {
+    let mut items = crate::metadata::collections::list(&db).await?;
+    for collection in items.iter_mut() {
+        collection.poster_path = resolve_poster_path(&app, collection.poster_path.take());
+    }
+    Ok(items)
+}
+
+#[tauri::command]
+pub async fn get_collection(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: i64,
+) -> AppResult<crate::metadata::collections::Collection> {
+    let mut collection = crate::metadata::collections::get(&db, id).await?;
+    collection.poster_path = resolve_poster_path(&app, collection.poster_path.take());
+    Ok(collection)
+}
+
+/// Plays the owned parts of a collection in release order, starting at
+/// the first unwatched one. Moves on only when a part was watched to the
+/// end, so quitting mpv stops the run.
+#[tauri::command]
+pub async fn play_collection(db: State<'_, Db>, id: i64) -> AppResult<player::PlayResult> {
+    let collection = crate::metadata::collections::get(&db, id).await?;
+    let owned: Vec<&crate::metadata::collections::CollectionPart> = collection
+        .parts
+        .iter()
+        .filter(|part| part.movie_id.is_some())
+        .collect();
+    let start = owned.iter().position(|part| !part.watched).unwrap_or(0);
+
+    let mut last = None;
+    for part in &owned[start..] {
+        let Some(movie_id) = part.movie_id else {
+            continue;
+        };
+        let movie = queries::get_movie(&db, movie_id).await?;
+        let result = player::play(&db, "movie", movie_id, &movie.path, movie.progress_seconds).await?;
+        last = Some(result);
+
+        if !queries::get_movie(&db, movie_id).await?.watched {
+            break;
+        }
+    }
+
+    last.ok_or_else(|| AppError::Other(format!("collection {id} has no owned parts")))
+}
+
+/// Cast, directors and writers of a movie or show.
+#[tauri::command]
+pub async fn list_title_people(
+    db: State<'_, Db>,
+    kind: String,
+    id: i64,
+) -> AppResult<Vec<crate::metadata::people::TitleCredit>> {
+    if !matches!(kind.as_str(), "movie" | "show") {
+        return Err(AppError::Other(format!("unknown kind: {kind}")));
+    }
+    crate::metadata::people::for_title(&db, &kind, id).await
+}
+
+/// Every movie and show in any library the person is credited on.
+#[tauri::command]
+pub async fn get_person_filmography(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: i64,
+) -> AppResult<crate::metadata::people::Filmography> {
+    let mut filmography = crate::metadata::people::filmography(&db, id).await?;
+    for entry in filmography.credits.iter_mut() {
+        entry.poster_path = resolve_poster_path(&app, entry.poster_path.take());
+    }
+    Ok(filmography)
+}
+
 #[tauri::command]
 pub async fn update_show_metadata(
     app: AppHandle,
     db: State<'_, Db>,
     id: i64,
-    title: Option<String>,
-    year: Option<i32>,
-    overview: Option<String>,
+    patch: MetadataPatch,
 ) -> AppResult<Show> {
-    queries::update_show_metadata(&db, id, title.as_deref(), year, overview.as_deref()).await?;
+    crate::metadata::manual::apply(&db, "show", id, &patch).await?;
     let mut show = queries::get_show(&db, id).await?;
-    show.poster_path = resolve_poster_path(&app, show.poster_path.take());
+    resolve_show_posters(&app, &mut show);
     Ok(show)
 }
 
@@ -184,13 +447,11 @@ pub async fn update_movie_metadata(
     app: AppHandle,
     db: State<'_, Db>,
     id: i64,
-    title: Option<String>,
-    year: Option<i32>,
-    overview: Option<String>,
+    patch: MetadataPatch,
 ) -> AppResult<Movie> {
-    queries::update_movie_metadata(&db, id, title.as_deref(), year, overview.as_deref()).await?;
+    crate::metadata::manual::apply(&db, "movie", id, &patch).await?;
     let mut movie = queries::get_movie(&db, id).await?;
-    movie.poster_path = resolve_poster_path(&app, movie.poster_path.take());
+    resolve_movie_posters(&app, &mut movie);
     Ok(movie)
 }
 
@@ -283,9 +544,20 @@ async fn on_setting_changed(
             }
             wake_worker(app);
         }
-        "scrape_language" => {
+        "scrape_language" if previous != next => {
+            // Titles already fetched in the new language switch over from
+            // stored text; only the rest go back to the providers.
+            let language = crate::metadata::locale::scrape_language(db).await?;
+            crate::metadata::locale::apply_language(db, &language).await?;
+            crate::metadata::locale::enqueue_missing(db, &language).await?;
             wake_worker(app);
         }
+        "metadata_refresh_days" | "metadata_refresh_continuing_days" if previous != next => {
+            let enqueued = crate::metadata::refresh::enqueue_stale(db).await?;
+            if enqueued > 0 {
+                wake_worker(app);
+            }
+        }
         _ => {}
     }
 
@@ -301,8 +573,11 @@ fn wake_worker(app: &AppHandle) {
 #[tauri::command]
 pub async fn metadata_status_counts(
     db: State<'_, Db>,
+    http: State<'_, crate::metadata::http::ProviderHttp>,
 ) -> AppResult<crate::models::MetadataStatusCounts> {
-    queries::metadata_status_counts(&db).await
+    let mut counts = queries::metadata_status_counts(&db).await?;
+    counts.rate_limits = http.rate_limit_status();
+    Ok(counts)
 }
 
 #[tauri::command]
@@ -336,27 +611,9 @@ pub async fn unlink_metadata(
     kind: String,
     id: i64,
 ) -> AppResult<()> {
-    let (table, extras) = match kind.as_str() {
-        "show" => ("shows", "first_air_date = NULL, "),
-        "movie" => ("movies", "runtime_minutes = NULL, "),
-        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
-    };
-
-    let sql = format!(
-        "UPDATE {table} SET
-             provider = NULL,
-             provider_id = NULL,
-             rating = NULL,
-             genres = NULL,
-             top_cast = NULL,
-             {extras}
-             metadata_synced_at = NULL,
-             metadata_locked = 0
-         WHERE id = ?1"
-    );
-
-    sqlx::query(&sql).bind(id).execute(&*db).await?;
-    crate::metadata::queries::force_enqueue(&db, &kind, id).await?;
+    let mut tx = db.begin().await?;
+    crate::metadata::queries::unlink_in_tx(&mut tx, &kind, id).await?;
+    tx.commit().await?;
     wake_worker(&app);
 
     Ok(())
@@ -365,12 +622,15 @@ pub async fn unlink_metadata(
 #[tauri::command]
 pub async fn metadata_search(
     db: State<'_, Db>,
-    http: State<'_, reqwest::Client>,
+    http: State<'_, crate::metadata::http::ProviderHttp>,
     kind: String,
     query: String,
     year: Option<i32>,
     provider: String,
 ) -> AppResult<Vec<crate::metadata::matching::MatchCandidate>> {
+    use crate::metadata::{imdb, tmdb};
+
+    let language = crate::metadata::locale::scrape_language(&db).await?;
     match provider.as_str() {
         "tmdb" => {
             let api_key = queries::get_app_setting(&db, "tmdb_api_key")
@@ -378,14 +638,14 @@ pub async fn metadata_search(
                 .ok_or_else(|| AppError::Other("no TMDB key configured".to_string()))?;
 
             match kind.as_str() {
-                "movie" => crate::metadata::tmdb::search_movie(&http, &api_key, &query, year).await,
-                "show" => crate::metadata::tmdb::search_show(&http, &api_key, &query, year).await,
+                "movie" => tmdb::search_movie(&http, &api_key, &query, year, &language).await,
+                "show" => tmdb::search_show(&http, &api_key, &query, year, &language).await,
                 other => Err(AppError::Other(format!("unknown kind: {other}"))),
             }
         }
         "imdb" => match kind.as_str() {
-            "movie" => crate::metadata::imdb::search_movie(&http, &query, year).await,
-            "show" => crate::metadata::imdb::search_show(&http, &query, year).await,
+            "movie" => imdb::search_movie(&http, &query, year, &language).await,
+            "show" => imdb::search_show(&http, &query, year, &language).await,
             other => Err(AppError::Other(format!("unknown kind: {other}"))),
         },
         other => Err(AppError::Other(format!("unknown provider: {other}"))),
@@ -401,30 +661,133 @@ pub async fn link_metadata(
     provider: String,
     provider_id: String,
 ) -> AppResult<()> {
-    if !matches!(provider.as_str(), "tmdb" | "imdb") {
-        return Err(AppError::Other(format!("unknown provider: {provider}")));
+    let mut tx = db.begin().await?;
+    crate::metadata::queries::link_in_tx(&mut tx, &kind, media_id, &provider, &provider_id)
+        .await?;
+    tx.commit().await?;
+    wake_worker(&app);
+
+    Ok(())
+}
+
+/// Diffs a candidate's metadata against the title without writing
+/// anything. With no candidate, previews a refresh of the current link.
+#[tauri::command]
+pub async fn preview_metadata(
+    app: AppHandle,
+    db: State<'_, Db>,
+    http: State<'_, crate::metadata::http::ProviderHttp>,
+    kind: String,
+    media_id: i64,
+    candidate: Option<preview::Candidate>,
+) -> AppResult<preview::Preview> {
+    let candidate = match candidate {
+        Some(candidate) => candidate,
+        None => preview::current_candidate(&db, &kind, media_id).await?,
+    };
+    let proposed = preview::fetch(&db, &http, &kind, media_id, &candidate).await?;
+    let mut fields = preview::diff(&db, &kind, media_id, &proposed).await?;
+    for field in fields.iter_mut().filter(|field| field.field == "poster") {
+        let stored = field.current.as_str().map(str::to_string);
+        field.current = serde_json::json!(resolve_poster_path(&app, stored));
     }
 
-    let table = match kind.as_str() {
-        "show" => "shows",
-        "movie" => "movies",
-        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+    Ok(preview::Preview {
+        kind,
+        media_id,
+        candidate,
+        fields,
+    })
+}
+
+/// Applies a previewed candidate. Without `fields` the title is linked
+/// to it like `link_metadata`; with them only those fields are written
+/// and the row is locked.
+#[tauri::command]
+pub async fn apply_metadata_preview(
+    app: AppHandle,
+    db: State<'_, Db>,
+    http: State<'_, crate::metadata::http::ProviderHttp>,
+    kind: String,
+    media_id: i64,
+    candidate: preview::Candidate,
+    fields: Option<Vec<String>>,
+) -> AppResult<()> {
+    let Some(fields) = fields else {
+        let mut tx = db.begin().await?;
+        crate::metadata::queries::link_in_tx(
+            &mut tx,
+            &kind,
+            media_id,
+            &candidate.provider,
+            &candidate.provider_id,
+        )
+        .await?;
+        tx.commit().await?;
+        wake_worker(&app);
+        return Ok(());
     };
 
-    sqlx::query(&format!(
-        "UPDATE {table} SET provider = ?2, provider_id = ?3, metadata_locked = 0
-         WHERE id = ?1"
-    ))
-    .bind(media_id)
-    .bind(&provider)
-    .bind(&provider_id)
-    .execute(&*db)
-    .await?;
+    let posters_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|error| AppError::Other(format!("app_data_dir: {error}")))?
+        .join("posters");
+    let proposed = preview::fetch(&db, &http, &kind, media_id, &candidate).await?;
+    preview::apply_fields(&db, &http, &posters_dir, &kind, media_id, &proposed, &fields).await
+}
 
-    crate::metadata::queries::force_enqueue(&db, &kind, media_id).await?;
+/// Links titles in bulk from a CSV or JSON mapping file. Every entry is
+/// validated first; the links are written and enqueued in one
+/// transaction only when none has a problem and `dry_run` is off.
+#[tauri::command]
+pub async fn import_metadata_links(
+    app: AppHandle,
+    db: State<'_, Db>,
+    path: String,
+    dry_run: bool,
+) -> AppResult<crate::metadata::link_import::ImportReport> {
+    let contents = tokio::fs::read_to_string(&path).await?;
+    let mappings = crate::metadata::link_import::parse_file(&path, &contents)?;
+    let report = crate::metadata::link_import::import(&db, &mappings, dry_run).await?;
+    if report.applied {
+        wake_worker(&app);
+    }
+    Ok(report)
+}
+
+/// A Needs-review match confirmed by the user.
+#[derive(Debug, serde::Deserialize)]
+pub struct ReviewApproval {
+    pub kind: String,
+    pub media_id: i64,
+    pub provider: String,
+    pub provider_id: String,
+}
+
+/// Links every approved item in one transaction — all or nothing.
+/// Returns the number of items linked.
+#[tauri::command]
+pub async fn approve_review_matches(
+    app: AppHandle,
+    db: State<'_, Db>,
+    approvals: Vec<ReviewApproval>,
+) -> AppResult<usize> {
+    let mut tx = db.begin().await?;
+    for approval in &approvals {
+        crate::metadata::queries::link_in_tx(
+            &mut tx,
+            &approval.kind,
+            approval.media_id,
+            &approval.provider,
+            &approval.provider_id,
+        )
+        .await?;
+    }
+    tx.commit().await?;
     wake_worker(&app);
 
-    Ok(())
+    Ok(approvals.len())
 }
 
 #[tauri::command]
@@ -434,6 +797,102 @@ pub async fn list_needs_review(
     queries::list_needs_review(&db).await
 }
 
+/// Failed and dead-lettered metadata jobs, with the title they belong to.
+#[tauri::command]
+pub async fn list_failed_metadata_jobs(
+    db: State<'_, Db>,
+) -> AppResult<Vec<crate::metadata::queries::FailedJob>> {
+    crate::metadata::queries::list_failed(&db).await
+}
+
+/// Resets attempts and backoff on the given jobs, or on every failed job
+/// when `jobs` is omitted. Returns the number of jobs reset.
+#[tauri::command]
+pub async fn retry_metadata_jobs(
+    app: AppHandle,
+    db: State<'_, Db>,
+    jobs: Option<Vec<crate::metadata::queries::JobRef>>,
+) -> AppResult<u64> {
+    let reset = crate::metadata::queries::retry_failed(&db, jobs.as_deref()).await?;
+    wake_worker(&app);
+    Ok(reset)
+}
+
+/// Gives up on the given jobs. Unlinked titles move to Needs-review.
+#[tauri::command]
+pub async fn dismiss_metadata_jobs(
+    app: AppHandle,
+    db: State<'_, Db>,
+    jobs: Vec<crate::metadata::queries::JobRef>,
+) -> AppResult<u64> {
+    let removed = crate::metadata::queries::dismiss(&db, &jobs).await?;
+    wake_worker(&app);
+    Ok(removed)
+}
+
+/// Deletes jobs whose show or movie no longer exists.
+#[tauri::command]
+pub async fn purge_orphaned_metadata_jobs(app: AppHandle, db: State<'_, Db>) -> AppResult<u64> {
+    let removed = crate::metadata::queries::purge_orphaned(&db).await?;
+    wake_worker(&app);
+    Ok(removed)
+}
+
+/// Recent provider attempts for one title, newest first — what was
+/// searched, how many candidates came back and which (if any) was picked.
+#[tauri::command]
+pub async fn metadata_job_log(
+    db: State<'_, Db>,
+    kind: String,
+    id: i64,
+    limit: Option<i64>,
+) -> AppResult<Vec<crate::metadata::job_log::JobLogEntry>> {
+    let limit = limit.unwrap_or(20).clamp(1, 200);
+    crate::metadata::job_log::list_for_media(&db, &kind, id, limit).await
+}
+
+/// Learned title aliases, alphabetical by normalized title.
+#[tauri::command]
+pub async fn list_title_aliases(
+    db: State<'_, Db>,
+) -> AppResult<Vec<crate::metadata::aliases::TitleAlias>> {
+    crate::metadata::aliases::list(&db).await
+}
+
+#[tauri::command]
+pub async fn update_title_alias(
+    db: State<'_, Db>,
+    id: i64,
+    provider: String,
+    provider_id: String,
+) -> AppResult<()> {
+    crate::metadata::aliases::update(&db, id, &provider, &provider_id).await
+}
+
+#[tauri::command]
+pub async fn delete_title_alias(db: State<'_, Db>, id: i64) -> AppResult<()> {
+    crate::metadata::aliases::delete(&db, id).await
+}
+
+/// Writes every alias to `path` as pretty-printed JSON. Returns the
+/// number of aliases written.
+#[tauri::command]
+pub async fn export_title_aliases(db: State<'_, Db>, path: String) -> AppResult<usize> {
+    let aliases = crate::metadata::aliases::list(&db).await?;
+    let json = serde_json::to_vec_pretty(&aliases)
+        .map_err(|error| AppError::Other(format!("serialize aliases: {error}")))?;
+    tokio::fs::write(&path, json).await?;
+
+    Ok(aliases.len())
+}
+
+/// Drops cached provider responses (all, or just `provider`) so the next
+/// sync goes back to the network. Returns the number of entries removed.
+#[tauri::command]
+pub async fn clear_http_cache(db: State<'_, Db>, provider: Option<String>) -> AppResult<u64> {
+    crate::metadata::http::clear_cache(&db, provider.as_deref()).await
+}
+
 #[tauri::command]
 pub async fn admin_list_rows(
     db: State<'_, Db>,
@@ -495,6 +954,22 @@ fn resolve_poster_path(app: &AppHandle, stored: Option<String>) -> Option<String
     Some(resolved.to_string_lossy().to_string())
 }
 
+fn resolve_movie_posters(app: &AppHandle, movie: &mut Movie) {
+    movie.poster_path = resolve_poster_path(app, movie.poster_path.take());
+    movie.poster_thumb_path = resolve_poster_path(app, movie.poster_thumb_path.take());
+    movie.poster_card_path = resolve_poster_path(app, movie.poster_card_path.take());
+    movie.poster_hero_path = resolve_poster_path(app, movie.poster_hero_path.take());
+    movie.backdrop_path = resolve_poster_path(app, movie.backdrop_path.take());
+}
+
+fn resolve_show_posters(app: &AppHandle, show: &mut Show) {
+    show.poster_path = resolve_poster_path(app, show.poster_path.take());
+    show.poster_thumb_path = resolve_poster_path(app, show.poster_thumb_path.take());
+    show.poster_card_path = resolve_poster_path(app, show.poster_card_path.take());
+    show.poster_hero_path = resolve_poster_path(app, show.poster_hero_path.take());
+    show.backdrop_path = resolve_poster_path(app, show.backdrop_path.take());
+}
+
 /// Best-effort cleanup of a manual poster file. Only deletes the file when
 /// it sits inside our own `<app_data>/posters/` directory — any other path
 /// is ignored so a malformed `poster_path` can never remove user media.
@@ -524,6 +999,20 @@ async fn try_remove_managed_poster(app: &AppHandle, poster_path: &str) {
     let _ = tokio::fs::remove_file(&canonical_candidate).await;
 }
 
+/// Renders the variants and blurhash of a poster [`copy_poster`] just
+/// stored. Best-effort: without them the UI shows the poster itself.
+async fn store_poster_variants(app: &AppHandle, db: &Db, kind: &str, id: i64, poster_path: &str) {
+    let posters_dir = match app.path().app_data_dir() {
+        Ok(dir) => dir.join("posters"),
+        Err(_) => return,
+    };
+    if let Err(error) =
+        crate::metadata::posters::store_variants(db, &posters_dir, kind, id, poster_path).await
+    {
+        eprintln!("poster variants for {kind} {id} failed: {error}");
+    }
+}
+
 #[tauri::command]
 pub async fn set_show_poster_from_file(
     app: AppHandle,
@@ -531,10 +1020,11 @@ pub async fn set_show_poster_from_file(
     id: i64,
     source_path: String,
 ) -> AppResult<Show> {
-    let destination = copy_poster(&app, "show", id, &source_path).await?;
+    let destination = copy_poster(&app, &format!("show-{id}"), &source_path).await?;
     queries::set_show_poster(&db, id, &destination, "manual").await?;
+    store_poster_variants(&app, &db, "show", id, &destination).await;
     let mut show = queries::get_show(&db, id).await?;
-    show.poster_path = resolve_poster_path(&app, show.poster_path.take());
+    resolve_show_posters(&app, &mut show);
     Ok(show)
 }
 
@@ -545,10 +1035,11 @@ pub async fn set_movie_poster_from_file(
     id: i64,
     source_path: String,
 ) -> AppResult<Movie> {
-    let destination = copy_poster(&app, "movie", id, &source_path).await?;
+    let destination = copy_poster(&app, &format!("movie-{id}"), &source_path).await?;
     queries::set_movie_poster(&db, id, &destination, "manual").await?;
+    store_poster_variants(&app, &db, "movie", id, &destination).await;
     let mut movie = queries::get_movie(&db, id).await?;
-    movie.poster_path = resolve_poster_path(&app, movie.poster_path.take());
+    resolve_movie_posters(&app, &mut movie);
     Ok(movie)
 }
 
@@ -556,7 +1047,7 @@ pub async fn set_movie_poster_from_file(
 pub async fn reset_show_poster(app: AppHandle, db: State<'_, Db>, id: i64) -> AppResult<Show> {
     queries::reset_show_poster(&db, id).await?;
     let mut show = queries::get_show(&db, id).await?;
-    show.poster_path = resolve_poster_path(&app, show.poster_path.take());
+    resolve_show_posters(&app, &mut show);
     Ok(show)
 }
 
@@ -564,20 +1055,59 @@ pub async fn reset_show_poster(app: AppHandle, db: State<'_, Db>, id: i64) -> Ap
 pub async fn reset_movie_poster(app: AppHandle, db: State<'_, Db>, id: i64) -> AppResult<Movie> {
     queries::reset_movie_poster(&db, id).await?;
     let mut movie = queries::get_movie(&db, id).await?;
-    movie.poster_path = resolve_poster_path(&app, movie.poster_path.take());
+    resolve_movie_posters(&app, &mut movie);
+    Ok(movie)
+}
+
+#[tauri::command]
+pub async fn set_show_backdrop_from_file(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: i64,
+    source_path: String,
+) -> AppResult<Show> {
+    let destination = copy_poster(&app, &format!("show-{id}-backdrop"), &source_path).await?;
+    queries::set_show_backdrop(&db, id, &destination).await?;
+    let mut show = queries::get_show(&db, id).await?;
+    resolve_show_posters(&app, &mut show);
+    Ok(show)
+}
+
+#[tauri::command]
+pub async fn set_movie_backdrop_from_file(
+    app: AppHandle,
+    db: State<'_, Db>,
+    id: i64,
+    source_path: String,
+) -> AppResult<Movie> {
+    let destination = copy_poster(&app, &format!("movie-{id}-backdrop"), &source_path).await?;
+    queries::set_movie_backdrop(&db, id, &destination).await?;
+    let mut movie = queries::get_movie(&db, id).await?;
+    resolve_movie_posters(&app, &mut movie);
     Ok(movie)
 }
 
-/// Copy `source_path` into `<app_data>/posters/{kind}-{id}.{ext}` and return
+#[tauri::command]
+pub async fn reset_show_backdrop(app: AppHandle, db: State<'_, Db>, id: i64) -> AppResult<Show> {
+    queries::reset_show_backdrop(&db, id).await?;
+    let mut show = queries::get_show(&db, id).await?;
+    resolve_show_posters(&app, &mut show);
+    Ok(show)
+}
+
+#[tauri::command]
+pub async fn reset_movie_backdrop(app: AppHandle, db: State<'_, Db>, id: i64) -> AppResult<Movie> {
+    queries::reset_movie_backdrop(&db, id).await?;
+    let mut movie = queries::get_movie(&db, id).await?;
+    resolve_movie_posters(&app, &mut movie);
+    Ok(movie)
+}
+
+/// Copy `source_path` into `<app_data>/posters/{stem}.{ext}` and return
 /// the destination path as a string. Rejects sources whose extension isn't
 /// in [`ALLOWED_POSTER_EXTS`] so we don't accidentally accept arbitrary
 /// files.
-async fn copy_poster(
-    app: &AppHandle,
-    kind: &str,
-    id: i64,
-    source_path: &str,
-) -> AppResult<String> {
+async fn copy_poster(app: &AppHandle, stem: &str, source_path: &str) -> AppResult<String> {
     let source = Path::new(source_path);
     if !source.exists() {
         return Err(AppError::Other(format!(
@@ -603,7 +1133,7 @@ async fn copy_poster(
     let poster_dir = app_data_dir.join("posters");
     tokio::fs::create_dir_all(&poster_dir).await?;
 
-    let destination = poster_dir.join(format!("{kind}-{id}.{extension}"));
+    let destination = poster_dir.join(format!("{stem}.{extension}"));
     tokio::fs::copy(source, &destination).await?;
 
     Ok(destination.to_string_lossy().to_string())
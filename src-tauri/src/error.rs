@@ -24,6 +24,12 @@ pub enum AppError {
     #[error("mpv not available on PATH")]
     MpvMissing,
 
+    #[error("blocked by parental controls")]
+    ParentalBlocked,
+
+    #[error("incorrect PIN")]
+    WrongPin,
+
     #[error("{0}")]
     Other(String),
 }
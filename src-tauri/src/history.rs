@@ -0,0 +1,265 @@
+//! Playback sessions. `player::play` appends one row per mpv run, so
+//! unlike `watch_history` — one mutable row per item — the log can say
+//! what was watched on a given day and how often a title was played.
+//! Date ranges are inclusive `YYYY-MM-DD` local dates, matched against
+//! when the session started.
+
+use sqlx::SqlitePool;
+
+use crate::error::AppResult;
+use crate::parental;
+
+/// One finished mpv run, as `player::play` saw it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewSession {
+    pub media_kind: &'static str,
+    pub media_id: i64,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub start_position_seconds: i64,
+    pub end_position_seconds: i64,
+    pub duration_seconds: Option<i64>,
+    pub completed: bool,
+    pub exit_status: Option<i32>,
+}
+
+/// A logged session with the title it played. Titles removed from the
+/// library since have no `title`.
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct PlaybackSession {
+    pub id: i64,
+    pub media_kind: String,
+    pub media_id: i64,
+    pub title: Option<String>,
+    pub show_id: Option<i64>,
+    pub show_title: Option<String>,
+    pub season: Option<i64>,
+    pub episode: Option<i64>,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub start_position_seconds: i64,
+    pub end_position_seconds: i64,
+    pub duration_seconds: Option<i64>,
+    pub completed: bool,
+    pub exit_status: Option<i64>,
+}
+
+/// How often one movie or episode was played.
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct PlayCount {
+    pub media_kind: String,
+    pub media_id: i64,
+    pub title: Option<String>,
+    pub show_title: Option<String>,
+    pub season: Option<i64>,
+    pub episode: Option<i64>,
+    pub plays: i64,
+    pub completed_plays: i64,
+    pub last_played_at: i64,
+}
+
+pub async fn record(pool: &SqlitePool, session: &NewSession) -> AppResult<i64> {
+    let id = sqlx::query_scalar(
+        "INSERT INTO playback_sessions
+             (media_kind, media_id, started_at, ended_at, start_position_seconds,
+              end_position_seconds, duration_seconds, completed, exit_status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         RETURNING id",
+    )
+    .bind(session.media_kind)
+    .bind(session.media_id)
+    .bind(session.started_at)
+    .bind(session.ended_at)
+    .bind(session.start_position_seconds)
+    .bind(session.end_position_seconds)
+    .bind(session.duration_seconds)
+    .bind(session.completed)
+    .bind(session.exit_status)
+    .fetch_one(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Joins a session `p` to its movie `m`, or episode `e` and show `s`.
+pub const TITLE_JOINS: &str = "LEFT JOIN movies m ON p.media_kind = 'movie' AND m.id = p.media_id
+     LEFT JOIN episodes e ON p.media_kind = 'episode' AND e.id = p.media_id
+     LEFT JOIN shows s ON s.id = e.show_id";
+
+/// Sessions of titles parental controls allow that started between
+/// `from` and `to` (either end open when `None`), bound as `?1` and
+/// `?2`.
+pub fn filter_clause() -> String {
+    format!(
+        "((p.media_kind = 'movie' AND {movie}) OR (p.media_kind = 'episode' AND {show}))
+         AND (?1 IS NULL OR date(p.started_at, 'unixepoch', 'localtime') >= ?1)
+         AND (?2 IS NULL OR date(p.started_at, 'unixepoch', 'localtime') <= ?2)",
+        movie = parental::visible_clause("movie", "m"),
+        show = parental::visible_clause("show", "s"),
+    )
+}
+
+/// Sessions started between `from` and `to`, newest first.
+pub async fn list(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<Vec<PlaybackSession>> {
+    let sessions = sqlx::query_as(&format!(
+        "SELECT p.id, p.media_kind, p.media_id, COALESCE(m.title, e.title) AS title,
+                s.id AS show_id, s.title AS show_title, e.season, e.episode,
+                p.started_at, p.ended_at, p.start_position_seconds, p.end_position_seconds,
+                p.duration_seconds, p.completed, p.exit_status
+         FROM playback_sessions p
+         {TITLE_JOINS}
+         WHERE {}
+         ORDER BY p.started_at DESC, p.id DESC",
+        filter_clause()
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(sessions)
+}
+
+/// Plays per movie and episode between `from` and `to`, most played
+/// first.
+pub async fn play_counts(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<Vec<PlayCount>> {
+    let counts = sqlx::query_as(&format!(
+        "SELECT p.media_kind, p.media_id, COALESCE(m.title, e.title) AS title,
+                s.title AS show_title, e.season, e.episode,
+                COUNT(*) AS plays, SUM(p.completed) AS completed_plays,
+                MAX(p.started_at) AS last_played_at
+         FROM playback_sessions p
+         {TITLE_JOINS}
+         WHERE {}
+         GROUP BY p.media_kind, p.media_id
+         ORDER BY plays DESC, last_played_at DESC",
+        filter_clause()
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    /// Unix time of local noon on `date`, so the local date is `date`.
+    async fn noon(pool: &SqlitePool, date: &str) -> i64 {
+        sqlx::query_scalar("SELECT CAST(strftime('%s', ?1 || ' 12:00:00', 'utc') AS INTEGER)")
+            .bind(date)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn session(media_kind: &'static str, media_id: i64, started_at: i64) -> NewSession {
+        NewSession {
+            media_kind,
+            media_id,
+            started_at,
+            ended_at: started_at + 3600,
+            start_position_seconds: 0,
+            end_position_seconds: 3600,
+            duration_seconds: Some(3700),
+            completed: true,
+            exit_status: Some(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn sessions_are_listed_by_date_and_counted_per_title() {
+        let pool = fresh_pool().await;
+        sqlx::query(
+            "INSERT INTO libraries (id, path, kind) VALUES
+                 (1, '/m', 'movies'),
+                 (2, '/tv', 'series')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path)
+             VALUES (1, 1, 'Heat', '/m/Heat.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO shows (id, library_id, title, folder_path, fingerprint)
+             VALUES (1, 2, 'Lost', '/tv/Lost', 'a')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO episodes (id, show_id, season, episode, title, path)
+             VALUES (7, 1, 1, 2, 'Tabula Rasa', '/tv/Lost/s01e02.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let friday = noon(&pool, "2024-03-01").await;
+        let saturday = noon(&pool, "2024-03-02").await;
+        let sunday = noon(&pool, "2024-03-03").await;
+        record(&pool, &session("movie", 1, friday)).await.unwrap();
+        record(&pool, &session("episode", 7, saturday)).await.unwrap();
+        let stopped = NewSession {
+            completed: false,
+            end_position_seconds: 600,
+            exit_status: Some(4),
+            ..session("movie", 1, sunday)
+        };
+        record(&pool, &stopped).await.unwrap();
+
+        let weekend = list(&pool, Some("2024-03-02"), Some("2024-03-03")).await.unwrap();
+        let seen: Vec<(&str, Option<&str>, Option<&str>)> = weekend
+            .iter()
+            .map(|session| {
+                (
+                    session.media_kind.as_str(),
+                    session.title.as_deref(),
+                    session.show_title.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            seen,
+            vec![("movie", Some("Heat"), None), ("episode", Some("Tabula Rasa"), Some("Lost"))]
+        );
+        assert_eq!(weekend[0].exit_status, Some(4));
+        assert!(!weekend[0].completed);
+        assert_eq!(list(&pool, None, None).await.unwrap().len(), 3);
+
+        let counts = play_counts(&pool, None, None).await.unwrap();
+        let tallies: Vec<(i64, i64, i64)> = counts
+            .iter()
+            .map(|count| (count.media_id, count.plays, count.completed_plays))
+            .collect();
+        assert_eq!(tallies, vec![(1, 2, 1), (7, 1, 1)]);
+        assert_eq!(counts[0].last_played_at, sunday);
+    }
+}
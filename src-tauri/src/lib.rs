@@ -2,11 +2,14 @@ mod admin;
 mod commands;
 mod db;
 mod error;
+mod history;
 mod metadata;
 mod models;
+mod parental;
 mod player;
 mod queries;
 mod scanner;
+mod stats;
 
 use tauri::Manager;
 
@@ -30,11 +33,17 @@ pub fn run() {
                 .user_agent(concat!("rustflix/", env!("CARGO_PKG_VERSION")))
                 .build()
                 .expect("failed to build reqwest client");
-            app.manage(http_client.clone());
-
             let pool_for_worker = app.state::<db::Db>().inner().clone();
-            let notify =
-                metadata::worker::spawn(pool_for_worker, http_client, app.handle().clone());
+            let provider_http =
+                metadata::http::ProviderHttp::new(http_client, pool_for_worker.clone())
+                    .with_endpoints(metadata::http::Endpoints::from_env());
+            app.manage(provider_http.clone());
+
+            let notify = metadata::worker::spawn(
+                pool_for_worker,
+                provider_http,
+                app_data_dir.join("posters"),
+            );
             app.manage(notify);
 
             Ok(())
@@ -48,6 +57,10 @@ pub fn run() {
             commands::get_movie,
             commands::list_shows,
             commands::get_show,
+            commands::list_genres,
+            commands::list_title_ratings,
+            commands::list_rating_sources,
+            commands::list_external_ids,
             commands::get_seasons,
             commands::get_episode,
             commands::continue_watching,
@@ -55,6 +68,19 @@ pub fn run() {
             commands::check_mpv,
             commands::play_movie,
             commands::play_episode,
+            commands::list_playback_history,
+            commands::list_play_counts,
+            commands::viewing_stats,
+            commands::show_calendar,
+            commands::list_collections,
+            commands::get_collection,
+            commands::play_collection,
+            commands::get_parental_controls,
+            commands::set_parental_controls,
+            commands::disable_parental_controls,
+            commands::list_certifications,
+            commands::list_title_people,
+            commands::get_person_filmography,
             commands::update_show_metadata,
             commands::update_movie_metadata,
             commands::update_episode_title,
@@ -64,6 +90,10 @@ pub fn run() {
             commands::set_movie_poster_from_file,
             commands::reset_show_poster,
             commands::reset_movie_poster,
+            commands::set_show_backdrop_from_file,
+            commands::set_movie_backdrop_from_file,
+            commands::reset_show_backdrop,
+            commands::reset_movie_backdrop,
             commands::get_app_setting,
             commands::set_app_setting,
             commands::metadata_status_counts,
@@ -71,7 +101,22 @@ pub fn run() {
             commands::unlink_metadata,
             commands::metadata_search,
             commands::link_metadata,
+            commands::preview_metadata,
+            commands::apply_metadata_preview,
+            commands::import_metadata_links,
             commands::list_needs_review,
+            commands::approve_review_matches,
+            commands::metadata_job_log,
+            commands::list_failed_metadata_jobs,
+            commands::retry_metadata_jobs,
+            commands::dismiss_metadata_jobs,
+            commands::purge_orphaned_metadata_jobs,
+            commands::clear_http_cache,
+            commands::run_poster_maintenance,
+            commands::list_title_aliases,
+            commands::update_title_alias,
+            commands::delete_title_alias,
+            commands::export_title_aliases,
             commands::admin_list_rows,
             commands::admin_update_row,
             commands::admin_delete_rows,
@@ -0,0 +1,349 @@
+//! Airing details: when a show ended, where it airs and which episodes
+//! are coming. Applied payloads write the columns on `shows` and rewrite
+//! the show's `show_air_dates`; `calendar` reads them back against the
+//! library. Only TMDB lists networks and episode air dates; IMDB gives
+//! the end year alone.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::AppResult;
+use crate::metadata::imdb::TitleNode;
+use crate::metadata::tmdb::{TmdbEpisodeAir, TmdbShowDetails};
+use crate::parental;
+
+/// Days back `calendar` looks for aired episodes missing from the library.
+pub const DEFAULT_RECENT_DAYS: i64 = 30;
+
+/// Airing details as parsed from a provider payload.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Airing {
+    pub end_year: Option<i32>,
+    pub networks: Vec<String>,
+    pub next_episode_air_date: Option<String>,
+    pub episodes: Vec<AirDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AirDate {
+    pub season: i64,
+    pub episode: i64,
+    pub title: Option<String>,
+    /// `YYYY-MM-DD`.
+    pub air_date: String,
+}
+
+/// One calendar line: an episode of a library show and when it airs.
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct CalendarEntry {
+    pub show_id: i64,
+    pub show_title: String,
+    pub poster_path: Option<String>,
+    pub season: i64,
+    pub episode: i64,
+    pub title: Option<String>,
+    pub air_date: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct Calendar {
+    /// Airing today or later, soonest first.
+    pub upcoming: Vec<CalendarEntry>,
+    /// Aired in the last days but not in the library, newest first.
+    pub missing: Vec<CalendarEntry>,
+}
+
+pub fn from_tmdb(details: &TmdbShowDetails) -> Airing {
+    let end_year = if details.is_airing() {
+        None
+    } else {
+        details
+            .last_air_date
+            .as_deref()
+            .and_then(|date| date.get(0..4))
+            .and_then(|year| year.parse().ok())
+    };
+
+    let mut episodes: Vec<AirDate> = details
+        .last_episode_to_air
+        .iter()
+        .chain(details.next_episode_to_air.iter())
+        .chain(details.season_episodes.iter())
+        .filter_map(air_date)
+        .collect();
+    episodes.sort_by_key(|episode| (episode.season, episode.episode));
+    episodes.dedup_by_key(|episode| (episode.season, episode.episode));
+
+    Airing {
+        end_year,
+        networks: details.networks.iter().map(|network| network.name.clone()).collect(),
+        next_episode_air_date: details
+            .next_episode_to_air
+            .as_ref()
+            .and_then(|episode| episode.air_date.clone())
+            .filter(|date| !date.is_empty()),
+        episodes,
+    }
+}
+
+pub fn from_imdb(details: &TitleNode) -> Airing {
+    Airing {
+        end_year: details.release_year.as_ref().and_then(|release| release.end_year),
+        ..Airing::default()
+    }
+}
+
+fn air_date(episode: &TmdbEpisodeAir) -> Option<AirDate> {
+    let date = episode.air_date.as_deref().filter(|date| !date.is_empty())?;
+    Some(AirDate {
+        season: episode.season_number,
+        episode: episode.episode_number,
+        title: episode.name.clone().filter(|name| !name.is_empty()),
+        air_date: date.to_string(),
+    })
+}
+
+/// Writes the show's airing columns and replaces its air dates.
+pub async fn store_in_tx(
+    conn: &mut SqliteConnection,
+    show_id: i64,
+    airing: &Airing,
+) -> AppResult<()> {
+    let networks = (!airing.networks.is_empty())
+        .then(|| serde_json::to_string(&airing.networks).unwrap_or_else(|_| "[]".to_string()));
+    sqlx::query(
+        "UPDATE shows SET end_year = ?1, networks = ?2, next_episode_air_date = ?3
+         WHERE id = ?4",
+    )
+    .bind(airing.end_year)
+    .bind(networks)
+    .bind(airing.next_episode_air_date.as_deref())
+    .bind(show_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM show_air_dates WHERE show_id = ?1")
+        .bind(show_id)
+        .execute(&mut *conn)
+        .await?;
+    for episode in &airing.episodes {
+        sqlx::query(
+            "INSERT INTO show_air_dates (show_id, season, episode, title, air_date)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(show_id)
+        .bind(episode.season)
+        .bind(episode.episode)
+        .bind(episode.title.as_deref())
+        .bind(&episode.air_date)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Upcoming episodes of library shows, and the ones that aired in the
+/// last `recent_days` without a file in the library. `today` is a
+/// `YYYY-MM-DD` date, the local date when `None`.
+pub async fn calendar(
+    pool: &SqlitePool,
+    today: Option<&str>,
+    recent_days: i64,
+) -> AppResult<Calendar> {
+    let select = format!(
+        "SELECT a.show_id, s.title AS show_title,
+                COALESCE(s.poster_thumb_path, s.poster_path) AS poster_path,
+                a.season, a.episode, a.title, a.air_date
+         FROM show_air_dates a
+         JOIN shows s ON s.id = a.show_id
+         WHERE {}",
+        parental::visible_clause("show", "s")
+    );
+
+    let upcoming = sqlx::query_as(&format!(
+        "{select} AND a.air_date >= COALESCE(?1, date('now', 'localtime'))
+         ORDER BY a.air_date, s.title COLLATE NOCASE, a.season, a.episode"
+    ))
+    .bind(today)
+    .fetch_all(pool)
+    .await?;
+
+    let missing = sqlx::query_as(&format!(
+        "{select}
+           AND a.air_date < COALESCE(?1, date('now', 'localtime'))
+           AND a.air_date >= date(COALESCE(?1, date('now', 'localtime')), ?2)
+           AND NOT EXISTS (
+             SELECT 1 FROM episodes e
+             WHERE e.show_id = a.show_id AND e.season = a.season AND e.episode = a.episode
+           )
+         ORDER BY a.air_date DESC, s.title COLLATE NOCASE, a.season, a.episode"
+    ))
+    .bind(today)
+    .bind(format!("-{} days", recent_days.max(0)))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Calendar { upcoming, missing })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn show_details(json: &str) -> TmdbShowDetails {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn tmdb_airing_show_lists_networks_and_air_dates() {
+        let mut details = show_details(
+            r#"{
+                "id": 1, "name": "Drama", "overview": null, "first_air_date": "2022-01-01",
+                "status": "Returning Series", "last_air_date": "2024-03-03",
+                "vote_average": 8.0, "poster_path": null, "genres": [], "credits": null,
+                "networks": [{ "name": "HBO" }, { "name": "Max" }],
+                "last_episode_to_air": {
+                    "season_number": 2, "episode_number": 3, "name": "Three",
+                    "air_date": "2024-03-03"
+                },
+                "next_episode_to_air": {
+                    "season_number": 2, "episode_number": 4, "name": "Four",
+                    "air_date": "2024-03-10"
+                }
+            }"#,
+        );
+        details.season_episodes = vec![
+            TmdbEpisodeAir {
+                season_number: 2,
+                episode_number: 5,
+                name: Some("Five".into()),
+                air_date: Some("2024-03-17".into()),
+            },
+            TmdbEpisodeAir {
+                season_number: 2,
+                episode_number: 6,
+                name: None,
+                air_date: None,
+            },
+            TmdbEpisodeAir {
+                season_number: 2,
+                episode_number: 4,
+                name: Some("Four".into()),
+                air_date: Some("2024-03-10".into()),
+            },
+        ];
+
+        let airing = from_tmdb(&details);
+        assert_eq!(airing.end_year, None);
+        assert_eq!(airing.networks, vec!["HBO", "Max"]);
+        assert_eq!(airing.next_episode_air_date.as_deref(), Some("2024-03-10"));
+        let numbers: Vec<(i64, i64)> =
+            airing.episodes.iter().map(|episode| (episode.season, episode.episode)).collect();
+        assert_eq!(numbers, vec![(2, 3), (2, 4), (2, 5)]);
+    }
+
+    #[test]
+    fn tmdb_ended_show_takes_its_end_year_from_the_last_air_date() {
+        let details = show_details(
+            r#"{
+                "id": 1, "name": "Old", "overview": null, "first_air_date": "2008-01-20",
+                "status": "Ended", "last_air_date": "2013-09-29",
+                "vote_average": null, "poster_path": null, "genres": [], "credits": null
+            }"#,
+        );
+        let airing = from_tmdb(&details);
+        assert_eq!(airing.end_year, Some(2013));
+        assert!(airing.networks.is_empty());
+        assert!(airing.episodes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn calendar_lists_upcoming_and_missing_episodes() {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/tv', 'series')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO shows (id, library_id, title, folder_path, fingerprint) VALUES
+                 (1, 1, 'Drama', '/tv/Drama', 'a'),
+                 (2, 1, 'Comedy', '/tv/Comedy', 'b')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO episodes (show_id, season, episode, title, path)
+             VALUES (1, 2, 2, 'Two', '/tv/Drama/s02e02.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let drama = Airing {
+            end_year: None,
+            networks: vec!["HBO".into()],
+            next_episode_air_date: Some("2024-03-10".into()),
+            episodes: [(1, "2024-01-01"), (2, "2024-02-25"), (3, "2024-03-03"), (4, "2024-03-10")]
+                .into_iter()
+                .map(|(episode, date)| AirDate {
+                    season: 2,
+                    episode,
+                    title: None,
+                    air_date: date.into(),
+                })
+                .collect(),
+        };
+        store_in_tx(&mut conn, 1, &drama).await.unwrap();
+        let comedy = Airing {
+            next_episode_air_date: Some("2024-03-05".into()),
+            episodes: vec![AirDate {
+                season: 1,
+                episode: 1,
+                title: Some("Pilot".into()),
+                air_date: "2024-03-05".into(),
+            }],
+            ..Airing::default()
+        };
+        store_in_tx(&mut conn, 2, &comedy).await.unwrap();
+        drop(conn);
+
+        let calendar = calendar(&pool, Some("2024-03-05"), 30).await.unwrap();
+        let upcoming: Vec<(i64, i64, &str)> = calendar
+            .upcoming
+            .iter()
+            .map(|entry| (entry.show_id, entry.episode, entry.air_date.as_str()))
+            .collect();
+        assert_eq!(upcoming, vec![(2, 1, "2024-03-05"), (1, 4, "2024-03-10")]);
+        // Episode 2 is in the library; episode 1 aired too long ago.
+        let missing: Vec<(i64, i64)> = calendar
+            .missing
+            .iter()
+            .map(|entry| (entry.show_id, entry.episode))
+            .collect();
+        assert_eq!(missing, vec![(1, 3)]);
+
+        let (networks, next): (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT networks, next_episode_air_date FROM shows WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(networks.as_deref(), Some(r#"["HBO"]"#));
+        assert_eq!(next.as_deref(), Some("2024-03-10"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Learned title aliases. Linking a title by hand records
+//! `(kind, normalized scanned title, year) → (provider, provider_id)` in
+//! `title_aliases`; the worker consults it before searching, so a title
+//! the user already corrected never goes back through fuzzy matching.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::dispatch::Provider;
+use crate::metadata::matching;
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct TitleAlias {
+    pub id: i64,
+    pub kind: String,
+    pub normalized_title: String,
+    pub year: Option<i32>,
+    pub provider: String,
+    pub provider_id: String,
+    pub source_title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Records (or repoints) the alias for a scanned title. The latest link
+/// wins: linking the same title again overwrites the previous target.
+pub async fn record_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    title: &str,
+    year: Option<i32>,
+    provider: &str,
+    provider_id: &str,
+) -> AppResult<()> {
+    let normalized = matching::normalize(title);
+    if normalized.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "INSERT INTO title_aliases
+             (kind, normalized_title, year, provider, provider_id, source_title)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT DO UPDATE SET
+             provider = excluded.provider,
+             provider_id = excluded.provider_id,
+             source_title = excluded.source_title,
+             updated_at = strftime('%s','now')",
+    )
+    .bind(kind)
+    .bind(&normalized)
+    .bind(year)
+    .bind(provider)
+    .bind(provider_id)
+    .bind(title)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Forgets the alias for a scanned title, so a wrong hand link doesn't
+/// come back on the next fetch.
+pub async fn forget_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    title: &str,
+    year: Option<i32>,
+) -> AppResult<()> {
+    sqlx::query(
+        "DELETE FROM title_aliases WHERE kind = ?1 AND normalized_title = ?2 AND year IS ?3",
+    )
+    .bind(kind)
+    .bind(matching::normalize(title))
+    .bind(year)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// The provider id a scanned title is aliased to, if the alias points at
+/// `provider`. Aliases for the other provider are left to that
+/// provider's turn in the walk.
+pub async fn lookup(
+    pool: &SqlitePool,
+    kind: &str,
+    title: &str,
+    year: Option<i32>,
+    provider: Provider,
+) -> AppResult<Option<String>> {
+    let provider_id: Option<String> = sqlx::query_scalar(
+        "SELECT provider_id FROM title_aliases
+         WHERE kind = ?1 AND normalized_title = ?2 AND year IS ?3 AND provider = ?4",
+    )
+    .bind(kind)
+    .bind(matching::normalize(title))
+    .bind(year)
+    .bind(provider.as_str())
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(provider_id)
+}
+
+pub async fn list(pool: &SqlitePool) -> AppResult<Vec<TitleAlias>> {
+    let aliases: Vec<TitleAlias> = sqlx::query_as(
+        "SELECT id, kind, normalized_title, year, provider, provider_id, source_title,
+                created_at, updated_at
+         FROM title_aliases
+         ORDER BY normalized_title COLLATE NOCASE, year, kind",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(aliases)
+}
+
+/// Repoints an alias at a different provider id.
+pub async fn update(
+    pool: &SqlitePool,
+    id: i64,
+    provider: &str,
+    provider_id: &str,
+) -> AppResult<()> {
+    if !matches!(provider, "tmdb" | "imdb") {
+        return Err(AppError::Other(format!("unknown provider: {provider}")));
+    }
+    let provider_id = provider_id.trim();
+    if provider_id.is_empty() {
+        return Err(AppError::Other("provider id must not be empty".into()));
+    }
+
+    let result = sqlx::query(
+        "UPDATE title_aliases
+         SET provider = ?2, provider_id = ?3, updated_at = strftime('%s','now')
+         WHERE id = ?1",
+    )
+    .bind(id)
+    .bind(provider)
+    .bind(provider_id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::Other(format!("no alias with id {id}")));
+    }
+    Ok(())
+}
+
+pub async fn delete(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    sqlx::query("DELETE FROM title_aliases WHERE id = ?1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    async fn record(pool: &SqlitePool, title: &str, year: Option<i32>, provider: &str, id: &str) {
+        let mut conn = pool.acquire().await.unwrap();
+        record_in_tx(&mut conn, "movie", title, year, provider, id)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn lookup_matches_normalized_title_year_and_provider() {
+        let pool = fresh_pool().await;
+        record(&pool, "The Matrix", Some(1999), "tmdb", "603").await;
+
+        let hit = lookup(&pool, "movie", "matrix", Some(1999), Provider::Tmdb).await.unwrap();
+        assert_eq!(hit.as_deref(), Some("603"));
+
+        let other_year = lookup(&pool, "movie", "The Matrix", Some(2021), Provider::Tmdb)
+            .await
+            .unwrap();
+        assert_eq!(other_year, None);
+
+        let other_provider = lookup(&pool, "movie", "The Matrix", Some(1999), Provider::Imdb)
+            .await
+            .unwrap();
+        assert_eq!(other_provider, None);
+
+        let other_kind = lookup(&pool, "show", "The Matrix", Some(1999), Provider::Tmdb)
+            .await
+            .unwrap();
+        assert_eq!(other_kind, None);
+    }
+
+    #[tokio::test]
+    async fn relinking_overwrites_including_yearless_titles() {
+        let pool = fresh_pool().await;
+        record(&pool, "Heat", None, "tmdb", "1").await;
+        record(&pool, "Heat", None, "imdb", "tt0113277").await;
+        record(&pool, "Heat", Some(1995), "tmdb", "949").await;
+
+        let aliases = list(&pool).await.unwrap();
+        assert_eq!(aliases.len(), 2);
+
+        let yearless = lookup(&pool, "movie", "Heat", None, Provider::Imdb).await.unwrap();
+        assert_eq!(yearless.as_deref(), Some("tt0113277"));
+        assert_eq!(lookup(&pool, "movie", "Heat", None, Provider::Tmdb).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn update_validates_and_delete_removes() {
+        let pool = fresh_pool().await;
+        record(&pool, "Heat", Some(1995), "tmdb", "1").await;
+        let id = list(&pool).await.unwrap()[0].id;
+
+        assert!(update(&pool, id, "netflix", "1").await.is_err());
+        assert!(update(&pool, id, "tmdb", "  ").await.is_err());
+        update(&pool, id, "tmdb", " 949 ").await.unwrap();
+        let hit = lookup(&pool, "movie", "Heat", Some(1995), Provider::Tmdb).await.unwrap();
+        assert_eq!(hit.as_deref(), Some("949"));
+
+        delete(&pool, id).await.unwrap();
+        assert!(list(&pool).await.unwrap().is_empty());
+    }
+}
@@ -5,6 +5,7 @@
 use sqlx::SqliteConnection;
 
 use crate::error::AppResult;
+use crate::metadata::{airing, certifications, external_ids, genres, ratings};
 use crate::metadata::tmdb::{TmdbCastMember, TmdbMovieDetails, TmdbShowDetails};
 
 /// Apply a fetched movie payload onto an existing `movies` row. Returns
@@ -25,10 +26,8 @@ pub async fn apply_movie_details(
     let download_extension =
         compute_poster_extension(current_poster_origin.as_deref(), details.poster_path.as_deref());
 
-    let genres_json = serde_json::to_string(
-        &details.genres.iter().map(|g| g.name.clone()).collect::<Vec<_>>(),
-    )
-    .unwrap_or_else(|_| "[]".to_string());
+    let genre_names: Vec<String> = details.genres.iter().map(|g| g.name.clone()).collect();
+    let genres_json = serde_json::to_string(&genre_names).unwrap_or_else(|_| "[]".to_string());
     let cast_json = build_cast_json(details.credits.as_ref().map(|c| &c.cast));
     let year = parse_year(details.release_date.as_deref());
 
@@ -86,6 +85,14 @@ pub async fn apply_movie_details(
         .await?;
     }
 
+    genres::store_in_tx(&mut *conn, "movie", movie_id, "tmdb", &genre_names).await?;
+    certifications::store_in_tx(&mut *conn, "movie", movie_id, &certifications::from_tmdb_movie(details))
+        .await?;
+    let rating = ratings::from_tmdb(details.vote_average, details.vote_count);
+    ratings::store_in_tx(&mut *conn, "movie", movie_id, "tmdb", rating).await?;
+    let ids = external_ids::from_tmdb_movie(details);
+    external_ids::store_in_tx(&mut *conn, "movie", movie_id, &ids).await?;
+
     Ok(download_extension)
 }
 
@@ -103,10 +110,8 @@ pub async fn apply_show_details(
     let download_extension =
         compute_poster_extension(current_poster_origin.as_deref(), details.poster_path.as_deref());
 
-    let genres_json = serde_json::to_string(
-        &details.genres.iter().map(|g| g.name.clone()).collect::<Vec<_>>(),
-    )
-    .unwrap_or_else(|_| "[]".to_string());
+    let genre_names: Vec<String> = details.genres.iter().map(|g| g.name.clone()).collect();
+    let genres_json = serde_json::to_string(&genre_names).unwrap_or_else(|_| "[]".to_string());
     let cast_json = build_cast_json(details.credits.as_ref().map(|c| &c.cast));
     let year = parse_year(details.first_air_date.as_deref());
 
@@ -124,6 +129,7 @@ pub async fn apply_show_details(
                  first_air_date = ?7,
                  poster_path = ?8,
                  poster_origin = 'tmdb',
+                 status = COALESCE(?10, status),
                  metadata_synced_at = strftime('%s','now')
              WHERE id = ?9",
         )
@@ -136,6 +142,7 @@ pub async fn apply_show_details(
         .bind(details.first_air_date.as_deref())
         .bind(&local_path)
         .bind(show_id)
+        .bind(details.status.as_deref())
         .execute(&mut *conn)
         .await?;
     } else {
@@ -149,6 +156,7 @@ pub async fn apply_show_details(
                  genres = ?5,
                  top_cast = ?6,
                  first_air_date = ?7,
+                 status = COALESCE(?9, status),
                  metadata_synced_at = strftime('%s','now')
              WHERE id = ?8",
         )
@@ -160,14 +168,24 @@ pub async fn apply_show_details(
         .bind(&cast_json)
         .bind(details.first_air_date.as_deref())
         .bind(show_id)
+        .bind(details.status.as_deref())
         .execute(&mut *conn)
         .await?;
     }
 
+    genres::store_in_tx(&mut *conn, "show", show_id, "tmdb", &genre_names).await?;
+    certifications::store_in_tx(&mut *conn, "show", show_id, &certifications::from_tmdb_show(details))
+        .await?;
+    airing::store_in_tx(&mut *conn, show_id, &airing::from_tmdb(details)).await?;
+    let rating = ratings::from_tmdb(details.vote_average, details.vote_count);
+    ratings::store_in_tx(&mut *conn, "show", show_id, "tmdb", rating).await?;
+    let ids = external_ids::from_tmdb_show(details);
+    external_ids::store_in_tx(&mut *conn, "show", show_id, &ids).await?;
+
     Ok(download_extension)
 }
 
-fn build_cast_json(cast: Option<&Vec<TmdbCastMember>>) -> String {
+pub fn build_cast_json(cast: Option<&Vec<TmdbCastMember>>) -> String {
     let trimmed: Vec<_> = cast
         .map(|members| members.iter().take(10).collect())
         .unwrap_or_default();
@@ -235,20 +253,12 @@ pub async fn apply_imdb_movie_details(
         .and_then(|text| text.plain_text.as_deref());
     let year = details.release_year.as_ref().and_then(|release| release.year);
     let rating = imdb_rating(&details.ratings_summary);
-    let genres_json = serde_json::to_string(
-        &details
-            .genres
-            .as_ref()
-            .map(|wrapper| {
-                wrapper
-                    .genres
-                    .iter()
-                    .map(|genre| genre.text.clone())
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default(),
-    )
-    .unwrap_or_else(|_| "[]".to_string());
+    let genre_names: Vec<String> = details
+        .genres
+        .as_ref()
+        .map(|wrapper| wrapper.genres.iter().map(|genre| genre.text.clone()).collect())
+        .unwrap_or_default();
+    let genres_json = serde_json::to_string(&genre_names).unwrap_or_else(|_| "[]".to_string());
     let cast_json = build_imdb_cast_json(&details.principal_credits);
     let runtime_minutes = details
         .runtime
@@ -309,6 +319,14 @@ pub async fn apply_imdb_movie_details(
         .await?;
     }
 
+    genres::store_in_tx(&mut *conn, "movie", movie_id, "imdb", &genre_names).await?;
+    certifications::store_in_tx(&mut *conn, "movie", movie_id, &certifications::from_imdb(details))
+        .await?;
+    let rating = ratings::from_imdb(details.ratings_summary.as_ref());
+    ratings::store_in_tx(&mut *conn, "movie", movie_id, "imdb", rating).await?;
+    let ids = external_ids::from_imdb(details);
+    external_ids::store_in_tx(&mut *conn, "movie", movie_id, &ids).await?;
+
     Ok(download_target)
 }
 
@@ -337,20 +355,12 @@ pub async fn apply_imdb_show_details(
         .and_then(|text| text.plain_text.as_deref());
     let year = details.release_year.as_ref().and_then(|release| release.year);
     let rating = imdb_rating(&details.ratings_summary);
-    let genres_json = serde_json::to_string(
-        &details
-            .genres
-            .as_ref()
-            .map(|wrapper| {
-                wrapper
-                    .genres
-                    .iter()
-                    .map(|genre| genre.text.clone())
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default(),
-    )
-    .unwrap_or_else(|_| "[]".to_string());
+    let genre_names: Vec<String> = details
+        .genres
+        .as_ref()
+        .map(|wrapper| wrapper.genres.iter().map(|genre| genre.text.clone()).collect())
+        .unwrap_or_default();
+    let genres_json = serde_json::to_string(&genre_names).unwrap_or_else(|_| "[]".to_string());
     let cast_json = build_imdb_cast_json(&details.principal_credits);
     let first_air_date = details.release_date.as_ref().and_then(|date| {
         match (date.year, date.month, date.day) {
@@ -358,6 +368,7 @@ pub async fn apply_imdb_show_details(
             _ => None,
         }
     });
+    let status = imdb_show_status(details);
 
     if let Some((_size, _url, local_path)) = download_target.as_ref() {
         sqlx::query(
@@ -372,6 +383,7 @@ pub async fn apply_imdb_show_details(
                  first_air_date = COALESCE(?7, first_air_date),
                  poster_path = ?8,
                  poster_origin = 'imdb',
+                 status = ?10,
                  metadata_synced_at = strftime('%s','now')
              WHERE id = ?9",
         )
@@ -384,6 +396,7 @@ pub async fn apply_imdb_show_details(
         .bind(first_air_date.as_deref())
         .bind(local_path)
         .bind(show_id)
+        .bind(status)
         .execute(&mut *conn)
         .await?;
     } else {
@@ -397,6 +410,7 @@ pub async fn apply_imdb_show_details(
                  genres = ?5,
                  top_cast = ?6,
                  first_air_date = COALESCE(?7, first_air_date),
+                 status = ?9,
                  metadata_synced_at = strftime('%s','now')
              WHERE id = ?8",
         )
@@ -408,13 +422,33 @@ pub async fn apply_imdb_show_details(
         .bind(&cast_json)
         .bind(first_air_date.as_deref())
         .bind(show_id)
+        .bind(status)
         .execute(&mut *conn)
         .await?;
     }
 
+    genres::store_in_tx(&mut *conn, "show", show_id, "imdb", &genre_names).await?;
+    certifications::store_in_tx(&mut *conn, "show", show_id, &certifications::from_imdb(details))
+        .await?;
+    airing::store_in_tx(&mut *conn, show_id, &airing::from_imdb(details)).await?;
+    let rating = ratings::from_imdb(details.ratings_summary.as_ref());
+    ratings::store_in_tx(&mut *conn, "show", show_id, "imdb", rating).await?;
+    let ids = external_ids::from_imdb(details);
+    external_ids::store_in_tx(&mut *conn, "show", show_id, &ids).await?;
+
     Ok(download_target)
 }
 
+/// IMDB has no status field; a series with an `endYear` has ended, one
+/// without is still running. Uses TMDB's wording so the refresh
+/// scheduler can treat both providers alike.
+fn imdb_show_status(details: &TitleNode) -> &'static str {
+    match details.release_year.as_ref().and_then(|release| release.end_year) {
+        Some(_) => "Ended",
+        None => "Returning Series",
+    }
+}
+
 fn imdb_rating(ratings: &Option<RatingsNode>) -> Option<f64> {
     let ratings = ratings.as_ref()?;
     let votes = ratings.vote_count.unwrap_or(0);
@@ -427,7 +461,7 @@ fn imdb_rating(ratings: &Option<RatingsNode>) -> Option<f64> {
     ratings.aggregate_rating
 }
 
-fn build_imdb_cast_json(credits: &[PrincipalCredits]) -> String {
+pub fn build_imdb_cast_json(credits: &[PrincipalCredits]) -> String {
     // Match on category.id == "cast" (stable lowercase id). The response's
     // category.text is the plural display name ("Stars") and would not match.
     let cast_block = credits.iter().find(|block| block.category.id == "cast");
@@ -0,0 +1,187 @@
+//! Backdrops: wide artwork behind the detail page header. TMDB names one
+//! per title (`backdrop_path`); IMDB has none as such, so the widest of
+//! its still frames stands in. Stored and downloaded like posters, and
+//! with the same rule: sync never replaces a manual backdrop.
+
+use std::path::Path;
+
+use sqlx::SqliteConnection;
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::http::ProviderHttp;
+use crate::metadata::imdb::{self, PosterSize, TitleNode};
+use crate::metadata::tmdb;
+use crate::metadata::worker::PosterDownload;
+
+/// TMDB width requested for backdrops.
+const TMDB_SIZE: &str = "w1280";
+
+/// The first landscape still frame in the IMDB details.
+pub fn from_imdb(details: &TitleNode) -> Option<&str> {
+    details
+        .images
+        .as_ref()?
+        .edges
+        .iter()
+        .map(|edge| &edge.node)
+        .find(|image| matches!((image.width, image.height), (Some(w), Some(h)) if w > h))
+        .map(|image| image.url.as_str())
+}
+
+/// Points the title at the provider's backdrop, unless it has a manual
+/// one or the provider has none. Returns the download for the worker
+/// to run once the transaction commits.
+pub async fn apply_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    provider: &str,
+    source: Option<&str>,
+) -> AppResult<Option<PosterDownload>> {
+    let table = match kind {
+        "movie" => "movies",
+        "show" => "shows",
+        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+    };
+    let Some(source) = source else {
+        return Ok(None);
+    };
+
+    let origin: Option<String> =
+        sqlx::query_scalar(&format!("SELECT backdrop_origin FROM {table} WHERE id = ?1"))
+            .bind(media_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if origin.as_deref() == Some("manual") {
+        return Ok(None);
+    }
+
+    let extension = match provider {
+        "tmdb" => Path::new(source)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("jpg")
+            .to_lowercase(),
+        _ => "jpg".to_string(),
+    };
+    let filename = format!("{kind}-{media_id}-backdrop.{extension}");
+    sqlx::query(&format!(
+        "UPDATE {table} SET backdrop_path = ?1, backdrop_origin = ?2 WHERE id = ?3"
+    ))
+    .bind(&filename)
+    .bind(provider)
+    .bind(media_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Some(PosterDownload {
+        url: source.to_string(),
+        filename,
+        size: (provider == "imdb").then_some(PosterSize::Hero),
+    }))
+}
+
+/// Downloads a backdrop into `posters_dir`.
+pub async fn download(
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    download: &PosterDownload,
+) -> AppResult<()> {
+    let dest = posters_dir.join(&download.filename);
+    match download.size {
+        None => tmdb::download_poster_at(http, &download.url, TMDB_SIZE, &dest).await,
+        Some(size) => imdb::download_poster(http, &download.url, &dest, size).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    #[test]
+    fn imdb_backdrop_is_the_first_landscape_still() {
+        let details: TitleNode = serde_json::from_str(
+            r#"{
+                "id": "tt0133093",
+                "images": { "edges": [
+                    { "node": { "url": "http://img/tall.jpg", "width": 1000, "height": 1500 } },
+                    { "node": { "url": "http://img/unsized.jpg" } },
+                    { "node": { "url": "http://img/wide.jpg", "width": 3000, "height": 1250 } },
+                    { "node": { "url": "http://img/later.jpg", "width": 1920, "height": 1080 } }
+                ] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(from_imdb(&details), Some("http://img/wide.jpg"));
+
+        let bare: TitleNode = serde_json::from_str(r#"{ "id": "tt1" }"#).unwrap();
+        assert_eq!(from_imdb(&bare), None);
+    }
+
+    #[tokio::test]
+    async fn sync_never_replaces_a_manual_backdrop() {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path) VALUES
+                 (1, 1, 'Synced', '/movies/1.mkv'),
+                 (2, 1, 'Manual', '/movies/2.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        crate::queries::set_movie_backdrop(&pool, 2, "/data/posters/movie-2-backdrop.png")
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let download = apply_in_tx(&mut conn, "movie", 1, "tmdb", Some("/abc.JPG"))
+            .await
+            .unwrap()
+            .expect("download");
+        assert_eq!(download.filename, "movie-1-backdrop.jpg");
+        assert!(download.size.is_none());
+        let skipped = apply_in_tx(&mut conn, "movie", 2, "imdb", Some("http://img/wide.jpg"))
+            .await
+            .unwrap();
+        assert!(skipped.is_none());
+        drop(conn);
+
+        let rows: Vec<(Option<String>, Option<String>)> =
+            sqlx::query_as("SELECT backdrop_path, backdrop_origin FROM movies ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows[0], (Some("movie-1-backdrop.jpg".into()), Some("tmdb".into())));
+        assert_eq!(
+            rows[1],
+            (Some("/data/posters/movie-2-backdrop.png".into()), Some("manual".into()))
+        );
+
+        crate::queries::reset_movie_backdrop(&pool, 2).await.unwrap();
+        let reset: (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT backdrop_path, backdrop_origin FROM movies WHERE id = 2")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(reset, (None, None));
+    }
+}
@@ -0,0 +1,252 @@
+//! Age certifications ("PG-13", "FSK 16", …). Both providers return
+//! certifications for every country at once, so `store_in_tx` keeps all
+//! of them; `certification_country` only picks which one is shown and
+//! checked by parental controls, and changing it needs no re-fetch.
+//! Each row carries the minimum age its certification implies, which is
+//! what parental controls compare against.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::AppResult;
+use crate::metadata::imdb::TitleNode;
+use crate::metadata::queries::PRIORITY_REFRESH;
+use crate::metadata::tmdb::{TmdbMovieDetails, TmdbShowDetails};
+use crate::queries as app_queries;
+
+/// Country used when `certification_country` is unset.
+pub const DEFAULT_COUNTRY: &str = "US";
+
+/// One country's certification for a title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certification {
+    pub country: String,
+    pub certification: String,
+}
+
+/// Certifications a country's boards use, with the minimum age each
+/// implies, youngest first. Countries not listed fall back to the age
+/// number in the certification itself (`FSK 16`, `-12`, `K-7`, …).
+pub fn known_ratings(country: &str) -> &'static [(&'static str, i64)] {
+    match country {
+        "US" => &[
+            ("G", 0),
+            ("TV-Y", 0),
+            ("TV-G", 0),
+            ("TV-Y7", 7),
+            ("PG", 10),
+            ("TV-PG", 10),
+            ("PG-13", 13),
+            ("TV-14", 14),
+            ("R", 17),
+            ("TV-MA", 17),
+            ("NC-17", 18),
+        ],
+        "GB" => &[("U", 0), ("PG", 8), ("12A", 12), ("12", 12), ("15", 15), ("18", 18), ("R18", 18)],
+        "AU" => &[
+            ("G", 0),
+            ("PG", 8),
+            ("M", 15),
+            ("MA15+", 15),
+            ("R18+", 18),
+            ("X18+", 18),
+        ],
+        "DE" => &[("0", 0), ("6", 6), ("12", 12), ("16", 16), ("18", 18)],
+        "FR" => &[("U", 0), ("10", 10), ("12", 12), ("16", 16), ("18", 18)],
+        _ => &[],
+    }
+}
+
+/// Minimum age for a certification in `country`; `None` for "not
+/// rated" and anything unrecognised.
+pub fn min_age(country: &str, certification: &str) -> Option<i64> {
+    let certification = certification.trim();
+    if let Some((_, age)) = known_ratings(country)
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(certification))
+    {
+        return Some(*age);
+    }
+
+    let upper = certification.to_ascii_uppercase();
+    if matches!(upper.as_str(), "U" | "G" | "ALL" | "AL" | "TP" | "BTL") {
+        return Some(0);
+    }
+    let digits: String = certification
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok().filter(|age| *age <= 21)
+}
+
+/// `certification_country`, falling back to `DEFAULT_COUNTRY`.
+pub async fn country(pool: &SqlitePool) -> AppResult<String> {
+    let stored = app_queries::get_app_setting(pool, "certification_country").await?;
+    Ok(stored
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_COUNTRY.to_string()))
+}
+
+/// TMDB `release_dates`: per country, the theatrical release's
+/// certification, else the first release that has one.
+pub fn from_tmdb_movie(details: &TmdbMovieDetails) -> Vec<Certification> {
+    let Some(release_dates) = details.release_dates.as_ref() else {
+        return Vec::new();
+    };
+
+    release_dates
+        .results
+        .iter()
+        .filter_map(|country| {
+            let rated = || {
+                country
+                    .release_dates
+                    .iter()
+                    .filter(|release| !release.certification.trim().is_empty())
+            };
+            let pick = rated()
+                .find(|release| release.release_type == Some(3))
+                .or_else(|| rated().next())?;
+            Some(Certification {
+                country: country.iso_3166_1.clone(),
+                certification: pick.certification.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// TMDB `content_ratings`, one per country.
+pub fn from_tmdb_show(details: &TmdbShowDetails) -> Vec<Certification> {
+    let Some(content_ratings) = details.content_ratings.as_ref() else {
+        return Vec::new();
+    };
+
+    content_ratings
+        .results
+        .iter()
+        .filter(|rating| !rating.rating.trim().is_empty())
+        .map(|rating| Certification {
+            country: rating.iso_3166_1.clone(),
+            certification: rating.rating.trim().to_string(),
+        })
+        .collect()
+}
+
+/// IMDB `certificates`; the first one listed per country wins.
+pub fn from_imdb(details: &TitleNode) -> Vec<Certification> {
+    let mut out: Vec<Certification> = Vec::new();
+    let edges = details
+        .certificates
+        .as_ref()
+        .map(|connection| connection.edges.as_slice())
+        .unwrap_or_default();
+
+    for edge in edges {
+        let Some(country) = edge.node.country.as_ref() else {
+            continue;
+        };
+        let rating = edge.node.rating.trim();
+        if rating.is_empty() || out.iter().any(|seen| seen.country == country.id) {
+            continue;
+        }
+        out.push(Certification {
+            country: country.id.clone(),
+            certification: rating.to_string(),
+        });
+    }
+
+    out
+}
+
+/// Replaces a title's certifications.
+pub async fn store_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    certifications: &[Certification],
+) -> AppResult<()> {
+    sqlx::query("DELETE FROM certifications WHERE kind = ?1 AND media_id = ?2")
+        .bind(kind)
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for certification in certifications {
+        sqlx::query(
+            "INSERT OR IGNORE INTO certifications
+                 (kind, media_id, country, certification, min_age)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(kind)
+        .bind(media_id)
+        .bind(&certification.country)
+        .bind(&certification.certification)
+        .bind(min_age(&certification.country, &certification.certification))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Queues a low-priority refresh for every linked, unlocked title synced
+/// before certifications were stored. Returns the number of jobs added.
+pub async fn enqueue_missing(pool: &SqlitePool) -> AppResult<u64> {
+    let mut inserted = 0;
+
+    for (kind, table) in [("movie", "movies"), ("show", "shows")] {
+        inserted += sqlx::query(&format!(
+            "INSERT INTO metadata_jobs (kind, media_id, priority)
+             SELECT ?1, id, ?2 FROM {table}
+             WHERE provider IS NOT NULL
+               AND metadata_locked = 0
+               AND NOT EXISTS (
+                 SELECT 1 FROM certifications c WHERE c.kind = ?1 AND c.media_id = {table}.id
+               )
+             ON CONFLICT(kind, media_id) DO NOTHING"
+        ))
+        .bind(kind)
+        .bind(PRIORITY_REFRESH)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_age_uses_country_tables_then_the_number() {
+        assert_eq!(min_age("US", "PG-13"), Some(13));
+        assert_eq!(min_age("US", "tv-ma"), Some(17));
+        assert_eq!(min_age("US", "NR"), None);
+        assert_eq!(min_age("GB", "12A"), Some(12));
+        assert_eq!(min_age("DE", "16"), Some(16));
+        assert_eq!(min_age("SE", "Btl"), Some(0));
+        assert_eq!(min_age("IN", "A"), None);
+        assert_eq!(min_age("SE", "15"), Some(15));
+        assert_eq!(min_age("NL", "AL"), Some(0));
+        assert_eq!(min_age("KR", "K-7"), Some(7));
+        assert_eq!(min_age("FI", "K-16"), Some(16));
+    }
+
+    #[test]
+    fn tmdb_movie_prefers_the_theatrical_certification() {
+        let details: TmdbMovieDetails =
+            serde_json::from_str(include_str!("../../tests/fixtures/tmdb-movie-details.json"))
+                .unwrap();
+        let certifications = from_tmdb_movie(&details);
+        assert_eq!(
+            certifications,
+            vec![
+                Certification { country: "US".into(), certification: "R".into() },
+                Certification { country: "DE".into(), certification: "16".into() },
+            ]
+        );
+    }
+}
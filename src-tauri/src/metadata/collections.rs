@@ -0,0 +1,277 @@
+//! Movie collections (franchises). TMDB movie details name the
+//! collection a movie belongs to; the worker fetches the collection's
+//! full part list and stores it here, so the library can show which
+//! parts are owned and which are missing. IMDB exposes no collection
+//! data, so IMDB-linked movies only join a collection through TMDB.
+
+use std::collections::HashMap;
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::tmdb::TmdbCollection;
+use crate::parental;
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub overview: Option<String>,
+    pub poster_path: Option<String>,
+    pub part_count: i64,
+    pub owned_count: i64,
+    /// Every part, owned or not, in release order.
+    #[sqlx(skip)]
+    pub parts: Vec<CollectionPart>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct CollectionPart {
+    #[serde(skip)]
+    pub collection_id: i64,
+    pub provider_id: String,
+    pub title: String,
+    pub release_date: Option<String>,
+    pub poster_url: Option<String>,
+    pub overview: Option<String>,
+    /// The library movie for this part; `None` when it's missing.
+    pub movie_id: Option<i64>,
+    pub watched: bool,
+}
+
+/// Upserts a TMDB collection and replaces its part list. Returns the
+/// collection's row id and whether it still needs a poster downloaded.
+pub async fn store_tmdb_in_tx(
+    conn: &mut SqliteConnection,
+    collection: &TmdbCollection,
+    image_base: &str,
+) -> AppResult<(i64, bool)> {
+    let (id, poster_path): (i64, Option<String>) = sqlx::query_as(
+        "INSERT INTO collections (provider, provider_id, name, overview)
+         VALUES ('tmdb', ?1, ?2, ?3)
+         ON CONFLICT(provider, provider_id) DO UPDATE SET
+             name = excluded.name,
+             overview = COALESCE(excluded.overview, overview),
+             updated_at = strftime('%s','now')
+         RETURNING id, poster_path",
+    )
+    .bind(collection.id.to_string())
+    .bind(&collection.name)
+    .bind(collection.overview.as_deref().filter(|text| !text.is_empty()))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM collection_parts WHERE collection_id = ?1")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+
+    for part in &collection.parts {
+        sqlx::query(
+            "INSERT INTO collection_parts
+                 (collection_id, provider_id, title, release_date, poster_url, overview)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(collection_id, provider_id) DO NOTHING",
+        )
+        .bind(id)
+        .bind(part.id.to_string())
+        .bind(&part.title)
+        .bind(part.release_date.as_deref().filter(|date| !date.is_empty()))
+        .bind(part.poster_path.as_ref().map(|path| format!("{image_base}{path}")))
+        .bind(part.overview.as_deref().filter(|text| !text.is_empty()))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok((id, poster_path.is_none() && collection.poster_path.is_some()))
+}
+
+pub async fn set_poster(pool: &SqlitePool, id: i64, poster_path: &str) -> AppResult<()> {
+    sqlx::query("UPDATE collections SET poster_path = ?2 WHERE id = ?1")
+        .bind(id)
+        .bind(poster_path)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Movie `m` is a copy of part `p` of collection `c`: linked to the
+/// collection's provider under the part's id, or linked elsewhere (an
+/// IMDB link) with that id known from `external_ids`.
+const OWNS_PART: &str = "((m.provider = c.provider AND m.provider_id = p.provider_id)
+       OR m.id IN (SELECT x.media_id FROM external_ids x
+                    WHERE x.kind = 'movie' AND x.source = c.provider
+                      AND x.external_id = p.provider_id))";
+
+/// Parts whose movie parental controls hide count as missing.
+fn collection_select() -> String {
+    format!(
+        "SELECT c.id, c.name, c.overview, c.poster_path,
+                (SELECT COUNT(*) FROM collection_parts p WHERE p.collection_id = c.id) AS part_count,
+                (SELECT COUNT(*) FROM collection_parts p
+                  WHERE p.collection_id = c.id
+                    AND EXISTS (SELECT 1 FROM movies m WHERE {OWNS_PART} AND {visible})
+                ) AS owned_count
+         FROM collections c",
+        visible = parental::visible_clause("movie", "m"),
+    )
+}
+
+fn part_select() -> String {
+    format!(
+        "SELECT p.collection_id, p.provider_id, p.title, p.release_date, p.poster_url, p.overview,
+                (SELECT MIN(m.id) FROM movies m WHERE {OWNS_PART} AND {visible}) AS movie_id,
+                COALESCE((SELECT MAX(w.watched) FROM movies m
+                            JOIN watch_history w ON w.media_kind = 'movie' AND w.media_id = m.id
+                           WHERE {OWNS_PART}), 0)
+                  AS watched
+         FROM collection_parts p
+         JOIN collections c ON c.id = p.collection_id",
+        visible = parental::visible_clause("movie", "m"),
+    )
+}
+
+/// Collections with at least one owned part, by name.
+pub async fn list(pool: &SqlitePool) -> AppResult<Vec<Collection>> {
+    let sql = format!("{} ORDER BY c.name COLLATE NOCASE", collection_select());
+    let mut collections: Vec<Collection> = sqlx::query_as(&sql).fetch_all(pool).await?;
+    collections.retain(|collection| collection.owned_count > 0);
+
+    let sql = format!(
+        "{} ORDER BY p.collection_id, p.release_date IS NULL, p.release_date, p.title",
+        part_select()
+    );
+    let parts: Vec<CollectionPart> = sqlx::query_as(&sql).fetch_all(pool).await?;
+    let mut by_collection: HashMap<i64, Vec<CollectionPart>> = HashMap::new();
+    for part in parts {
+        by_collection.entry(part.collection_id).or_default().push(part);
+    }
+    for collection in collections.iter_mut() {
+        collection.parts = by_collection.remove(&collection.id).unwrap_or_default();
+    }
+
+    Ok(collections)
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> AppResult<Collection> {
+    let sql = format!("{} WHERE c.id = ?1", collection_select());
+    let mut collection: Collection = sqlx::query_as(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::Other(format!("collection not found: {id}")))?;
+
+    let sql = format!(
+        "{} WHERE p.collection_id = ?1
+         ORDER BY p.release_date IS NULL, p.release_date, p.title",
+        part_select()
+    );
+    collection.parts = sqlx::query_as(&sql).bind(id).fetch_all(pool).await?;
+
+    Ok(collection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::tmdb::TmdbCollectionPart;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn part(id: i64, title: &str, release_date: &str) -> TmdbCollectionPart {
+        TmdbCollectionPart {
+            id,
+            title: title.to_string(),
+            overview: None,
+            release_date: Some(release_date.to_string()),
+            poster_path: Some(format!("/{id}.jpg")),
+        }
+    }
+
+    fn matrix() -> TmdbCollection {
+        TmdbCollection {
+            id: 2344,
+            name: "The Matrix Collection".to_string(),
+            overview: Some("Wachowskis".to_string()),
+            poster_path: Some("/collection.jpg".to_string()),
+            parts: vec![
+                part(624860, "The Matrix Resurrections", "2021-12-16"),
+                part(603, "The Matrix", "1999-03-30"),
+                part(604, "The Matrix Reloaded", "2003-05-15"),
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn parts_are_listed_in_release_order_with_ownership() {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/tmp', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path, provider, provider_id)
+             VALUES (10, 1, 'The Matrix', '/tmp/a.mkv', 'tmdb', '603'),
+                    (11, 1, 'Resurrections', '/tmp/b.mkv', 'tmdb', '624860'),
+                    (12, 1, 'Reloaded', '/tmp/c.mkv', 'imdb', 'tt0234215')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        // The IMDB-linked copy owns its part through its stored TMDB id.
+        sqlx::query(
+            "INSERT INTO external_ids (kind, media_id, source, external_id)
+             VALUES ('movie', 12, 'tmdb', '604')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO watch_history (media_kind, media_id, watched) VALUES ('movie', 10, 1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let (id, needs_poster) = store_tmdb_in_tx(&mut conn, &matrix(), "http://img").await.unwrap();
+        assert!(needs_poster);
+        drop(conn);
+        set_poster(&pool, id, "collection-1.jpg").await.unwrap();
+
+        // Storing again replaces the part list and keeps the poster.
+        let mut conn = pool.acquire().await.unwrap();
+        let (again, needs_poster) =
+            store_tmdb_in_tx(&mut conn, &matrix(), "http://img").await.unwrap();
+        assert_eq!(again, id);
+        assert!(!needs_poster);
+        drop(conn);
+
+        let collections = list(&pool).await.unwrap();
+        assert_eq!(collections.len(), 1);
+        let collection = &collections[0];
+        assert_eq!((collection.owned_count, collection.part_count), (3, 3));
+        let order: Vec<(&str, Option<i64>, bool)> = collection
+            .parts
+            .iter()
+            .map(|part| (part.provider_id.as_str(), part.movie_id, part.watched))
+            .collect();
+        assert_eq!(
+            order,
+            vec![("603", Some(10), true), ("604", Some(12), false), ("624860", Some(11), false)]
+        );
+        assert_eq!(collection.parts[1].poster_url.as_deref(), Some("http://img/604.jpg"));
+        assert_eq!(get(&pool, id).await.unwrap().parts.len(), 3);
+    }
+}
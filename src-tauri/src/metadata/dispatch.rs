@@ -13,6 +13,16 @@ pub enum Provider {
     Imdb,
 }
 
+impl Provider {
+    /// The value stored in `provider` columns.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Provider::Tmdb => "tmdb",
+            Provider::Imdb => "imdb",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ParkReason {
     TmdbAuthRequired,
@@ -0,0 +1,201 @@
+//! Cross-provider ids. A row links to one provider through
+//! `provider`/`provider_id`; `external_ids` keeps every id known for the
+//! title: the linked provider's own, what TMDB lists under
+//! `external_ids`, and the TMDB id found for an IMDB-linked title. The
+//! worker fetches by these ids instead of searching when a title isn't
+//! linked to the provider it's trying.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::AppResult;
+use crate::metadata::imdb::TitleNode;
+use crate::metadata::tmdb::{TmdbExternalIds, TmdbMovieDetails, TmdbShowDetails};
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct ExternalId {
+    pub source: String,
+    pub external_id: String,
+}
+
+impl ExternalId {
+    fn new(source: &str, external_id: impl Into<String>) -> Self {
+        Self {
+            source: source.to_string(),
+            external_id: external_id.into(),
+        }
+    }
+}
+
+pub fn from_tmdb_movie(details: &TmdbMovieDetails) -> Vec<ExternalId> {
+    let mut ids = from_tmdb(details.id, details.external_ids.as_ref());
+    if !ids.iter().any(|id| id.source == "imdb") {
+        if let Some(imdb_id) = details.imdb_id.as_deref().filter(|id| !id.is_empty()) {
+            ids.push(ExternalId::new("imdb", imdb_id));
+        }
+    }
+    ids
+}
+
+pub fn from_tmdb_show(details: &TmdbShowDetails) -> Vec<ExternalId> {
+    from_tmdb(details.id, details.external_ids.as_ref())
+}
+
+fn from_tmdb(tmdb_id: i64, external: Option<&TmdbExternalIds>) -> Vec<ExternalId> {
+    let mut ids = vec![ExternalId::new("tmdb", tmdb_id.to_string())];
+    let Some(external) = external else {
+        return ids;
+    };
+    if let Some(imdb_id) = external.imdb_id.as_deref().filter(|id| !id.is_empty()) {
+        ids.push(ExternalId::new("imdb", imdb_id));
+    }
+    if let Some(tvdb_id) = external.tvdb_id.filter(|id| *id > 0) {
+        ids.push(ExternalId::new("tvdb", tvdb_id.to_string()));
+    }
+    if let Some(wikidata_id) = external.wikidata_id.as_deref().filter(|id| !id.is_empty()) {
+        ids.push(ExternalId::new("wikidata", wikidata_id));
+    }
+    ids
+}
+
+/// IMDB's title API doesn't cross-reference other databases.
+pub fn from_imdb(details: &TitleNode) -> Vec<ExternalId> {
+    vec![ExternalId::new("imdb", details.id.as_str())]
+}
+
+/// Writes `ids` for the title. Sources the payload doesn't mention keep
+/// their id; `clear_in_tx` drops them all when the link changes.
+pub async fn store_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    ids: &[ExternalId],
+) -> AppResult<()> {
+    for id in ids {
+        sqlx::query(
+            "INSERT INTO external_ids (kind, media_id, source, external_id)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(kind, media_id, source) DO UPDATE SET
+                 external_id = excluded.external_id,
+                 updated_at = strftime('%s','now')",
+        )
+        .bind(kind)
+        .bind(media_id)
+        .bind(&id.source)
+        .bind(&id.external_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn store(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    ids: &[ExternalId],
+) -> AppResult<()> {
+    let mut conn = pool.acquire().await?;
+    store_in_tx(&mut conn, kind, media_id, ids).await
+}
+
+pub async fn clear_in_tx(conn: &mut SqliteConnection, kind: &str, media_id: i64) -> AppResult<()> {
+    sqlx::query("DELETE FROM external_ids WHERE kind = ?1 AND media_id = ?2")
+        .bind(kind)
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// The title's id in `source`, if known.
+pub async fn lookup(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    source: &str,
+) -> AppResult<Option<String>> {
+    let id = sqlx::query_scalar(
+        "SELECT external_id FROM external_ids
+         WHERE kind = ?1 AND media_id = ?2 AND source = ?3",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .bind(source)
+    .fetch_optional(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Every known id for a title, by source.
+pub async fn for_title(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<Vec<ExternalId>> {
+    let ids = sqlx::query_as(
+        "SELECT source, external_id FROM external_ids
+         WHERE kind = ?1 AND media_id = ?2
+         ORDER BY source",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    #[test]
+    fn tmdb_show_lists_its_external_ids() {
+        let details: TmdbShowDetails = serde_json::from_str(
+            r#"{
+                "id": 1399, "name": "Game of Thrones", "overview": null,
+                "first_air_date": "2011-04-17", "vote_average": 8.4, "poster_path": null,
+                "genres": [], "credits": null,
+                "external_ids": { "imdb_id": "tt0944947", "tvdb_id": 121361, "wikidata_id": "" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            from_tmdb_show(&details),
+            vec![
+                ExternalId::new("tmdb", "1399"),
+                ExternalId::new("imdb", "tt0944947"),
+                ExternalId::new("tvdb", "121361"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn ids_are_kept_per_source_until_cleared() {
+        let pool = fresh_pool().await;
+        store(&pool, "movie", 1, &[ExternalId::new("imdb", "tt0133093")]).await.unwrap();
+        store(&pool, "movie", 1, &[ExternalId::new("tmdb", "603")]).await.unwrap();
+        store(&pool, "movie", 2, &[ExternalId::new("tmdb", "604")]).await.unwrap();
+
+        assert_eq!(lookup(&pool, "movie", 1, "tmdb").await.unwrap().as_deref(), Some("603"));
+        assert_eq!(
+            for_title(&pool, "movie", 1).await.unwrap(),
+            vec![ExternalId::new("imdb", "tt0133093"), ExternalId::new("tmdb", "603")]
+        );
+
+        let mut conn = pool.acquire().await.unwrap();
+        clear_in_tx(&mut conn, "movie", 1).await.unwrap();
+        drop(conn);
+        assert!(for_title(&pool, "movie", 1).await.unwrap().is_empty());
+        assert_eq!(lookup(&pool, "movie", 2, "tmdb").await.unwrap().as_deref(), Some("604"));
+    }
+}
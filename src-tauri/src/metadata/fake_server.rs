@@ -0,0 +1,307 @@
+//! In-process fake TMDB / IMDB server for offline tests. Binds an
+//! ephemeral localhost port, answers each route with a canned response
+//! and records every request it sees. Speaks just enough HTTP/1.1 for
+//! reqwest: one request per connection, `Connection: close`.
+//!
+//! Routes match on method + path; the query string is ignored unless the
+//! route names one (`/3/movie/603?language=de`), in which case each of
+//! its pairs must be present. Later routes win, so a test can start from
+//! `with_provider_fixtures` and override a single endpoint with an auth
+//! failure or a 429.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::metadata::http::Endpoints;
+
+pub const TMDB_SEARCH_MOVIE: &str = include_str!("../../tests/fixtures/tmdb-search-movie.json");
+pub const TMDB_MOVIE_DETAILS: &str = include_str!("../../tests/fixtures/tmdb-movie-details.json");
+pub const TMDB_COLLECTION: &str = include_str!("../../tests/fixtures/tmdb-collection.json");
+pub const IMDB_SUGGESTION_MOVIE: &str =
+    include_str!("../../tests/fixtures/imdb-suggestion-movie.json");
+pub const IMDB_GRAPHQL_MOVIE: &str = include_str!("../../tests/fixtures/imdb-graphql-movie.json");
+
+/// Bytes served for any poster route: a small real JPEG, so the variants
+/// rendered from it can be checked.
+pub const POSTER_BYTES: &[u8] = include_bytes!("../../tests/fixtures/poster.jpg");
+
+#[derive(Debug, Clone)]
+pub struct FakeResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl FakeResponse {
+    pub fn json(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.into().into_bytes(),
+        }
+    }
+
+    pub fn bytes(content_type: &str, body: &[u8]) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".into(), content_type.into())],
+            body: body.to_vec(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path plus query string, exactly as sent.
+    pub target: String,
+}
+
+impl RecordedRequest {
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or("")
+    }
+
+    /// Raw `name=value` pairs of the query string.
+    pub fn query_pairs(&self) -> Vec<&str> {
+        match self.target.split_once('?') {
+            Some((_, query)) => query.split('&').filter(|pair| !pair.is_empty()).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+struct Route {
+    method: String,
+    path: String,
+    response: FakeResponse,
+}
+
+impl Route {
+    fn matches(&self, request: &RecordedRequest) -> bool {
+        let (path, query) = self.path.split_once('?').unwrap_or((&self.path, ""));
+        let pairs = request.query_pairs();
+        self.method == request.method
+            && path == request.path()
+            && query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .all(|pair| pairs.contains(&pair))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    routes: Vec<Route>,
+    requests: Vec<RecordedRequest>,
+}
+
+pub struct FakeServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl FakeServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind fake server");
+        let addr = listener.local_addr().expect("fake server addr");
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let accept_state = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = accept_state.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, state).await;
+                });
+            }
+        });
+
+        Self { addr, state, task }
+    }
+
+    /// A server answering the TMDB movie search/details/collection, IMDB
+    /// suggestion, IMDB GraphQL and the poster routes from the recorded
+    /// fixtures.
+    /// IMDB image URLs in the fixtures are rewritten to point here.
+    pub async fn with_provider_fixtures() -> Self {
+        let server = Self::start().await;
+        let base = server.base_url();
+
+        server.route("GET", "/3/search/movie", FakeResponse::json(TMDB_SEARCH_MOVIE));
+        server.route("GET", "/3/movie/603", FakeResponse::json(TMDB_MOVIE_DETAILS));
+        server.route("GET", "/3/collection/2344", FakeResponse::json(TMDB_COLLECTION));
+        server.route(
+            "GET",
+            "/t/p/w500/bV9qTVHTVf0gkW0j7p7M0ILD4pG.jpg",
+            FakeResponse::bytes("image/jpeg", POSTER_BYTES),
+        );
+        server.route(
+            "GET",
+            "/t/p/w500/f89U3ADr1oiB1s9GkdPOEpXUk5H.jpg",
+            FakeResponse::bytes("image/jpeg", POSTER_BYTES),
+        );
+        server.route(
+            "GET",
+            "/t/p/w1280/fNG7i7RqMErkcqhohV2a6cV1Ehy.jpg",
+            FakeResponse::bytes("image/jpeg", POSTER_BYTES),
+        );
+
+        server.route(
+            "GET",
+            "/suggestion/t/the_matrix_1999.json",
+            FakeResponse::json(IMDB_SUGGESTION_MOVIE),
+        );
+        server.route(
+            "POST",
+            "/graphql",
+            FakeResponse::json(
+                IMDB_GRAPHQL_MOVIE.replace("https://m.media-amazon.com", &base),
+            ),
+        );
+        server.route(
+            "GET",
+            "/images/M/matrix@._V1_SX500_.jpg",
+            FakeResponse::bytes("image/jpeg", POSTER_BYTES),
+        );
+        server.route(
+            "GET",
+            "/images/M/matrix-still@._V1_QL90_UX1280_.jpg",
+            FakeResponse::bytes("image/jpeg", POSTER_BYTES),
+        );
+
+        server
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Endpoints pointing every provider at this server.
+    pub fn endpoints(&self) -> Endpoints {
+        let base = self.base_url();
+        Endpoints {
+            tmdb_api: format!("{base}/3"),
+            tmdb_image: format!("{base}/t/p/w500"),
+            imdb_suggestion: format!("{base}/suggestion"),
+            imdb_graphql: format!("{base}/graphql"),
+        }
+    }
+
+    pub fn route(&self, method: &str, path: &str, response: FakeResponse) {
+        self.state.lock().unwrap().routes.push(Route {
+            method: method.to_string(),
+            path: path.to_string(),
+            response,
+        });
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Number of requests seen for `path`, any method.
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|request| request.path() == path)
+            .count()
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<State>>) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let head_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(index) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break index + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("").to_string();
+    let target = request_line.next().unwrap_or("").to_string();
+
+    let content_length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buffer.len() < head_end + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = RecordedRequest { method, target };
+    let response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request.clone());
+        state
+            .routes
+            .iter()
+            .rev()
+            .find(|route| route.matches(&request))
+            .map(|route| route.response.clone())
+            .unwrap_or_else(|| FakeResponse::status(404))
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.shutdown().await
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
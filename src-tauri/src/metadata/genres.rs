@@ -0,0 +1,285 @@
+//! Normalized genres. Both apply paths hand the provider's genre names
+//! to `store_in_tx`, which maps them onto canonical genres through
+//! `genre_aliases` and rewrites the title's join rows. A name with no
+//! alias becomes a canonical genre of its own (or joins the one with the
+//! same slug), so new provider genres show up without a migration.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use crate::parental;
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct GenreCount {
+    pub id: i64,
+    pub slug: String,
+    pub name: String,
+    pub movie_count: i64,
+    pub show_count: i64,
+}
+
+/// Join table and its title column for a media kind.
+fn join_table(kind: &str) -> AppResult<(&'static str, &'static str)> {
+    match kind {
+        "movie" => Ok(("movie_genres", "movie_id")),
+        "show" => Ok(("show_genres", "show_id")),
+        other => Err(AppError::Other(format!("unknown kind: {other}"))),
+    }
+}
+
+/// Removes all of a title's genres.
+pub async fn clear_in_tx(conn: &mut SqliteConnection, kind: &str, media_id: i64) -> AppResult<()> {
+    let (table, column) = join_table(kind)?;
+    sqlx::query(&format!("DELETE FROM {table} WHERE {column} = ?1"))
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Replaces a title's genres with the canonical genres behind `names`.
+pub async fn store_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    provider: &str,
+    names: &[String],
+) -> AppResult<()> {
+    clear_in_tx(conn, kind, media_id).await?;
+    let (table, column) = join_table(kind)?;
+
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        for genre_id in resolve_in_tx(conn, provider, name).await? {
+            sqlx::query(&format!(
+                "INSERT OR IGNORE INTO {table} ({column}, genre_id) VALUES (?1, ?2)"
+            ))
+            .bind(media_id)
+            .bind(genre_id)
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Canonical genre ids for one provider name, learning an alias for
+/// names seen for the first time.
+async fn resolve_in_tx(
+    conn: &mut SqliteConnection,
+    provider: &str,
+    name: &str,
+) -> AppResult<Vec<i64>> {
+    let known: Vec<i64> = sqlx::query_scalar(
+        "SELECT genre_id FROM genre_aliases WHERE provider = ?1 AND provider_name = ?2",
+    )
+    .bind(provider)
+    .bind(name)
+    .fetch_all(&mut *conn)
+    .await?;
+    if !known.is_empty() {
+        return Ok(known);
+    }
+
+    let slug = slugify(name);
+    if slug.is_empty() {
+        return Ok(Vec::new());
+    }
+    let genre_id: i64 = sqlx::query_scalar(
+        "INSERT INTO genres (slug, name) VALUES (?1, ?2)
+         ON CONFLICT(slug) DO UPDATE SET slug = excluded.slug
+         RETURNING id",
+    )
+    .bind(&slug)
+    .bind(name)
+    .fetch_one(&mut *conn)
+    .await?;
+    sqlx::query(
+        "INSERT OR IGNORE INTO genre_aliases (provider, provider_name, genre_id)
+         VALUES (?1, ?2, ?3)",
+    )
+    .bind(provider)
+    .bind(name)
+    .bind(genre_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(vec![genre_id])
+}
+
+/// Genres with at least one title parental controls allow, with
+/// per-kind counts.
+pub async fn counts(pool: &SqlitePool) -> AppResult<Vec<GenreCount>> {
+    let rows: Vec<GenreCount> = sqlx::query_as(&format!(
+        "SELECT * FROM (
+           SELECT g.id, g.slug, g.name,
+                  (SELECT COUNT(*) FROM movie_genres mg JOIN movies m ON m.id = mg.movie_id
+                    WHERE mg.genre_id = g.id AND {movie_visible}) AS movie_count,
+                  (SELECT COUNT(*) FROM show_genres sg JOIN shows s ON s.id = sg.show_id
+                    WHERE sg.genre_id = g.id AND {show_visible}) AS show_count
+           FROM genres g
+         )
+         WHERE movie_count > 0 OR show_count > 0
+         ORDER BY name COLLATE NOCASE",
+        movie_visible = parental::visible_clause("movie", "m"),
+        show_visible = parental::visible_clause("show", "s"),
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// `WHERE` terms requiring every genre in `slugs`, for a select aliased
+/// `m` (movies) or `s` (shows). Bind the slugs in order, starting at
+/// `?first`.
+pub fn filter_clause(kind: &str, slugs: &[String], first: usize) -> AppResult<String> {
+    let (table, column) = join_table(kind)?;
+    let alias = if kind == "movie" { "m" } else { "s" };
+    let terms: Vec<String> = (0..slugs.len())
+        .map(|index| {
+            format!(
+                "EXISTS (SELECT 1 FROM {table} j JOIN genres g ON g.id = j.genre_id
+                          WHERE j.{column} = {alias}.id AND g.slug = ?{})",
+                first + index
+            )
+        })
+        .collect();
+
+    Ok(terms.join(" AND "))
+}
+
+/// `Science Fiction` → `science-fiction`.
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    async fn slugs_for(pool: &SqlitePool, kind: &str, id: i64) -> Vec<String> {
+        let (table, column) = join_table(kind).unwrap();
+        sqlx::query_scalar(&format!(
+            "SELECT g.slug FROM {table} j JOIN genres g ON g.id = j.genre_id
+             WHERE j.{column} = ?1 ORDER BY g.slug"
+        ))
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn slugify_collapses_punctuation() {
+        assert_eq!(slugify("Science Fiction"), "science-fiction");
+        assert_eq!(slugify("Film-Noir"), "film-noir");
+        assert_eq!(slugify(" Sci-Fi & Fantasy "), "sci-fi-fantasy");
+    }
+
+    #[tokio::test]
+    async fn provider_names_map_onto_canonical_genres() {
+        let pool = fresh_pool().await;
+        sqlx::query(
+            "INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies'), (2, '/tv', 'series')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path)
+             VALUES (1, 1, 'A', '/movies/a.mkv'), (2, 1, 'B', '/movies/b.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO shows (id, library_id, title, folder_path, fingerprint)
+             VALUES (1, 2, 'S', '/tv/S', 'fp')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        store_in_tx(&mut conn, "movie", 1, "tmdb", &names(&["Science Fiction", "Action"]))
+            .await
+            .unwrap();
+        store_in_tx(&mut conn, "movie", 2, "imdb", &names(&["Sci-Fi", "Mockumentary"]))
+            .await
+            .unwrap();
+        store_in_tx(&mut conn, "show", 1, "tmdb", &names(&["Sci-Fi & Fantasy"]))
+            .await
+            .unwrap();
+        drop(conn);
+
+        assert_eq!(slugs_for(&pool, "movie", 1).await, vec!["action", "science-fiction"]);
+        assert_eq!(slugs_for(&pool, "movie", 2).await, vec!["mockumentary", "science-fiction"]);
+        assert_eq!(slugs_for(&pool, "show", 1).await, vec!["fantasy", "science-fiction"]);
+
+        // Re-applying replaces rather than appends.
+        let mut conn = pool.acquire().await.unwrap();
+        store_in_tx(&mut conn, "movie", 1, "tmdb", &names(&["Drama"])).await.unwrap();
+        drop(conn);
+        assert_eq!(slugs_for(&pool, "movie", 1).await, vec!["drama"]);
+
+        let counts = counts(&pool).await.unwrap();
+        let summary: Vec<(&str, i64, i64)> = counts
+            .iter()
+            .map(|genre| (genre.slug.as_str(), genre.movie_count, genre.show_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("drama", 1, 0),
+                ("fantasy", 0, 1),
+                ("mockumentary", 1, 0),
+                ("science-fiction", 1, 1),
+            ]
+        );
+
+        let ids = |movies: Vec<crate::models::Movie>| -> Vec<i64> {
+            movies.iter().map(|movie| movie.id).collect()
+        };
+        let view = crate::queries::RatingView::default();
+        let all = crate::queries::list_movies(&pool, &[], &view).await.unwrap();
+        assert_eq!(ids(all), vec![1, 2]);
+        let sci_fi = crate::queries::list_movies(&pool, &names(&["science-fiction"]), &view)
+            .await
+            .unwrap();
+        assert_eq!(ids(sci_fi), vec![2]);
+        let both = crate::queries::list_movies(&pool, &names(&["drama", "science-fiction"]), &view)
+            .await
+            .unwrap();
+        assert!(both.is_empty());
+        let shows = crate::queries::list_shows(&pool, &names(&["fantasy"]), &view).await.unwrap();
+        assert_eq!(shows.len(), 1);
+    }
+}
@@ -0,0 +1,589 @@
+//! Shared HTTP front door for provider calls. Wraps the reqwest client
+//! with an on-disk response cache stored in `http_cache`, so an
+//! unlink/relink cycle or a re-run sync doesn't hit TMDB / IMDB again.
+//!
+//! Entries are keyed by the full request (method, URL minus `api_key`,
+//! body) plus the requested language. Each endpoint passes its own TTL.
+//! Expired entries are revalidated with `If-None-Match` /
+//! `If-Modified-Since`; a `304` extends them without re-downloading.
+//! A `revalidating` handle does that for fresh entries too, so a forced
+//! or scheduled refresh never re-applies a stale answer. When the
+//! network is unreachable, an expired entry is served as-is.
+//! `prune_cache` drops entries long past their expiry.
+//!
+//! Only `200 OK` responses are stored. Cache failures are logged and
+//! bypassed — they never fail the provider call.
+//!
+//! Provider base URLs live in `Endpoints`. They default to the production
+//! hosts and can be overridden through `RUSTFLIX_*` environment variables
+//! (or directly, in tests) to point the app at a mirror or a fake server.
+//!
+//! Every network call goes through the provider's token bucket
+//! (`metadata::rate_limit`). Nothing sleeps through a provider's pause:
+//! a `429`, or a call made while the bucket is still blocked, comes
+//! back as a `429` with `retry_after` set. The worker defers the job;
+//! interactive commands report the wait.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, Request, Response, StatusCode};
+use sqlx::SqlitePool;
+
+use crate::error::AppResult;
+use crate::metadata::dispatch::Provider;
+use crate::metadata::rate_limit::RateLimiters;
+use crate::metadata::{imdb, tmdb};
+use crate::models::RateLimitStatus;
+
+#[derive(Clone)]
+pub struct ProviderHttp {
+    client: Client,
+    cache: Option<SqlitePool>,
+    limits: Arc<RateLimiters>,
+    endpoints: Arc<Endpoints>,
+    /// Revalidate cache hits before `expires_at`.
+    revalidate: bool,
+}
+
+/// Base URLs for every provider endpoint the app talks to. No trailing
+/// slashes on the `_base` fields; paths are appended as `/…`.
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    pub tmdb_api: String,
+    /// Prefix for TMDB poster paths (which start with `/`).
+    pub tmdb_image: String,
+    pub imdb_suggestion: String,
+    pub imdb_graphql: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            tmdb_api: tmdb::API_BASE.to_string(),
+            tmdb_image: tmdb::IMAGE_BASE.to_string(),
+            imdb_suggestion: imdb::SUGGESTION_BASE.to_string(),
+            imdb_graphql: imdb::GRAPHQL_URL.to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// Defaults, overridden by whichever of `RUSTFLIX_TMDB_API_BASE`,
+    /// `RUSTFLIX_TMDB_IMAGE_BASE`, `RUSTFLIX_IMDB_SUGGESTION_BASE` and
+    /// `RUSTFLIX_IMDB_GRAPHQL_URL` are set.
+    pub fn from_env() -> Self {
+        let read = |name: &str, fallback: String| {
+            std::env::var(name)
+                .ok()
+                .map(|value| value.trim().trim_end_matches('/').to_string())
+                .filter(|value| !value.is_empty())
+                .unwrap_or(fallback)
+        };
+        let defaults = Self::default();
+
+        Self {
+            tmdb_api: read("RUSTFLIX_TMDB_API_BASE", defaults.tmdb_api),
+            tmdb_image: read("RUSTFLIX_TMDB_IMAGE_BASE", defaults.tmdb_image),
+            imdb_suggestion: read("RUSTFLIX_IMDB_SUGGESTION_BASE", defaults.imdb_suggestion),
+            imdb_graphql: read("RUSTFLIX_IMDB_GRAPHQL_URL", defaults.imdb_graphql),
+        }
+    }
+}
+
+/// A provider response, either fresh from the network or replayed from
+/// the cache. A revalidated `304` is reported as `200 OK`.
+pub struct Fetched {
+    pub status: StatusCode,
+    pub body: Vec<u8>,
+    /// For a `429`: how long the provider asked us to back off.
+    pub retry_after: Option<Duration>,
+}
+
+impl Fetched {
+    fn ok(body: Vec<u8>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            body,
+            retry_after: None,
+        }
+    }
+
+    fn throttled(retry_after: Option<Duration>) -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            body: Vec::new(),
+            retry_after,
+        }
+    }
+
+    /// `"; retry in 42s"` for a throttle with a known pause, else empty.
+    /// Appended to `rate_limited:` errors so the UI can say how long.
+    pub fn retry_hint(&self) -> String {
+        match self.retry_after {
+            Some(wait) => format!("; retry in {}s", wait.as_secs().max(1)),
+            None => String::new(),
+        }
+    }
+}
+
+/// What `execute` got: a response, or a refusal to call a provider that
+/// asked us to back off for the given time.
+enum Sent {
+    Response(Response),
+    Blocked(Duration),
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CacheEntry {
+    body: Vec<u8>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    expires_at: i64,
+}
+
+impl ProviderHttp {
+    pub fn new(client: Client, cache: SqlitePool) -> Self {
+        Self {
+            client,
+            cache: Some(cache),
+            limits: Arc::new(RateLimiters::default()),
+            endpoints: Arc::new(Endpoints::default()),
+            revalidate: false,
+        }
+    }
+
+    /// A handle that revalidates every cached response with the
+    /// provider, fresh or not. Shares the client, cache and buckets.
+    pub fn revalidating(&self) -> Self {
+        Self {
+            revalidate: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Arc::new(endpoints);
+        self
+    }
+
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    /// The underlying client, for uncached calls such as poster downloads.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Remaining provider-requested pause, if `provider` is throttled.
+    pub fn blocked_for(&self, provider: Provider) -> Option<Duration> {
+        self.limits.blocked_for(provider)
+    }
+
+    pub fn rate_limit_status(&self) -> Vec<RateLimitStatus> {
+        self.limits.status()
+    }
+
+    /// Executes `request` through the cache. `provider` and `language`
+    /// are stored alongside the entry; `ttl` decides how long it is
+    /// served without revalidation (unless this handle is
+    /// `revalidating`).
+    pub async fn send_cached(
+        &self,
+        mut request: Request,
+        provider: Provider,
+        language: &str,
+        ttl: Duration,
+    ) -> Result<Fetched, reqwest::Error> {
+        let Some(pool) = self.cache.as_ref() else {
+            return self.send_uncached(provider, request).await;
+        };
+
+        let key = cache_key(&request);
+        let entry = match load_entry(pool, &key, language).await {
+            Ok(entry) => entry,
+            Err(error) => {
+                eprintln!("http cache lookup failed: {error}");
+                None
+            }
+        };
+
+        let now = unix_now();
+        if let Some(entry) = entry.as_ref() {
+            if entry.expires_at > now && !self.revalidate {
+                return Ok(Fetched::ok(entry.body.clone()));
+            }
+
+            let headers = request.headers_mut();
+            if let Some(value) = entry.etag.as_deref().and_then(|v| v.parse().ok()) {
+                headers.insert(IF_NONE_MATCH, value);
+            }
+            if let Some(value) = entry.last_modified.as_deref().and_then(|v| v.parse().ok()) {
+                headers.insert(IF_MODIFIED_SINCE, value);
+            }
+        }
+
+        let response = match self.execute(provider, request).await {
+            Ok(Sent::Response(response)) => response,
+            Ok(Sent::Blocked(wait)) => return Ok(Fetched::throttled(Some(wait))),
+            Err(error) => {
+                // Offline: a stale answer beats no answer.
+                return match entry {
+                    Some(stale) => Ok(Fetched::ok(stale.body)),
+                    None => Err(error),
+                };
+            }
+        };
+
+        let status = response.status();
+        let expires_at = now + ttl.as_secs() as i64;
+
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = entry {
+                if let Err(error) = touch_entry(pool, &key, language, expires_at).await {
+                    eprintln!("http cache touch failed: {error}");
+                }
+                return Ok(Fetched::ok(entry.body));
+            }
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Ok(Fetched::throttled(self.blocked_for(provider)));
+        }
+
+        let etag = header_string(&response, ETAG);
+        let last_modified = header_string(&response, LAST_MODIFIED);
+        let body = response.bytes().await?.to_vec();
+
+        if status == StatusCode::OK {
+            let stored = store_entry(
+                pool,
+                &key,
+                language,
+                provider.as_str(),
+                &body,
+                etag.as_deref(),
+                last_modified.as_deref(),
+                expires_at,
+            )
+            .await;
+            if let Err(error) = stored {
+                eprintln!("http cache store failed: {error}");
+            }
+        }
+
+        Ok(Fetched {
+            status,
+            body,
+            retry_after: None,
+        })
+    }
+
+    async fn send_uncached(
+        &self,
+        provider: Provider,
+        request: Request,
+    ) -> Result<Fetched, reqwest::Error> {
+        let response = match self.execute(provider, request).await? {
+            Sent::Response(response) => response,
+            Sent::Blocked(wait) => return Ok(Fetched::throttled(Some(wait))),
+        };
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Ok(Fetched::throttled(self.blocked_for(provider)));
+        }
+        let body = response.bytes().await?.to_vec();
+        Ok(Fetched {
+            status,
+            body,
+            retry_after: None,
+        })
+    }
+
+    /// Rate-limited execute: one attempt, no retries. A throttle blocks
+    /// the bucket and is returned as-is; while the bucket is blocked, no
+    /// request is made at all.
+    async fn execute(&self, provider: Provider, request: Request) -> Result<Sent, reqwest::Error> {
+        if let Err(wait) = self.limits.acquire(provider).await {
+            return Ok(Sent::Blocked(wait));
+        }
+        let response = self.client.execute(request).await?;
+        self.limits.observe(provider, response.status(), response.headers());
+        Ok(Sent::Response(response))
+    }
+}
+
+/// Deletes cached responses, optionally only for one provider. Returns
+/// the number of entries removed.
+pub async fn clear_cache(pool: &SqlitePool, provider: Option<&str>) -> AppResult<u64> {
+    let result = sqlx::query("DELETE FROM http_cache WHERE ?1 IS NULL OR provider = ?1")
+        .bind(provider)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Deletes entries that expired more than `grace_secs` ago. Recently
+/// expired ones stay for offline fallback and cheap `304`
+/// revalidation. Returns the number of entries removed.
+pub async fn prune_cache(pool: &SqlitePool, grace_secs: i64) -> AppResult<u64> {
+    let result = sqlx::query("DELETE FROM http_cache WHERE expires_at < ?1")
+        .bind(unix_now() - grace_secs)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// `METHOD url [body]`, with the `api_key` query parameter removed so a
+/// key change doesn't invalidate the cache (and the key never hits disk).
+fn cache_key(request: &Request) -> String {
+    let mut url = request.url().clone();
+    let retained: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| name != "api_key")
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if retained.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(retained);
+    }
+
+    let mut key = format!("{} {url}", request.method());
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        key.push(' ');
+        key.push_str(&String::from_utf8_lossy(body));
+    }
+    key
+}
+
+fn header_string(response: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+async fn load_entry(pool: &SqlitePool, key: &str, language: &str) -> AppResult<Option<CacheEntry>> {
+    let entry = sqlx::query_as(
+        "SELECT body, etag, last_modified, expires_at FROM http_cache
+         WHERE cache_key = ?1 AND language = ?2",
+    )
+    .bind(key)
+    .bind(language)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(entry)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn store_entry(
+    pool: &SqlitePool,
+    key: &str,
+    language: &str,
+    provider: &str,
+    body: &[u8],
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+    expires_at: i64,
+) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO http_cache
+             (cache_key, language, provider, body, etag, last_modified, fetched_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%s','now'), ?7)
+         ON CONFLICT(cache_key, language) DO UPDATE SET
+             provider = excluded.provider,
+             body = excluded.body,
+             etag = excluded.etag,
+             last_modified = excluded.last_modified,
+             fetched_at = excluded.fetched_at,
+             expires_at = excluded.expires_at",
+    )
+    .bind(key)
+    .bind(language)
+    .bind(provider)
+    .bind(body)
+    .bind(etag)
+    .bind(last_modified)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn touch_entry(pool: &SqlitePool, key: &str, language: &str, expires_at: i64) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE http_cache SET expires_at = ?3, fetched_at = strftime('%s','now')
+         WHERE cache_key = ?1 AND language = ?2",
+    )
+    .bind(key)
+    .bind(language)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    #[test]
+    fn cache_key_drops_api_key_but_keeps_other_params() {
+        let client = Client::new();
+        let request = client
+            .get("https://api.example.com/3/search/movie")
+            .query(&[("api_key", "secret"), ("query", "The Matrix"), ("year", "1999")])
+            .build()
+            .unwrap();
+
+        let key = cache_key(&request);
+        assert!(!key.contains("secret"));
+        assert_eq!(
+            key,
+            "GET https://api.example.com/3/search/movie?query=The+Matrix&year=1999"
+        );
+    }
+
+    #[test]
+    fn cache_key_includes_post_body() {
+        let client = Client::new();
+        let first = client
+            .post("https://graphql.example.com/")
+            .body(r#"{"id":"tt1"}"#)
+            .build()
+            .unwrap();
+        let second = client
+            .post("https://graphql.example.com/")
+            .body(r#"{"id":"tt2"}"#)
+            .build()
+            .unwrap();
+
+        assert_ne!(cache_key(&first), cache_key(&second));
+    }
+
+    #[tokio::test]
+    async fn entries_are_scoped_by_language() {
+        let pool = fresh_pool().await;
+        store_entry(&pool, "GET /x", "en", "tmdb", b"english", None, None, i64::MAX)
+            .await
+            .unwrap();
+        store_entry(&pool, "GET /x", "sv", "tmdb", b"svenska", Some("\"v1\""), None, i64::MAX)
+            .await
+            .unwrap();
+
+        let english = load_entry(&pool, "GET /x", "en").await.unwrap().unwrap();
+        let swedish = load_entry(&pool, "GET /x", "sv").await.unwrap().unwrap();
+        assert_eq!(english.body, b"english");
+        assert_eq!(swedish.body, b"svenska");
+        assert_eq!(swedish.etag.as_deref(), Some("\"v1\""));
+    }
+
+    #[tokio::test]
+    async fn clear_cache_filters_by_provider() {
+        let pool = fresh_pool().await;
+        store_entry(&pool, "GET /a", "en", "tmdb", b"a", None, None, 0).await.unwrap();
+        store_entry(&pool, "GET /b", "en", "imdb", b"b", None, None, 0).await.unwrap();
+
+        assert_eq!(clear_cache(&pool, Some("imdb")).await.unwrap(), 1);
+        assert!(load_entry(&pool, "GET /a", "en").await.unwrap().is_some());
+        assert_eq!(clear_cache(&pool, None).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn revalidating_handle_checks_fresh_entries_with_the_provider() {
+        use crate::metadata::fake_server::{FakeResponse, FakeServer};
+
+        let pool = fresh_pool().await;
+        let server = FakeServer::start().await;
+        server.route("GET", "/3/movie/1", FakeResponse::status(304));
+        let http = ProviderHttp::new(Client::new(), pool.clone());
+        let url = format!("{}/3/movie/1", server.base_url());
+        let request = || http.client().get(&url).build().unwrap();
+
+        let key = cache_key(&request());
+        store_entry(&pool, &key, "en", "tmdb", b"cached", Some("\"v1\""), None, i64::MAX)
+            .await
+            .unwrap();
+
+        let ttl = Duration::from_secs(60);
+        let plain = http.send_cached(request(), Provider::Tmdb, "en", ttl).await.unwrap();
+        assert_eq!(plain.body, b"cached");
+        assert_eq!(server.hits("/3/movie/1"), 0);
+
+        let revalidated = http
+            .revalidating()
+            .send_cached(request(), Provider::Tmdb, "en", ttl)
+            .await
+            .unwrap();
+        assert_eq!((revalidated.status, revalidated.body), (StatusCode::OK, b"cached".to_vec()));
+        assert_eq!(server.hits("/3/movie/1"), 1);
+    }
+
+    #[tokio::test]
+    async fn throttles_fail_fast_with_the_retry_after() {
+        use crate::metadata::fake_server::{FakeResponse, FakeServer};
+
+        let pool = fresh_pool().await;
+        let server = FakeServer::start().await;
+        server.route(
+            "GET",
+            "/3/search/movie",
+            FakeResponse::status(429).with_header("Retry-After", "600"),
+        );
+        let http = ProviderHttp::new(Client::new(), pool);
+        let url = format!("{}/3/search/movie", server.base_url());
+        let ttl = Duration::from_secs(60);
+
+        for _ in 0..2 {
+            let request = http.client().get(&url).build().unwrap();
+            let fetched = http.send_cached(request, Provider::Tmdb, "en", ttl).await.unwrap();
+            assert_eq!(fetched.status, StatusCode::TOO_MANY_REQUESTS);
+            assert!(fetched.retry_after.unwrap() > Duration::from_secs(590));
+        }
+        // The second call never reached the provider.
+        assert_eq!(server.hits("/3/search/movie"), 1);
+    }
+
+    #[tokio::test]
+    async fn prune_cache_keeps_recently_expired_entries() {
+        let pool = fresh_pool().await;
+        let now = unix_now();
+        store_entry(&pool, "GET /old", "en", "tmdb", b"o", None, None, now - 100).await.unwrap();
+        store_entry(&pool, "GET /stale", "en", "tmdb", b"s", None, None, now - 10).await.unwrap();
+        store_entry(&pool, "GET /fresh", "en", "tmdb", b"f", None, None, now + 10).await.unwrap();
+
+        assert_eq!(prune_cache(&pool, 50).await.unwrap(), 1);
+        assert!(load_entry(&pool, "GET /old", "en").await.unwrap().is_none());
+        assert!(load_entry(&pool, "GET /stale", "en").await.unwrap().is_some());
+        assert!(load_entry(&pool, "GET /fresh", "en").await.unwrap().is_some());
+    }
+}
@@ -21,14 +21,22 @@
 //! Users are responsible for their own compliance with the linked terms.
 //! The tmdb_only mode remains a functional escape hatch.
 
-use reqwest::{Client, StatusCode};
+use std::time::Duration;
+
+use reqwest::StatusCode;
 use serde::Deserialize;
 
 use crate::error::{AppError, AppResult};
 use crate::metadata::dispatch::Provider;
+use crate::metadata::http::{Fetched, ProviderHttp};
+use crate::metadata::locale::{self, LocalizedText};
 use crate::metadata::matching::MatchCandidate;
 
-const SUGGESTION_BASE: &str = "https://v3.sg.media-imdb.com/suggestion";
+/// Production default; the live value comes from `ProviderHttp::endpoints`.
+pub const SUGGESTION_BASE: &str = "https://v3.sg.media-imdb.com/suggestion";
+
+const SUGGESTION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DETAILS_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
 
 #[derive(Debug, Deserialize)]
 struct SuggestionEnvelope {
@@ -45,28 +53,42 @@ struct SuggestionEntry {
     y: Option<i32>,
     #[serde(default)]
     qid: Option<String>,
+    /// Top-billed names, e.g. "Keanu Reeves, Laurence Fishburne".
+    #[serde(default)]
+    s: Option<String>,
+    #[serde(default)]
+    i: Option<SuggestionImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SuggestionImage {
+    #[serde(rename = "imageUrl")]
+    image_url: String,
 }
 
 pub async fn search_movie(
-    client: &Client,
+    http: &ProviderHttp,
     title: &str,
     year: Option<i32>,
+    language: &str,
 ) -> AppResult<Vec<MatchCandidate>> {
-    search_internal(client, title, year, &["movie"]).await
+    search_internal(http, title, year, language, &["movie"]).await
 }
 
 pub async fn search_show(
-    client: &Client,
+    http: &ProviderHttp,
     title: &str,
     year: Option<i32>,
+    language: &str,
 ) -> AppResult<Vec<MatchCandidate>> {
-    search_internal(client, title, year, &["tvSeries", "tvMiniSeries"]).await
+    search_internal(http, title, year, language, &["tvSeries", "tvMiniSeries"]).await
 }
 
 async fn search_internal(
-    client: &Client,
+    http: &ProviderHttp,
     title: &str,
     year: Option<i32>,
+    language: &str,
     qid_filter: &[&str],
 ) -> AppResult<Vec<MatchCandidate>> {
     let slug = slugify(title);
@@ -78,14 +100,14 @@ async fn search_internal(
     let envelope = match year {
         Some(year_value) => {
             let augmented = format!("{slug}_{year_value}");
-            let result = fetch_suggestion(client, &augmented).await?;
+            let result = fetch_suggestion(http, &augmented, language).await?;
             if result.d.is_empty() {
-                fetch_suggestion(client, &slug).await?
+                fetch_suggestion(http, &slug, language).await?
             } else {
                 result
             }
         }
-        None => fetch_suggestion(client, &slug).await?,
+        None => fetch_suggestion(http, &slug, language).await?,
     };
 
     Ok(envelope
@@ -102,34 +124,63 @@ async fn search_internal(
                 provider_id: entry.id,
                 title,
                 year: entry.y,
+                poster_url: entry
+                    .i
+                    .map(|image| rewrite_size(&image.image_url, PosterSize::Small)),
+                overview: entry.s.filter(|text| !text.is_empty()),
             })
         })
         .collect())
 }
 
-async fn fetch_suggestion(client: &Client, slug: &str) -> AppResult<SuggestionEnvelope> {
+async fn fetch_suggestion(
+    http: &ProviderHttp,
+    slug: &str,
+    language: &str,
+) -> AppResult<SuggestionEnvelope> {
     let first = slug.chars().next().unwrap_or('a');
     let shard = first.to_lowercase().next().unwrap_or('a');
-    let url = format!("{SUGGESTION_BASE}/{shard}/{slug}.json");
+    let url = format!("{}/{shard}/{slug}.json", http.endpoints().imdb_suggestion);
 
-    let response = client.get(&url).send().await.map_err(http_err)?;
-    let status = response.status();
+    let request = http
+        .client()
+        .get(&url)
+        .header("Accept-Language", locale::imdb_locale(language))
+        .build()
+        .map_err(http_err)?;
+    let response = send(http, request, language, SUGGESTION_TTL).await?;
+    let status = response.status;
     if status == StatusCode::ACCEPTED {
         return Err(AppError::Other(
             "imdb_waf: suggestion endpoint returned 202; see CLAUDE.md".to_string(),
         ));
     }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(AppError::Other(format!(
+            "rate_limited: imdb suggestion returned 429{}",
+            response.retry_hint()
+        )));
+    }
     if !status.is_success() {
         return Err(AppError::Other(format!(
             "imdb_rate_limited: suggestion {status}"
         )));
     }
-    response
-        .json::<SuggestionEnvelope>()
-        .await
+    serde_json::from_slice::<SuggestionEnvelope>(&response.body)
         .map_err(|error| AppError::Other(format!("imdb parse: suggestion: {error}")))
 }
 
+async fn send(
+    http: &ProviderHttp,
+    request: reqwest::Request,
+    language: &str,
+    ttl: Duration,
+) -> AppResult<Fetched> {
+    http.send_cached(request, Provider::Imdb, language, ttl)
+        .await
+        .map_err(http_err)
+}
+
 /// Lowercase + non-alphanumeric → `_`, no trailing underscore.
 fn slugify(title: &str) -> String {
     let mut output = String::with_capacity(title.len());
@@ -158,7 +209,8 @@ fn http_err(error: reqwest::Error) -> AppError {
 
 // ---- GraphQL details ----
 
-const GRAPHQL_URL: &str = "https://caching.graphql.imdb.com/";
+/// Production default; the live value comes from `ProviderHttp::endpoints`.
+pub const GRAPHQL_URL: &str = "https://caching.graphql.imdb.com/";
 
 const GRAPHQL_QUERY: &str = r#"
 query TitleDetails($id: ID!) {
@@ -173,10 +225,12 @@ query TitleDetails($id: ID!) {
     runtime { seconds }
     genres { genres { id text } }
     primaryImage { url width height }
+    images(first: 20, filter: { types: ["still_frame"] }) { edges { node { url width height } } }
+    certificates(first: 100) { edges { node { rating country { id } } } }
     principalCredits(filter: { categories: ["director","writer","cast"] }) {
       category { id text }
       credits {
-        name { id nameText { text } }
+        name { id nameText { text } primaryImage { url } }
         ... on Cast { characters { name } }
       }
     }
@@ -220,8 +274,25 @@ pub struct TitleNode {
     pub genres: Option<GenresWrapper>,
     #[serde(default, rename = "primaryImage")]
     pub primary_image: Option<PrimaryImage>,
+    /// Still frames, for backdrops.
+    #[serde(default)]
+    pub images: Option<ImageConnection>,
     #[serde(default, rename = "principalCredits")]
     pub principal_credits: Vec<PrincipalCredits>,
+    #[serde(default)]
+    pub certificates: Option<CertificateConnection>,
+    /// Title/plot per fetched language; filled by `fetch_*_details`.
+    #[serde(skip)]
+    pub localized: Vec<LocalizedText>,
+}
+
+impl TitleNode {
+    pub fn plot_text(&self) -> Option<&str> {
+        self.plot
+            .as_ref()
+            .and_then(|plot| plot.plot_text.as_ref())
+            .and_then(|text| text.plain_text.as_deref())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -232,8 +303,6 @@ pub struct TextNode {
 #[derive(Debug, Deserialize)]
 pub struct ReleaseYearNode {
     pub year: Option<i32>,
-    // Captured from IMDB so a future "ended in YYYY" UI can show it without a re-fetch.
-    #[allow(dead_code)]
     #[serde(rename = "endYear")]
     pub end_year: Option<i32>,
 }
@@ -286,6 +355,48 @@ pub struct PrimaryImage {
     pub url: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ImageConnection {
+    #[serde(default)]
+    pub edges: Vec<ImageEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageEdge {
+    pub node: ImageNode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageNode {
+    pub url: String,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CertificateConnection {
+    #[serde(default)]
+    pub edges: Vec<CertificateEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CertificateEdge {
+    pub node: CertificateNode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CertificateNode {
+    pub rating: String,
+    #[serde(default)]
+    pub country: Option<CountryNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CountryNode {
+    /// ISO 3166-1 alpha-2.
+    pub id: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PrincipalCredits {
     pub category: CategoryNode,
@@ -307,8 +418,12 @@ pub struct CreditNode {
 
 #[derive(Debug, Deserialize)]
 pub struct NameNode {
+    /// `nm…` person id.
+    pub id: String,
     #[serde(rename = "nameText")]
     pub name_text: TextNode,
+    #[serde(default, rename = "primaryImage")]
+    pub primary_image: Option<PrimaryImage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -316,44 +431,86 @@ pub struct CharacterNode {
     pub name: String,
 }
 
-pub async fn fetch_movie_details(client: &Client, imdb_id: &str) -> AppResult<TitleNode> {
-    fetch_details_internal(client, imdb_id).await
+pub async fn fetch_movie_details(
+    http: &ProviderHttp,
+    imdb_id: &str,
+    language: &str,
+) -> AppResult<TitleNode> {
+    fetch_details_internal(http, imdb_id, language).await
+}
+
+pub async fn fetch_show_details(
+    http: &ProviderHttp,
+    imdb_id: &str,
+    language: &str,
+) -> AppResult<TitleNode> {
+    fetch_details_internal(http, imdb_id, language).await
+}
+
+/// Details in `language`; a missing plot is filled from an English
+/// fetch. Every language fetched ends up in `localized`.
+async fn fetch_details_internal(
+    http: &ProviderHttp,
+    imdb_id: &str,
+    language: &str,
+) -> AppResult<TitleNode> {
+    let mut title = fetch_title(http, imdb_id, language).await?;
+    title.localized = vec![localized_text(&title, language)];
+
+    if title.localized[0].overview.is_none() && !locale::is_fallback(language) {
+        let english = fetch_title(http, imdb_id, locale::FALLBACK_LANGUAGE).await?;
+        title.localized.push(localized_text(&english, locale::FALLBACK_LANGUAGE));
+        title.plot = english.plot;
+    }
+
+    Ok(title)
 }
 
-pub async fn fetch_show_details(client: &Client, imdb_id: &str) -> AppResult<TitleNode> {
-    fetch_details_internal(client, imdb_id).await
+fn localized_text(title: &TitleNode, language: &str) -> LocalizedText {
+    LocalizedText::new(
+        language,
+        title.title_text.as_ref().map(|text| text.text.as_str()),
+        title.plot_text(),
+    )
 }
 
-async fn fetch_details_internal(client: &Client, imdb_id: &str) -> AppResult<TitleNode> {
+async fn fetch_title(http: &ProviderHttp, imdb_id: &str, language: &str) -> AppResult<TitleNode> {
     let body = serde_json::json!({
         "operationName": "TitleDetails",
         "variables": { "id": imdb_id },
         "query": GRAPHQL_QUERY,
     });
 
-    let response = client
-        .post(GRAPHQL_URL)
+    let request = http
+        .client()
+        .post(&http.endpoints().imdb_graphql)
         .header("Content-Type", "application/json")
+        .header("x-imdb-user-language", locale::imdb_locale(language))
+        .header("Accept-Language", locale::imdb_locale(language))
         .json(&body)
-        .send()
-        .await
+        .build()
         .map_err(http_err)?;
+    let response = send(http, request, language, DETAILS_TTL).await?;
 
-    let status = response.status();
+    let status = response.status;
     if status == StatusCode::ACCEPTED {
         return Err(AppError::Other(
             "imdb_waf: graphql returned 202; see CLAUDE.md".to_string(),
         ));
     }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(AppError::Other(format!(
+            "rate_limited: imdb graphql returned 429{}",
+            response.retry_hint()
+        )));
+    }
     if !status.is_success() {
         return Err(AppError::Other(format!(
             "imdb_rate_limited: graphql {status}"
         )));
     }
 
-    let envelope: GraphQLEnvelope = response
-        .json()
-        .await
+    let envelope: GraphQLEnvelope = serde_json::from_slice(&response.body)
         .map_err(|error| AppError::Other(format!("imdb parse: graphql: {error}")))?;
 
     if let Some(first_error) = envelope.errors.first() {
@@ -380,8 +537,6 @@ async fn fetch_details_internal(client: &Client, imdb_id: &str) -> AppResult<Tit
 #[derive(Debug, Clone, Copy)]
 pub enum PosterSize {
     Small,
-    // Reserved for the future backdrop / hero rendering path.
-    #[allow(dead_code)]
     Hero,
 }
 
@@ -395,7 +550,7 @@ impl PosterSize {
 }
 
 pub async fn download_poster(
-    client: &Client,
+    http: &ProviderHttp,
     image_url: &str,
     dest: &std::path::Path,
     size: PosterSize,
@@ -404,7 +559,7 @@ pub async fn download_poster(
 
     let url = rewrite_size(image_url, size);
 
-    let mut response = client.get(&url).send().await.map_err(http_err)?;
+    let mut response = http.client().get(&url).send().await.map_err(http_err)?;
     if !response.status().is_success() {
         return Err(AppError::Other(format!(
             "poster download failed: {} {}",
@@ -520,6 +675,7 @@ mod fixture_tests {
         let title = envelope.data.unwrap().title.unwrap();
         assert_eq!(title.id, "tt0903747");
         assert_eq!(title.release_year.as_ref().unwrap().end_year, Some(2013));
+        assert_eq!(crate::metadata::airing::from_imdb(&title).end_year, Some(2013));
     }
 
     #[test]
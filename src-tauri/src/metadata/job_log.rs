@@ -0,0 +1,165 @@
+//! Append-only `metadata_job_log`. The worker writes one row per provider
+//! attempt; nothing ever updates a row. Old rows are pruned by the refresh
+//! scheduler once they pass `metadata_job_log_days`.
+
+use sqlx::SqlitePool;
+
+use crate::error::AppResult;
+
+/// Outcome labels stored in `metadata_job_log.outcome`.
+pub const OUTCOME_MATCHED: &str = "matched";
+pub const OUTCOME_NO_MATCH: &str = "no_match";
+pub const OUTCOME_RATE_LIMITED: &str = "rate_limited";
+pub const OUTCOME_AUTH_REQUIRED: &str = "auth_required";
+pub const OUTCOME_ERROR: &str = "error";
+
+/// A row to append. `query` is what was searched, or `id:<provider_id>` /
+/// `alias:<provider_id>` when the row was fetched by a known id.
+#[derive(Debug, Default)]
+pub struct NewLogEntry<'a> {
+    pub kind: &'a str,
+    pub media_id: i64,
+    pub provider: &'a str,
+    pub query: Option<String>,
+    pub candidate_count: Option<i64>,
+    pub chosen_id: Option<String>,
+    pub outcome: &'a str,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct JobLogEntry {
+    pub id: i64,
+    pub kind: String,
+    pub media_id: i64,
+    pub provider: String,
+    pub query: Option<String>,
+    pub candidate_count: Option<i64>,
+    pub chosen_id: Option<String>,
+    pub outcome: String,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub logged_at: i64,
+}
+
+pub async fn append(pool: &SqlitePool, entry: &NewLogEntry<'_>) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO metadata_job_log
+             (kind, media_id, provider, query, candidate_count, chosen_id,
+              outcome, error, duration_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )
+    .bind(entry.kind)
+    .bind(entry.media_id)
+    .bind(entry.provider)
+    .bind(entry.query.as_deref())
+    .bind(entry.candidate_count)
+    .bind(entry.chosen_id.as_deref())
+    .bind(entry.outcome)
+    .bind(entry.error.as_deref())
+    .bind(entry.duration_ms)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Most recent attempts for one title, newest first.
+pub async fn list_for_media(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    limit: i64,
+) -> AppResult<Vec<JobLogEntry>> {
+    let entries: Vec<JobLogEntry> = sqlx::query_as(
+        "SELECT id, kind, media_id, provider, query, candidate_count, chosen_id,
+                outcome, error, duration_ms, logged_at
+         FROM metadata_job_log
+         WHERE kind = ?1 AND media_id = ?2
+         ORDER BY logged_at DESC, id DESC
+         LIMIT ?3",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+/// Deletes rows older than `max_age_secs`. Returns the number removed.
+pub async fn prune(pool: &SqlitePool, max_age_secs: i64) -> AppResult<u64> {
+    let result = sqlx::query(
+        "DELETE FROM metadata_job_log WHERE logged_at <= strftime('%s','now') - ?1",
+    )
+    .bind(max_age_secs)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn entry(media_id: i64, outcome: &'static str) -> NewLogEntry<'static> {
+        NewLogEntry {
+            kind: "movie",
+            media_id,
+            provider: "tmdb",
+            query: Some("The Matrix (1999)".to_string()),
+            candidate_count: Some(2),
+            outcome,
+            duration_ms: 12,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn list_for_media_returns_newest_first() {
+        let pool = fresh_pool().await;
+        append(&pool, &entry(1, OUTCOME_NO_MATCH)).await.unwrap();
+        append(&pool, &entry(1, OUTCOME_MATCHED)).await.unwrap();
+        append(&pool, &entry(2, OUTCOME_ERROR)).await.unwrap();
+
+        let entries = list_for_media(&pool, "movie", 1, 10).await.unwrap();
+        let outcomes: Vec<&str> = entries.iter().map(|e| e.outcome.as_str()).collect();
+        assert_eq!(outcomes, vec![OUTCOME_MATCHED, OUTCOME_NO_MATCH]);
+        assert_eq!(entries[0].candidate_count, Some(2));
+    }
+
+    #[tokio::test]
+    async fn prune_drops_only_old_rows() {
+        let pool = fresh_pool().await;
+        append(&pool, &entry(1, OUTCOME_NO_MATCH)).await.unwrap();
+        append(&pool, &entry(1, OUTCOME_MATCHED)).await.unwrap();
+        sqlx::query(
+            "UPDATE metadata_job_log SET logged_at = strftime('%s','now') - 40 * 86400
+             WHERE outcome = 'no_match'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let removed = prune(&pool, 30 * 86_400).await.unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(list_for_media(&pool, "movie", 1, 10).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,461 @@
+//! Bulk link import. A CSV or JSON file maps titles to
+//! `(provider, provider_id)`; each entry names its title by a movie file,
+//! an episode file or show folder, a movie's folder, or a row id. Every
+//! entry is checked first — unknown titles, provider ids another title
+//! already holds (`idx_movies_provider`/`idx_shows_provider`) and clashes
+//! within the file are reported — and only a clean file is linked, all
+//! in one transaction through `link_in_tx`, like the Needs-review batch
+//! approve.
+
+use std::collections::HashMap;
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::queries;
+
+/// One entry of the mapping file. A title is named either by `path` or
+/// by `kind` and `id`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LinkMapping {
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub provider: String,
+    #[serde(default)]
+    pub provider_id: String,
+}
+
+/// A mapping entry with where it came from: the CSV line, or the JSON
+/// array position counting from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberedMapping {
+    pub line: usize,
+    pub mapping: LinkMapping,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ImportProblem {
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ImportReport {
+    /// Entries that resolved to a title and would link cleanly.
+    pub valid: usize,
+    pub problems: Vec<ImportProblem>,
+    /// Whether the links were written. False on a dry run or when any
+    /// entry has a problem.
+    pub applied: bool,
+}
+
+/// Reads a mapping file, picking the format by extension.
+pub fn parse_file(path: &str, contents: &str) -> AppResult<Vec<NumberedMapping>> {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("csv") => parse_csv(contents),
+        Some("json") => parse_json(contents),
+        _ => Err(AppError::Other("link import needs a .csv or .json file".to_string())),
+    }
+}
+
+/// CSV with a header row naming the `LinkMapping` columns, in any order.
+/// Empty cells count as missing.
+pub fn parse_csv(contents: &str) -> AppResult<Vec<NumberedMapping>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|error| AppError::Other(format!("read CSV header: {error}")))?
+        .clone();
+    let mut mappings = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|error| AppError::Other(format!("read CSV: {error}")))?;
+        let line = record.position().map_or(0, |position| position.line() as usize);
+        let cell = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.eq_ignore_ascii_case(name))
+                .and_then(|index| record.get(index))
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let id = match cell("id") {
+            Some(value) => match value.parse() {
+                Ok(id) => Some(id),
+                Err(_) => return Err(AppError::Other(format!("line {line}: bad id {value:?}"))),
+            },
+            None => None,
+        };
+        mappings.push(NumberedMapping {
+            line,
+            mapping: LinkMapping {
+                path: cell("path"),
+                kind: cell("kind"),
+                id,
+                provider: cell("provider").unwrap_or_default(),
+                provider_id: cell("provider_id").unwrap_or_default(),
+            },
+        });
+    }
+    Ok(mappings)
+}
+
+/// A JSON array of `LinkMapping` objects.
+pub fn parse_json(contents: &str) -> AppResult<Vec<NumberedMapping>> {
+    let mappings: Vec<LinkMapping> = serde_json::from_str(contents)
+        .map_err(|error| AppError::Other(format!("read JSON: {error}")))?;
+    Ok(mappings
+        .into_iter()
+        .enumerate()
+        .map(|(index, mapping)| NumberedMapping {
+            line: index + 1,
+            mapping,
+        })
+        .collect())
+}
+
+/// A title an entry resolved to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Target {
+    kind: &'static str,
+    id: i64,
+}
+
+impl Target {
+    fn table(&self) -> &'static str {
+        if self.kind == "movie" {
+            "movies"
+        } else {
+            "shows"
+        }
+    }
+}
+
+/// The title `path` names: a movie file, a show folder, an episode file
+/// (its show) or a folder holding exactly one movie.
+async fn resolve_path(conn: &mut SqliteConnection, path: &str) -> AppResult<Option<Target>> {
+    let path = path.trim_end_matches(['/', '\\']);
+    let movie: Option<i64> = sqlx::query_scalar("SELECT id FROM movies WHERE path = ?1")
+        .bind(path)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if let Some(id) = movie {
+        return Ok(Some(Target { kind: "movie", id }));
+    }
+    let show: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM shows WHERE folder_path = ?1
+         UNION ALL
+         SELECT show_id FROM episodes WHERE path = ?1
+         LIMIT 1",
+    )
+    .bind(path)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some(id) = show {
+        return Ok(Some(Target { kind: "show", id }));
+    }
+    let in_folder: Vec<i64> = sqlx::query_scalar(
+        "SELECT id FROM movies
+         WHERE substr(path, 1, length(?1) + 1) IN (?1 || '/', ?1 || '\\')
+           AND instr(replace(substr(path, length(?1) + 2), '\\', '/'), '/') = 0
+         LIMIT 2",
+    )
+    .bind(path)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(match in_folder.as_slice() {
+        [id] => Some(Target { kind: "movie", id: *id }),
+        _ => None,
+    })
+}
+
+/// Checks one entry's shape and finds its title.
+async fn resolve(
+    conn: &mut SqliteConnection,
+    mapping: &LinkMapping,
+) -> AppResult<Result<Target, String>> {
+    if !matches!(mapping.provider.as_str(), "tmdb" | "imdb") {
+        return Ok(Err(format!("unknown provider {:?}", mapping.provider)));
+    }
+    if mapping.provider_id.trim().is_empty() {
+        return Ok(Err("missing provider_id".to_string()));
+    }
+
+    if let Some(path) = mapping.path.as_deref().filter(|path| !path.is_empty()) {
+        return Ok(resolve_path(conn, path).await?.ok_or_else(|| format!("unknown path {path}")));
+    }
+
+    let (Some(kind), Some(id)) = (mapping.kind.as_deref(), mapping.id) else {
+        return Ok(Err("needs a path, or a kind and id".to_string()));
+    };
+    let target = match kind {
+        "movie" => Target { kind: "movie", id },
+        "show" => Target { kind: "show", id },
+        other => return Ok(Err(format!("unknown kind {other:?}"))),
+    };
+    let exists: Option<i64> =
+        sqlx::query_scalar(&format!("SELECT id FROM {} WHERE id = ?1", target.table()))
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    Ok(exists.map(|_| target).ok_or_else(|| format!("no {kind} with id {id}")))
+}
+
+/// Validates `mappings` and, unless `dry_run` or anything is wrong,
+/// links every title in one transaction and enqueues it for the worker.
+pub async fn import(
+    pool: &SqlitePool,
+    mappings: &[NumberedMapping],
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let mut tx = pool.begin().await?;
+    let mut problems = Vec::new();
+    let mut planned: Vec<(Target, &str, &str)> = Vec::new();
+    // Where each title and each provider id was first claimed in the file.
+    let mut titles: HashMap<Target, usize> = HashMap::new();
+    let mut provider_ids: HashMap<(&str, &str, &str), usize> = HashMap::new();
+
+    for NumberedMapping { line, mapping } in mappings {
+        let target = match resolve(&mut tx, mapping).await? {
+            Ok(target) => target,
+            Err(reason) => {
+                problems.push(ImportProblem { line: *line, reason });
+                continue;
+            }
+        };
+        let Target { kind, id } = target;
+        let provider = mapping.provider.as_str();
+        let provider_id = mapping.provider_id.trim();
+        if let Some(first) = titles.insert(target, *line) {
+            problems.push(ImportProblem {
+                line: *line,
+                reason: format!("{kind} {id} is already mapped on line {first}"),
+            });
+            continue;
+        }
+        if let Some(first) = provider_ids.insert((kind, provider, provider_id), *line) {
+            problems.push(ImportProblem {
+                line: *line,
+                reason: format!("{provider} {provider_id} is already mapped on line {first}"),
+            });
+            continue;
+        }
+        planned.push((target, provider, provider_id));
+    }
+
+    // A provider id another title holds collides unless the file moves
+    // that title elsewhere too.
+    for (target, provider, provider_id) in &planned {
+        let holder: Option<i64> = sqlx::query_scalar(&format!(
+            "SELECT id FROM {} WHERE provider = ?1 AND provider_id = ?2 AND id != ?3",
+            target.table()
+        ))
+        .bind(provider)
+        .bind(provider_id)
+        .bind(target.id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(holder) = holder else {
+            continue;
+        };
+        let holder = Target { kind: target.kind, id: holder };
+        if !titles.contains_key(&holder) {
+            problems.push(ImportProblem {
+                line: titles[target],
+                reason: format!(
+                    "{provider} {provider_id} is already linked to {} {}",
+                    holder.kind, holder.id
+                ),
+            });
+        }
+    }
+    problems.sort_by_key(|problem| problem.line);
+
+    let valid = planned.len();
+    if dry_run || !problems.is_empty() {
+        return Ok(ImportReport {
+            valid,
+            problems,
+            applied: false,
+        });
+    }
+
+    // Unlink first so titles trading provider ids don't trip the unique
+    // index halfway through.
+    for (target, _, _) in &planned {
+        sqlx::query(&format!(
+            "UPDATE {} SET provider = NULL, provider_id = NULL WHERE id = ?1",
+            target.table()
+        ))
+        .bind(target.id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    for (target, provider, provider_id) in &planned {
+        queries::link_in_tx(&mut tx, target.kind, target.id, provider, provider_id).await?;
+    }
+    tx.commit().await?;
+
+    Ok(ImportReport {
+        valid,
+        problems,
+        applied: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn by_path(line: usize, path: &str, provider: &str, provider_id: &str) -> NumberedMapping {
+        NumberedMapping {
+            line,
+            mapping: LinkMapping {
+                path: Some(path.to_string()),
+                provider: provider.to_string(),
+                provider_id: provider_id.to_string(),
+                ..LinkMapping::default()
+            },
+        }
+    }
+
+    fn by_id(line: usize, kind: &str, id: i64, provider: &str, provider_id: &str) -> NumberedMapping {
+        NumberedMapping {
+            line,
+            mapping: LinkMapping {
+                kind: Some(kind.to_string()),
+                id: Some(id),
+                provider: provider.to_string(),
+                provider_id: provider_id.to_string(),
+                ..LinkMapping::default()
+            },
+        }
+    }
+
+    async fn links(pool: &SqlitePool) -> Vec<(i64, Option<String>)> {
+        sqlx::query_as("SELECT id, provider_id FROM movies ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn csv_columns_come_in_any_order() {
+        let csv = "provider_id,provider,path,kind,id\n\
+                   603,tmdb,\"/movies/Matrix, The (1999).mkv\",,\n\
+                   tt0944947,imdb,,show,7\n";
+        let mappings = parse_csv(csv).unwrap();
+        assert_eq!(mappings[0].line, 2);
+        assert_eq!(mappings[0].mapping.path.as_deref(), Some("/movies/Matrix, The (1999).mkv"));
+        assert_eq!(mappings[0].mapping.id, None);
+        assert_eq!(mappings[1], by_id(3, "show", 7, "imdb", "tt0944947"));
+        assert!(parse_csv("kind,id,provider,provider_id\nmovie,seven,tmdb,1\n").is_err());
+        assert!(parse_file("links.txt", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn only_a_clean_file_is_linked() {
+        let pool = fresh_pool().await;
+        sqlx::query(
+            "INSERT INTO libraries (id, path, kind) VALUES (1, '/m', 'movies'), (2, '/tv', 'series')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path, provider, provider_id) VALUES
+                 (1, 1, 'Alien', '/m/Alien (1979)/Alien.mkv', NULL, NULL),
+                 (2, 1, 'Heat', '/m/Heat.mkv', NULL, NULL),
+                 (3, 1, 'The Matrix', '/m/Matrix.mkv', 'tmdb', '603'),
+                 (4, 1, 'Matrix Reloaded', '/m/Reloaded.mkv', NULL, NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO shows (id, library_id, title, folder_path, fingerprint)
+             VALUES (1, 2, 'Lost', '/tv/Lost', 'a')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO episodes (show_id, season, episode, title, path)
+             VALUES (1, 1, 1, 'Pilot', '/tv/Lost/s01e01.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let good = vec![
+            by_path(2, "/m/Alien (1979)/", "tmdb", "348"),
+            by_path(3, "/tv/Lost/s01e01.mkv", "tmdb", "4607"),
+            by_id(4, "movie", 2, "imdb", "tt0113277"),
+        ];
+        let mut bad = good.clone();
+        bad.extend([
+            by_path(5, "/nowhere.mkv", "tmdb", "1"),
+            by_id(6, "movie", 2, "tmdb", "949"),
+            by_id(7, "movie", 4, "tmdb", "603"),
+            by_id(8, "movie", 1, "tvdb", "1"),
+        ]);
+        let report = import(&pool, &bad, false).await.unwrap();
+        let lines: Vec<usize> = report.problems.iter().map(|problem| problem.line).collect();
+        assert_eq!(lines, vec![5, 6, 7, 8]);
+        assert!(report.problems[2].reason.contains("already linked to movie 3"));
+        assert!(!report.applied);
+
+        let report = import(&pool, &good, true).await.unwrap();
+        assert_eq!((report.valid, report.applied), (3, false));
+        assert_eq!(links(&pool).await[0], (1, None));
+
+        // The Matrix hands 603 to Reloaded in the same file, so that's no collision.
+        let mut clean = good.clone();
+        clean.extend([by_id(5, "movie", 3, "tmdb", "604"), by_id(6, "movie", 4, "tmdb", "603")]);
+        let report = import(&pool, &clean, false).await.unwrap();
+        assert!(report.applied, "{:?}", report.problems);
+        assert_eq!(
+            links(&pool).await,
+            vec![
+                (1, Some("348".to_string())),
+                (2, Some("tt0113277".to_string())),
+                (3, Some("604".to_string())),
+                (4, Some("603".to_string())),
+            ]
+        );
+        let show: Option<String> = sqlx::query_scalar("SELECT provider_id FROM shows WHERE id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(show.as_deref(), Some("4607"));
+        let jobs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM metadata_jobs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(jobs, 5);
+    }
+}
@@ -0,0 +1,263 @@
+//! Scrape language. Every provider call takes the `scrape_language`
+//! setting; fields the provider has no translation for fall back to
+//! English. Localized titles and overviews are kept per language in
+//! `localized_metadata`, so switching language only re-fetches titles
+//! that were never fetched in the new one.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::AppResult;
+use crate::metadata::queries::PRIORITY_REFRESH;
+use crate::queries as app_queries;
+
+/// Language used when the configured one has no translation.
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// Title/overview as returned in one language. `None` fields had no
+/// translation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedText {
+    pub language: String,
+    pub title: Option<String>,
+    pub overview: Option<String>,
+}
+
+impl LocalizedText {
+    pub fn new(language: &str, title: Option<&str>, overview: Option<&str>) -> Self {
+        Self {
+            language: language.to_string(),
+            title: non_empty(title),
+            overview: non_empty(overview),
+        }
+    }
+}
+
+/// `scrape_language`, falling back to `default_for`.
+pub async fn scrape_language(pool: &SqlitePool) -> AppResult<String> {
+    let stored = app_queries::get_app_setting(pool, "scrape_language").await?;
+    Ok(stored
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or_else(|| app_queries::default_for("scrape_language").map(str::to_string))
+        .unwrap_or_else(|| FALLBACK_LANGUAGE.to_string()))
+}
+
+/// True for `en` and any regional English (`en-GB`, …) — no fallback
+/// request is worth making.
+pub fn is_fallback(language: &str) -> bool {
+    language == FALLBACK_LANGUAGE || language.starts_with("en-")
+}
+
+/// IMDB wants a full locale (`de-DE`). Bare languages get their most
+/// common region.
+pub fn imdb_locale(language: &str) -> String {
+    if language.contains('-') {
+        return language.to_string();
+    }
+    let region = match language {
+        "en" => "US",
+        "ja" => "JP",
+        "ko" => "KR",
+        "zh" => "CN",
+        "sv" => "SE",
+        "da" => "DK",
+        "cs" => "CZ",
+        "el" => "GR",
+        "uk" => "UA",
+        "he" => "IL",
+        "hi" => "IN",
+        other => return format!("{other}-{}", other.to_uppercase()),
+    };
+    format!("{language}-{region}")
+}
+
+/// Upserts the fetched languages for one title. A re-fetch overwrites
+/// fields it got and keeps the ones it didn't.
+pub async fn store_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    texts: &[LocalizedText],
+) -> AppResult<()> {
+    for text in texts {
+        sqlx::query(
+            "INSERT INTO localized_metadata (kind, media_id, language, title, overview)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(kind, media_id, language) DO UPDATE SET
+                 title = COALESCE(excluded.title, title),
+                 overview = COALESCE(excluded.overview, overview),
+                 fetched_at = strftime('%s','now')",
+        )
+        .bind(kind)
+        .bind(media_id)
+        .bind(&text.language)
+        .bind(text.title.as_deref())
+        .bind(text.overview.as_deref())
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Points every unlocked title's overview at the stored text for
+/// `language` (or English where that has none). Titles never fetched in
+/// `language` are left alone. Returns the number of rows updated.
+pub async fn apply_language(pool: &SqlitePool, language: &str) -> AppResult<u64> {
+    let mut updated = 0;
+
+    for (kind, table) in [("movie", "movies"), ("show", "shows")] {
+        updated += sqlx::query(&format!(
+            "UPDATE {table} SET overview = (
+                 SELECT COALESCE(l.overview, en.overview)
+                 FROM localized_metadata l
+                 LEFT JOIN localized_metadata en
+                   ON en.kind = l.kind AND en.media_id = l.media_id AND en.language = ?3
+                 WHERE l.kind = ?1 AND l.media_id = {table}.id AND l.language = ?2
+             )
+             WHERE metadata_locked = 0
+               AND EXISTS (
+                 SELECT 1 FROM localized_metadata l
+                 LEFT JOIN localized_metadata en
+                   ON en.kind = l.kind AND en.media_id = l.media_id AND en.language = ?3
+                 WHERE l.kind = ?1 AND l.media_id = {table}.id AND l.language = ?2
+                   AND COALESCE(l.overview, en.overview) IS NOT NULL
+               )"
+        ))
+        .bind(kind)
+        .bind(language)
+        .bind(FALLBACK_LANGUAGE)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(updated)
+}
+
+/// Queues a low-priority refresh for every linked, unlocked title with
+/// nothing stored in `language`. Returns the number of jobs added.
+pub async fn enqueue_missing(pool: &SqlitePool, language: &str) -> AppResult<u64> {
+    let mut inserted = 0;
+
+    for (kind, table) in [("movie", "movies"), ("show", "shows")] {
+        inserted += sqlx::query(&format!(
+            "INSERT INTO metadata_jobs (kind, media_id, priority)
+             SELECT ?1, id, ?3 FROM {table}
+             WHERE provider IS NOT NULL
+               AND metadata_locked = 0
+               AND NOT EXISTS (
+                 SELECT 1 FROM localized_metadata l
+                 WHERE l.kind = ?1 AND l.media_id = {table}.id AND l.language = ?2
+               )
+             ON CONFLICT(kind, media_id) DO NOTHING"
+        ))
+        .bind(kind)
+        .bind(language)
+        .bind(PRIORITY_REFRESH)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(inserted)
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    async fn seed_movie(pool: &SqlitePool, id: i64, provider_id: Option<&str>) {
+        sqlx::query("INSERT OR IGNORE INTO libraries (id, path, kind) VALUES (1, '/tmp', 'movies')")
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path, overview, provider, provider_id)
+             VALUES (?1, 1, 'Movie', '/tmp/' || ?1 || '.mkv', 'English plot', ?2, ?3)",
+        )
+        .bind(id)
+        .bind(provider_id.map(|_| "tmdb"))
+        .bind(provider_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn store(pool: &SqlitePool, id: i64, texts: &[LocalizedText]) {
+        let mut conn = pool.acquire().await.unwrap();
+        store_in_tx(&mut conn, "movie", id, texts).await.unwrap();
+    }
+
+    #[test]
+    fn imdb_locale_adds_a_region() {
+        assert_eq!(imdb_locale("de-AT"), "de-AT");
+        assert_eq!(imdb_locale("de"), "de-DE");
+        assert_eq!(imdb_locale("en"), "en-US");
+        assert_eq!(imdb_locale("ja"), "ja-JP");
+        assert!(is_fallback("en-GB"));
+        assert!(!is_fallback("es"));
+    }
+
+    #[tokio::test]
+    async fn switching_language_reuses_stored_text_and_queues_the_rest() {
+        let pool = fresh_pool().await;
+        seed_movie(&pool, 1, Some("1")).await;
+        seed_movie(&pool, 2, Some("2")).await;
+        seed_movie(&pool, 3, None).await;
+
+        store(
+            &pool,
+            1,
+            &[
+                LocalizedText::new("de", Some("Der Film"), None),
+                LocalizedText::new("en", Some("The Movie"), Some("English plot")),
+            ],
+        )
+        .await;
+        store(&pool, 1, &[LocalizedText::new("de", None, Some("Deutsche Handlung"))]).await;
+
+        assert_eq!(apply_language(&pool, "de").await.unwrap(), 1);
+        let overview: String = sqlx::query_scalar("SELECT overview FROM movies WHERE id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(overview, "Deutsche Handlung");
+        let title: Option<String> = sqlx::query_scalar(
+            "SELECT title FROM localized_metadata WHERE media_id = 1 AND language = 'de'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(title.as_deref(), Some("Der Film"));
+
+        // Only the linked movie without German text is queued.
+        assert_eq!(enqueue_missing(&pool, "de").await.unwrap(), 1);
+        let queued: Vec<i64> = sqlx::query_scalar("SELECT media_id FROM metadata_jobs")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, vec![2]);
+    }
+}
@@ -0,0 +1,516 @@
+//! Manual metadata edits. `apply` validates a typed patch and writes it
+//! the way a provider payload would be written — the row's columns plus
+//! the genre, credit and rating tables — under the `manual` source, then
+//! locks the row so sync leaves the curated values alone. Titles no
+//! provider knows (home videos, obscure releases) can be filled in
+//! entirely this way.
+
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::genres;
+use crate::metadata::people::{self, CreditInput};
+use crate::metadata::ratings::{self, RatingInput};
+
+/// Source recorded for hand-entered genres, people and ratings.
+pub const SOURCE: &str = "manual";
+
+/// Fields a patch can clear through `MetadataPatch::clear`. Genres and
+/// cast clear with an empty list instead; a title always needs a title.
+const CLEARABLE: [&str; 5] = ["year", "overview", "rating", "runtime_minutes", "first_air_date"];
+
+/// Fields to change. Only fields passed as `Some` or named in `clear` are
+/// touched; an empty `genres` or `cast` list clears them.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct MetadataPatch {
+    pub title: Option<String>,
+    pub year: Option<i32>,
+    pub overview: Option<String>,
+    pub genres: Option<Vec<String>>,
+    pub cast: Option<Vec<CastEntry>>,
+    /// Out of 10, like `rating`.
+    pub rating: Option<f64>,
+    /// Movies only.
+    pub runtime_minutes: Option<i64>,
+    /// Shows only; `YYYY-MM-DD`.
+    pub first_air_date: Option<String>,
+    /// Fields to set back to NULL, from `CLEARABLE`.
+    #[serde(default)]
+    pub clear: Vec<String>,
+}
+
+/// One cast member, in billing order within `MetadataPatch::cast`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CastEntry {
+    pub name: String,
+    pub character: Option<String>,
+}
+
+impl MetadataPatch {
+    /// Checks every field and returns the patch with text trimmed,
+    /// blank characters dropped and duplicate genres removed.
+    pub fn validated(&self, kind: &str) -> AppResult<MetadataPatch> {
+        let invalid = |message: &str| Err(AppError::Other(message.to_string()));
+        if kind != "movie" && kind != "show" {
+            return Err(AppError::Other(format!("unknown kind: {kind}")));
+        }
+        let mut clear: Vec<String> = Vec::new();
+        for field in &self.clear {
+            if !CLEARABLE.contains(&field.as_str()) {
+                return Err(AppError::Other(format!("{field} can't be cleared")));
+            }
+            if !clear.contains(field) {
+                clear.push(field.clone());
+            }
+        }
+        let cleared = |field: &str| clear.iter().any(|name| name == field);
+        let set_and_cleared = [
+            ("year", self.year.is_some()),
+            ("overview", self.overview.is_some()),
+            ("rating", self.rating.is_some()),
+            ("runtime_minutes", self.runtime_minutes.is_some()),
+            ("first_air_date", self.first_air_date.is_some()),
+        ]
+        .into_iter()
+        .find(|(field, set)| *set && cleared(field));
+        if let Some((field, _)) = set_and_cleared {
+            return Err(AppError::Other(format!("{field} can't be both set and cleared")));
+        }
+
+        let title = self.title.as_deref().map(str::trim);
+        if title == Some("") {
+            return invalid("title can't be empty");
+        }
+        if self.year.is_some_and(|year| !(1800..=2100).contains(&year)) {
+            return invalid("year must be between 1800 and 2100");
+        }
+        if self.rating.is_some_and(|rating| !(0.0..=10.0).contains(&rating)) {
+            return invalid("rating must be between 0 and 10");
+        }
+        if (self.runtime_minutes.is_some() || cleared("runtime_minutes")) && kind != "movie" {
+            return invalid("only movies have a runtime");
+        }
+        if self.runtime_minutes.is_some_and(|minutes| minutes <= 0) {
+            return invalid("runtime must be a positive number of minutes");
+        }
+        if (self.first_air_date.is_some() || cleared("first_air_date")) && kind != "show" {
+            return invalid("only shows have a first air date");
+        }
+        let first_air_date = self.first_air_date.as_deref().map(str::trim);
+        if first_air_date.is_some_and(|date| !is_valid_date(date)) {
+            return invalid("first air date must be a YYYY-MM-DD date");
+        }
+
+        let genres = self.genres.as_ref().map(|names| {
+            let mut kept: Vec<String> = Vec::new();
+            for name in names.iter().map(|name| name.trim()).filter(|name| !name.is_empty()) {
+                if !kept.iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+                    kept.push(name.to_string());
+                }
+            }
+            kept
+        });
+
+        let cast = match &self.cast {
+            Some(entries) => {
+                let mut kept = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    let name = entry.name.trim();
+                    if name.is_empty() {
+                        return invalid("every cast member needs a name");
+                    }
+                    kept.push(CastEntry {
+                        name: name.to_string(),
+                        character: entry
+                            .character
+                            .as_deref()
+                            .map(str::trim)
+                            .filter(|character| !character.is_empty())
+                            .map(str::to_string),
+                    });
+                }
+                Some(kept)
+            }
+            None => None,
+        };
+
+        Ok(MetadataPatch {
+            title: title.map(str::to_string),
+            year: self.year,
+            overview: self.overview.clone(),
+            genres,
+            cast,
+            rating: self.rating,
+            runtime_minutes: self.runtime_minutes,
+            first_air_date: first_air_date.map(str::to_string),
+            clear,
+        })
+    }
+
+    /// True when `field` is named in `clear`.
+    fn clears(&self, field: &str) -> bool {
+        self.clear.iter().any(|name| name == field)
+    }
+
+    /// True when no field is set or cleared. An empty `genres` or `cast`
+    /// list still counts, since it clears them.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.year.is_none()
+            && self.overview.is_none()
+            && self.genres.is_none()
+            && self.cast.is_none()
+            && self.rating.is_none()
+            && self.runtime_minutes.is_none()
+            && self.first_air_date.is_none()
+            && self.clear.is_empty()
+    }
+}
+
+/// `YYYY-MM-DD` with a day that exists in its month.
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) =
+        (year.parse::<i32>(), month.parse::<u32>(), day.parse::<u32>())
+    else {
+        return false;
+    };
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+/// Hand-entered people have no provider id; their name stands in, so the
+/// same name across titles is one person.
+fn cast_credits(cast: &[CastEntry]) -> Vec<CreditInput> {
+    cast.iter()
+        .enumerate()
+        .map(|(order, entry)| CreditInput {
+            person_provider_id: entry.name.to_lowercase(),
+            name: entry.name.clone(),
+            photo_url: None,
+            role: "cast",
+            job: None,
+            character: entry.character.clone(),
+            order: order as i64,
+        })
+        .collect()
+}
+
+/// `top_cast` JSON in the shape sync writes.
+fn cast_json(cast: &[CastEntry]) -> String {
+    let payload: Vec<serde_json::Value> = cast
+        .iter()
+        .take(10)
+        .enumerate()
+        .map(|(order, entry)| {
+            serde_json::json!({
+                "name": entry.name,
+                "character": entry.character,
+                "order": order,
+            })
+        })
+        .collect();
+    serde_json::to_string(&payload).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Validates `patch` and writes it in one transaction, locking the row.
+/// A patch with no fields set writes nothing and leaves the lock as is.
+pub async fn apply(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    patch: &MetadataPatch,
+) -> AppResult<()> {
+    let patch = patch.validated(kind)?;
+    let table = if kind == "movie" { "movies" } else { "shows" };
+    // Nothing to write, so nothing to protect from sync: leave the lock.
+    if patch.is_empty() {
+        let exists: bool =
+            sqlx::query_scalar(&format!("SELECT EXISTS(SELECT 1 FROM {table} WHERE id = ?1)"))
+                .bind(media_id)
+                .fetch_one(pool)
+                .await?;
+        return if exists { Ok(()) } else { Err(AppError::MediaNotFound(media_id)) };
+    }
+    let genres_json = patch
+        .genres
+        .as_ref()
+        .map(|names| serde_json::to_string(names).unwrap_or_else(|_| "[]".to_string()));
+    let top_cast = patch.cast.as_deref().map(cast_json);
+
+    let rating_changed = patch.rating.is_some() || patch.clears("rating");
+
+    let mut tx = pool.begin().await?;
+    // A field is written when it's set or cleared; a cleared one is None.
+    let updated = sqlx::query(&format!(
+        "UPDATE {table} SET
+             title = COALESCE(?1, title),
+             year = CASE WHEN ?2 THEN ?3 ELSE year END,
+             overview = CASE WHEN ?4 THEN ?5 ELSE overview END,
+             genres = COALESCE(?6, genres),
+             top_cast = COALESCE(?7, top_cast),
+             rating = CASE WHEN ?8 THEN ?9 ELSE rating END,
+             metadata_locked = 1
+         WHERE id = ?10"
+    ))
+    .bind(patch.title.as_deref())
+    .bind(patch.year.is_some() || patch.clears("year"))
+    .bind(patch.year)
+    .bind(patch.overview.is_some() || patch.clears("overview"))
+    .bind(patch.overview.as_deref())
+    .bind(genres_json.as_deref())
+    .bind(top_cast.as_deref())
+    .bind(rating_changed)
+    .bind(patch.rating)
+    .bind(media_id)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::MediaNotFound(media_id));
+    }
+    // `validated` only lets each of these through for its own kind.
+    if patch.runtime_minutes.is_some() || patch.clears("runtime_minutes") {
+        sqlx::query("UPDATE movies SET runtime_minutes = ?1 WHERE id = ?2")
+            .bind(patch.runtime_minutes)
+            .bind(media_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    if patch.first_air_date.is_some() || patch.clears("first_air_date") {
+        sqlx::query("UPDATE shows SET first_air_date = ?1 WHERE id = ?2")
+            .bind(&patch.first_air_date)
+            .bind(media_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    if let Some(names) = &patch.genres {
+        genres::store_in_tx(&mut tx, kind, media_id, SOURCE, names).await?;
+    }
+    if let Some(cast) = &patch.cast {
+        people::store_cast_in_tx(&mut tx, kind, media_id, SOURCE, &cast_credits(cast)).await?;
+    }
+    if rating_changed {
+        let rating = patch.rating.map(|value| RatingInput {
+            value,
+            scale: 10.0,
+            votes: None,
+        });
+        ratings::store_in_tx(&mut tx, kind, media_id, SOURCE, rating).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn cast(name: &str, character: Option<&str>) -> CastEntry {
+        CastEntry {
+            name: name.to_string(),
+            character: character.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn patches_are_validated_and_tidied() {
+        let rejected = [
+            ("movie", MetadataPatch { title: Some("  ".into()), ..Default::default() }),
+            ("movie", MetadataPatch { rating: Some(11.0), ..Default::default() }),
+            ("movie", MetadataPatch { runtime_minutes: Some(0), ..Default::default() }),
+            ("show", MetadataPatch { runtime_minutes: Some(90), ..Default::default() }),
+            ("movie", MetadataPatch { first_air_date: Some("2020-01-01".into()), ..Default::default() }),
+            ("show", MetadataPatch { first_air_date: Some("2023-02-29".into()), ..Default::default() }),
+            ("show", MetadataPatch { cast: Some(vec![cast(" ", None)]), ..Default::default() }),
+            ("movie", MetadataPatch { clear: vec!["title".into()], ..Default::default() }),
+            ("show", MetadataPatch { clear: vec!["runtime_minutes".into()], ..Default::default() }),
+            (
+                "movie",
+                MetadataPatch { year: Some(1998), clear: vec!["year".into()], ..Default::default() },
+            ),
+        ];
+        for (kind, patch) in &rejected {
+            assert!(patch.validated(kind).is_err(), "{kind} {patch:?}");
+        }
+
+        let patch = MetadataPatch {
+            title: Some(" Home Movies ".into()),
+            genres: Some(vec!["Family".into(), " ".into(), "family".into(), "Comedy".into()]),
+            cast: Some(vec![cast(" Ann ", Some(" ")), cast("Bob", Some("Dad"))]),
+            first_air_date: Some("2024-02-29".into()),
+            ..Default::default()
+        };
+        let tidy = patch.validated("show").unwrap();
+        assert_eq!(tidy.title.as_deref(), Some("Home Movies"));
+        assert_eq!(tidy.genres, Some(vec!["Family".to_string(), "Comedy".to_string()]));
+        assert_eq!(tidy.cast, Some(vec![cast("Ann", None), cast("Bob", Some("Dad"))]));
+    }
+
+    #[tokio::test]
+    async fn apply_writes_every_store_and_keeps_the_crew() {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path) VALUES (1, 1, 'clip', '/movies/clip.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let director = CreditInput {
+            person_provider_id: "d1".into(),
+            name: "Dana".into(),
+            photo_url: None,
+            role: "director",
+            job: Some("Director".into()),
+            character: None,
+            order: 0,
+        };
+        let mut conn = pool.acquire().await.unwrap();
+        people::store_in_tx(&mut conn, "movie", 1, "tmdb", &[director]).await.unwrap();
+        drop(conn);
+
+        let patch = MetadataPatch {
+            title: Some("Summer 1998".into()),
+            year: Some(1998),
+            genres: Some(vec!["Family".into()]),
+            cast: Some(vec![cast("Ann", Some("Herself")), cast("Bob", None)]),
+            rating: Some(9.5),
+            runtime_minutes: Some(42),
+            ..Default::default()
+        };
+        apply(&pool, "movie", 1, &patch).await.unwrap();
+
+        let (title, year, runtime, top_cast, locked): (String, i32, i64, String, i64) =
+            sqlx::query_as(
+                "SELECT title, year, runtime_minutes, top_cast, metadata_locked
+                 FROM movies WHERE id = 1",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!((title.as_str(), year, runtime, locked), ("Summer 1998", 1998, 42, 1));
+        assert!(top_cast.contains(r#""character":"Herself""#));
+
+        let credits: Vec<(String, String)> = people::for_title(&pool, "movie", 1)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|credit| (credit.name, credit.role))
+            .collect();
+        assert_eq!(
+            credits,
+            vec![
+                ("Ann".to_string(), "cast".to_string()),
+                ("Bob".to_string(), "cast".to_string()),
+                ("Dana".to_string(), "director".to_string()),
+            ]
+        );
+        let genre_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM movie_genres WHERE movie_id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(genre_count, 1);
+        let stored = ratings::for_title(&pool, "movie", 1).await.unwrap();
+        assert_eq!((stored[0].source.as_str(), stored[0].value), (SOURCE, 9.5));
+
+        assert!(matches!(
+            apply(&pool, "movie", 2, &MetadataPatch::default()).await,
+            Err(AppError::MediaNotFound(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn empty_patch_leaves_the_title_unlocked() {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path) VALUES (1, 1, 'clip', '/movies/clip.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        apply(&pool, "movie", 1, &MetadataPatch::default()).await.unwrap();
+
+        let locked: i64 = sqlx::query_scalar("SELECT metadata_locked FROM movies WHERE id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(locked, 0);
+    }
+
+    #[tokio::test]
+    async fn cleared_fields_are_set_to_null() {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path) VALUES (1, 1, 'clip', '/movies/clip.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let filled = MetadataPatch {
+            year: Some(1998),
+            overview: Some("Wrong tape.".into()),
+            rating: Some(7.0),
+            runtime_minutes: Some(42),
+            ..Default::default()
+        };
+        apply(&pool, "movie", 1, &filled).await.unwrap();
+
+        let patch = MetadataPatch {
+            title: Some("Summer 1998".into()),
+            clear: vec!["overview".into(), "rating".into(), "runtime_minutes".into()],
+            ..Default::default()
+        };
+        apply(&pool, "movie", 1, &patch).await.unwrap();
+
+        type Row = (String, Option<i32>, Option<String>, Option<f64>, Option<i64>);
+        let row: Row = sqlx::query_as(
+            "SELECT title, year, overview, rating, runtime_minutes FROM movies WHERE id = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(row, ("Summer 1998".to_string(), Some(1998), None, None, None));
+        assert!(ratings::for_title(&pool, "movie", 1).await.unwrap().is_empty());
+    }
+}
@@ -12,6 +12,13 @@ pub struct MatchCandidate {
     pub provider_id: String,
     pub title: String,
     pub year: Option<i32>,
+    /// Absolute poster thumbnail URL, when the search result carries one.
+    #[serde(default)]
+    pub poster_url: Option<String>,
+    /// Short description from the search result (TMDB overview, IMDB's
+    /// top-billed names).
+    #[serde(default)]
+    pub overview: Option<String>,
 }
 
 /// Returns `Some(candidate)` only when query_title (after normalization)
@@ -37,6 +44,55 @@ pub fn pick_confident_match<'a>(
     }
 }
 
+/// How well `candidate` fits the scanned title and year, from 0.0 to 1.0.
+/// Used to rank suggestions for review; auto-linking still goes through
+/// `pick_confident_match`.
+pub fn score(query_title: &str, query_year: Option<i32>, candidate: &MatchCandidate) -> f64 {
+    let title = title_similarity(&normalize(query_title), &normalize(&candidate.title));
+    let year = match (candidate.year, query_year) {
+        (_, None) => 1.0,
+        (None, Some(_)) => 0.7,
+        (Some(a), Some(b)) => match (a - b).abs() {
+            0 => 1.0,
+            1 => 0.9,
+            2 => 0.6,
+            _ => 0.3,
+        },
+    };
+    title * year
+}
+
+/// The `limit` best candidates by `score`, best first.
+pub fn rank(
+    query_title: &str,
+    query_year: Option<i32>,
+    candidates: &[MatchCandidate],
+    limit: usize,
+) -> Vec<(MatchCandidate, f64)> {
+    let mut scored: Vec<(MatchCandidate, f64)> = candidates
+        .iter()
+        .map(|candidate| (candidate.clone(), score(query_title, query_year, candidate)))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(limit);
+    scored
+}
+
+/// 1.0 for identical normalized titles, otherwise the Dice coefficient
+/// over their word sets.
+fn title_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let left: std::collections::HashSet<&str> = a.split_whitespace().collect();
+    let right: std::collections::HashSet<&str> = b.split_whitespace().collect();
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    let shared = left.intersection(&right).count() as f64;
+    2.0 * shared / (left.len() + right.len()) as f64
+}
+
 fn year_matches(candidate_year: Option<i32>, query_year: Option<i32>) -> bool {
     match (candidate_year, query_year) {
         (_, None) => true,
@@ -97,6 +153,8 @@ mod tests {
             provider_id: id.to_string(),
             title: title.to_string(),
             year,
+            poster_url: None,
+            overview: None,
         }
     }
 
@@ -178,4 +236,17 @@ mod tests {
         let picked = pick_confident_match("Foo Bar", Some(2010), &candidates);
         assert!(picked.is_some());
     }
+
+    #[test]
+    fn rank_orders_by_title_then_year_fit() {
+        let candidates = vec![
+            candidate("reloaded", "The Matrix Reloaded", Some(1999)),
+            candidate("remake", "The Matrix", Some(2030)),
+            candidate("original", "The Matrix", Some(1999)),
+        ];
+        let ranked = rank("The Matrix", Some(1999), &candidates, 2);
+        let ids: Vec<&str> = ranked.iter().map(|(c, _)| c.provider_id.as_str()).collect();
+        assert_eq!(ids, vec!["original", "reloaded"]);
+        assert_eq!(ranked[0].1, 1.0);
+    }
 }
@@ -1,10 +1,32 @@
 //! Metadata sync subsystem. See
 //! docs/superpowers/specs/2026-05-25-imdb-fallback-provider-design.md.
 
+pub mod airing;
+pub mod aliases;
 pub mod apply;
+pub mod backdrops;
+pub mod certifications;
+pub mod collections;
 pub mod dispatch;
+pub mod external_ids;
+#[cfg(test)]
+pub mod fake_server;
+pub mod genres;
+pub mod http;
 pub mod imdb;
+pub mod job_log;
+pub mod link_import;
+pub mod locale;
+pub mod manual;
 pub mod matching;
+pub mod people;
+pub mod poster_cache;
+pub mod posters;
+pub mod preview;
 pub mod queries;
+pub mod rate_limit;
+pub mod ratings;
+pub mod refresh;
+pub mod review;
 pub mod tmdb;
 pub mod worker;
@@ -0,0 +1,387 @@
+//! Cast and crew. Each applied payload rewrites the title's rows in
+//! `credits` and upserts the people in them, so a person's filmography
+//! is a join over every library. People are per provider: a TMDB-linked
+//! and an IMDB-linked title don't share a person row.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::imdb::PrincipalCredits;
+use crate::metadata::tmdb::TmdbCredits;
+use crate::parental;
+
+/// TMDB lists the whole cast; past this it's mostly uncredited extras.
+const MAX_CAST: usize = 30;
+
+/// One credit as parsed from a provider payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreditInput {
+    pub person_provider_id: String,
+    pub name: String,
+    pub photo_url: Option<String>,
+    pub role: &'static str,
+    pub job: Option<String>,
+    pub character: Option<String>,
+    pub order: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct Person {
+    pub id: i64,
+    pub provider: String,
+    pub provider_id: String,
+    pub name: String,
+    pub photo_url: Option<String>,
+}
+
+/// A person as credited on one title.
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct TitleCredit {
+    pub person_id: i64,
+    pub name: String,
+    pub photo_url: Option<String>,
+    pub role: String,
+    pub job: Option<String>,
+    pub character: Option<String>,
+    pub credit_order: i64,
+}
+
+/// A title in the library a person is credited on.
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct FilmographyEntry {
+    pub kind: String,
+    pub media_id: i64,
+    pub title: String,
+    pub year: Option<i32>,
+    pub poster_path: Option<String>,
+    pub role: String,
+    pub job: Option<String>,
+    pub character: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Filmography {
+    pub person: Person,
+    pub credits: Vec<FilmographyEntry>,
+}
+
+/// Cast (capped at `MAX_CAST`) plus directors and writers. Other crew
+/// departments are dropped.
+pub fn from_tmdb(credits: Option<&TmdbCredits>, image_base: &str) -> Vec<CreditInput> {
+    let Some(credits) = credits else {
+        return Vec::new();
+    };
+    let photo = |path: &Option<String>| path.as_ref().map(|path| format!("{image_base}{path}"));
+
+    let cast = credits.cast.iter().take(MAX_CAST).map(|member| CreditInput {
+        person_provider_id: member.id.to_string(),
+        name: member.name.clone(),
+        photo_url: photo(&member.profile_path),
+        role: "cast",
+        job: None,
+        character: member.character.clone().filter(|name| !name.is_empty()),
+        order: member.order,
+    });
+
+    let crew = credits
+        .crew
+        .iter()
+        .filter_map(|member| {
+            let role = match (member.department.as_str(), member.job.as_str()) {
+                ("Directing", "Director") => "director",
+                ("Writing", _) => "writer",
+                _ => return None,
+            };
+            Some((role, member))
+        })
+        .enumerate()
+        .map(|(index, (role, member))| CreditInput {
+            person_provider_id: member.id.to_string(),
+            name: member.name.clone(),
+            photo_url: photo(&member.profile_path),
+            role,
+            job: Some(member.job.clone()),
+            character: None,
+            order: index as i64,
+        });
+
+    cast.chain(crew).collect()
+}
+
+/// IMDB's principal credits: the `cast`, `director` and `writer` blocks.
+pub fn from_imdb(blocks: &[PrincipalCredits]) -> Vec<CreditInput> {
+    blocks
+        .iter()
+        .filter_map(|block| {
+            let role = match block.category.id.as_str() {
+                "cast" => "cast",
+                "director" => "director",
+                "writer" => "writer",
+                _ => return None,
+            };
+            Some((role, block))
+        })
+        .flat_map(|(role, block)| {
+            block.credits.iter().take(MAX_CAST).enumerate().map(move |(index, credit)| {
+                CreditInput {
+                    person_provider_id: credit.name.id.clone(),
+                    name: credit.name.name_text.text.clone(),
+                    photo_url: credit.name.primary_image.as_ref().map(|image| image.url.clone()),
+                    role,
+                    job: None,
+                    character: credit.characters.first().map(|character| character.name.clone()),
+                    order: index as i64,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Replaces a title's credits. A person credited twice in the same role
+/// (TMDB lists "Screenplay" and "Story" separately) keeps the first.
+pub async fn store_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    provider: &str,
+    credits: &[CreditInput],
+) -> AppResult<()> {
+    sqlx::query("DELETE FROM credits WHERE kind = ?1 AND media_id = ?2")
+        .bind(kind)
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+    insert_in_tx(conn, kind, media_id, provider, credits).await
+}
+
+/// Replaces only a title's cast, keeping its directors and writers.
+pub async fn store_cast_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    provider: &str,
+    cast: &[CreditInput],
+) -> AppResult<()> {
+    sqlx::query("DELETE FROM credits WHERE kind = ?1 AND media_id = ?2 AND role = 'cast'")
+        .bind(kind)
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+    insert_in_tx(conn, kind, media_id, provider, cast).await
+}
+
+async fn insert_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    provider: &str,
+    credits: &[CreditInput],
+) -> AppResult<()> {
+    for credit in credits {
+        let person_id: i64 = sqlx::query_scalar(
+            "INSERT INTO people (provider, provider_id, name, photo_url)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(provider, provider_id) DO UPDATE SET
+                 name = excluded.name,
+                 photo_url = COALESCE(excluded.photo_url, photo_url),
+                 updated_at = strftime('%s','now')
+             RETURNING id",
+        )
+        .bind(provider)
+        .bind(&credit.person_provider_id)
+        .bind(&credit.name)
+        .bind(credit.photo_url.as_deref())
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO credits
+                 (person_id, kind, media_id, role, job, character, credit_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(person_id)
+        .bind(kind)
+        .bind(media_id)
+        .bind(credit.role)
+        .bind(credit.job.as_deref())
+        .bind(credit.character.as_deref())
+        .bind(credit.order)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Everyone credited on a title: cast in billing order, then directors,
+/// then writers.
+pub async fn for_title(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<Vec<TitleCredit>> {
+    let credits: Vec<TitleCredit> = sqlx::query_as(
+        "SELECT p.id AS person_id, p.name, p.photo_url, c.role, c.job, c.character, c.credit_order
+         FROM credits c
+         JOIN people p ON p.id = c.person_id
+         WHERE c.kind = ?1 AND c.media_id = ?2
+         ORDER BY CASE c.role WHEN 'cast' THEN 0 WHEN 'director' THEN 1 ELSE 2 END,
+                  c.credit_order",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(credits)
+}
+
+/// A person's credits on titles in the library, newest first.
+pub async fn filmography(pool: &SqlitePool, person_id: i64) -> AppResult<Filmography> {
+    let person: Person = sqlx::query_as(
+        "SELECT id, provider, provider_id, name, photo_url FROM people WHERE id = ?1",
+    )
+    .bind(person_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::Other(format!("person not found: {person_id}")))?;
+
+    // Titles hidden by parental controls are left out.
+    let credits: Vec<FilmographyEntry> = sqlx::query_as(&format!(
+        "SELECT * FROM (
+             SELECT c.kind, c.media_id, m.title, m.year, m.poster_path, c.role, c.job, c.character
+             FROM credits c
+             JOIN movies m ON c.kind = 'movie' AND m.id = c.media_id
+             WHERE c.person_id = ?1 AND {movie_visible}
+             UNION ALL
+             SELECT c.kind, c.media_id, s.title, s.year, s.poster_path, c.role, c.job, c.character
+             FROM credits c
+             JOIN shows s ON c.kind = 'show' AND s.id = c.media_id
+             WHERE c.person_id = ?1 AND {show_visible}
+         )
+         ORDER BY year IS NULL, year DESC, title COLLATE NOCASE, role",
+        movie_visible = parental::visible_clause("movie", "m"),
+        show_visible = parental::visible_clause("show", "s"),
+    ))
+    .bind(person_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Filmography { person, credits })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::tmdb::TmdbMovieDetails;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn matrix_credits() -> Vec<CreditInput> {
+        let details: TmdbMovieDetails =
+            serde_json::from_str(include_str!("../../tests/fixtures/tmdb-movie-details.json"))
+                .unwrap();
+        from_tmdb(details.credits.as_ref(), "http://img")
+    }
+
+    #[test]
+    fn tmdb_keeps_cast_directors_and_writers() {
+        let credits = matrix_credits();
+        let roles: Vec<(&str, &str)> = credits
+            .iter()
+            .map(|credit| (credit.role, credit.name.as_str()))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                ("cast", "Keanu Reeves"),
+                ("cast", "Laurence Fishburne"),
+                ("cast", "Carrie-Anne Moss"),
+                ("director", "Lana Wachowski"),
+                ("director", "Lilly Wachowski"),
+                ("writer", "Lana Wachowski"),
+            ]
+        );
+        assert_eq!(
+            credits[0].photo_url.as_deref(),
+            Some("http://img/4D0PpNI0kmP58hgrwGC3wCjxhnm.jpg")
+        );
+        assert_eq!(credits[5].job.as_deref(), Some("Writer"));
+    }
+
+    #[tokio::test]
+    async fn filmography_spans_movies_and_shows_across_libraries() {
+        let pool = fresh_pool().await;
+        sqlx::query(
+            "INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies'), (2, '/tv', 'series')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, year, path)
+             VALUES (10, 1, 'The Matrix', 1999, '/movies/a.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO shows (id, library_id, title, year, folder_path, fingerprint)
+             VALUES (20, 2, 'Sense8', 2015, '/tv/Sense8', 'fp')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        store_in_tx(&mut conn, "movie", 10, "tmdb", &matrix_credits()).await.unwrap();
+        let lana = CreditInput {
+            person_provider_id: "9340".into(),
+            name: "Lana Wachowski".into(),
+            photo_url: None,
+            role: "director",
+            job: Some("Director".into()),
+            character: None,
+            order: 0,
+        };
+        store_in_tx(&mut conn, "show", 20, "tmdb", std::slice::from_ref(&lana)).await.unwrap();
+        // Re-applying replaces the title's credits rather than appending.
+        store_in_tx(&mut conn, "show", 20, "tmdb", &[lana]).await.unwrap();
+        drop(conn);
+
+        let people: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM people")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(people, 5);
+
+        let on_title = for_title(&pool, "movie", 10).await.unwrap();
+        assert_eq!(on_title.len(), 6);
+        assert_eq!(on_title[0].character.as_deref(), Some("Neo"));
+        assert_eq!(on_title[3].role, "director");
+
+        let lana_id = on_title[3].person_id;
+        let films = filmography(&pool, lana_id).await.unwrap();
+        assert_eq!(films.person.name, "Lana Wachowski");
+        let titles: Vec<(&str, &str)> = films
+            .credits
+            .iter()
+            .map(|entry| (entry.title.as_str(), entry.role.as_str()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![("Sense8", "director"), ("The Matrix", "director"), ("The Matrix", "writer")]
+        );
+
+        assert!(filmography(&pool, 9999).await.is_err());
+    }
+}
@@ -0,0 +1,654 @@
+//! Poster cache maintenance. `<app_data>/posters/` collects files nothing
+//! points at any more (deleted titles, a provider switch changing the
+//! extension, library removal cascades), and a failed download can leave
+//! a row pointing at a file that isn't there. `run` fixes both:
+//!
+//!   1. Every poster reference into the directory (movies, shows, their
+//!      variants and backdrops, collections) is checked. Missing, empty or
+//!      non-image files are cleared from the row. Broken variants of a poster
+//!      that's still fine are rendered from it again. Otherwise linked titles
+//!      are queued for a refresh, which downloads the poster again. A refresh
+//!      would undo the edits on a locked title, so its poster is fetched
+//!      straight from the linked provider instead.
+//!   2. Files no row references are deleted, except ones written in the
+//!      last `GRACE_SECS` (a download may not have been recorded yet).
+//!
+//! Posters found next to the media files (absolute paths outside the
+//! directory) are left alone. `spawn` runs a pass once a day.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use sqlx::SqlitePool;
+use tokio::sync::Notify;
+use tokio::time::sleep;
+
+use crate::error::AppResult;
+use crate::metadata::http::ProviderHttp;
+use crate::metadata::posters;
+use crate::metadata::preview;
+use crate::metadata::queries::PRIORITY_REFRESH;
+
+/// Delay before the first pass, after the startup scan and refresh.
+const STARTUP_DELAY_SECS: u64 = 10 * 60;
+
+const TICK_SECS: u64 = 24 * 60 * 60;
+
+/// Files younger than this are never treated as orphans.
+const GRACE_SECS: u64 = 10 * 60;
+
+const POSTER_COLUMNS: [&str; 5] = [
+    "poster_path",
+    "poster_thumb_path",
+    "poster_card_path",
+    "poster_hero_path",
+    "backdrop_path",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PosterCacheReport {
+    /// Unreferenced files deleted.
+    pub files_removed: i64,
+    pub bytes_reclaimed: u64,
+    /// Poster references cleared because the file was missing or corrupt.
+    pub broken: i64,
+    /// Titles queued to download their poster again.
+    pub requeued: u64,
+    /// Locked titles whose poster was downloaded again directly.
+    pub redownloaded: u64,
+    /// Titles whose variants were rendered again from their poster.
+    pub rerendered: u64,
+    /// Titles with broken references that weren't restored: unlinked
+    /// ones, and locked ones whose poster couldn't be downloaded.
+    pub not_requeued: u64,
+}
+
+pub fn spawn(pool: SqlitePool, http: ProviderHttp, posters_dir: PathBuf, notify: Arc<Notify>) {
+    tauri::async_runtime::spawn(async move {
+        sleep(Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        loop {
+            match run(&pool, &http, &posters_dir).await {
+                Ok(report) if report.requeued > 0 => notify.notify_one(),
+                Ok(_) => {}
+                Err(error) => eprintln!("poster cache maintenance failed: {error}"),
+            }
+            sleep(Duration::from_secs(TICK_SECS)).await;
+        }
+    });
+}
+
+/// One maintenance pass over `posters_dir`.
+pub async fn run(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+) -> AppResult<PosterCacheReport> {
+    let mut report = PosterCacheReport::default();
+
+    for (kind, table) in [("movie", "movies"), ("show", "shows")] {
+        verify_titles(pool, http, posters_dir, kind, table, &mut report).await?;
+    }
+    verify_collections(pool, posters_dir, &mut report).await?;
+    remove_orphans(pool, posters_dir, &mut report).await?;
+
+    Ok(report)
+}
+
+#[derive(sqlx::FromRow)]
+struct TitlePosters {
+    id: i64,
+    provider: Option<String>,
+    metadata_locked: i64,
+    poster_origin: Option<String>,
+    poster_path: Option<String>,
+    poster_thumb_path: Option<String>,
+    poster_card_path: Option<String>,
+    poster_hero_path: Option<String>,
+    backdrop_path: Option<String>,
+}
+
+async fn title_posters(pool: &SqlitePool, table: &str) -> AppResult<Vec<TitlePosters>> {
+    let rows = sqlx::query_as(&format!(
+        "SELECT id, provider, metadata_locked, poster_origin, {} FROM {table}
+         WHERE COALESCE(poster_path, poster_thumb_path, poster_card_path, poster_hero_path,
+                        backdrop_path) IS NOT NULL",
+        POSTER_COLUMNS.join(", ")
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+async fn verify_titles(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    kind: &str,
+    table: &str,
+    report: &mut PosterCacheReport,
+) -> AppResult<()> {
+    for row in title_posters(pool, table).await? {
+        let stored = [
+            &row.poster_path,
+            &row.poster_thumb_path,
+            &row.poster_card_path,
+            &row.poster_hero_path,
+            &row.backdrop_path,
+        ];
+        let mut cleared = Vec::new();
+        for (column, value) in POSTER_COLUMNS.iter().zip(stored) {
+            let Some(path) = value.as_deref().and_then(|value| managed_path(posters_dir, value))
+            else {
+                continue;
+            };
+            if !is_readable_image(&path).await {
+                cleared.push(*column);
+            }
+        }
+        if cleared.is_empty() {
+            continue;
+        }
+        report.broken += cleared.len() as i64;
+
+        // A lost poster takes its variants and origin with it, so the
+        // refresh is free to download a new one, even over a manual poster
+        // whose file is gone. A lost backdrop likewise loses its origin.
+        let poster_lost = cleared.contains(&"poster_path");
+        let mut columns: Vec<&str> = if poster_lost {
+            vec![
+                "poster_path",
+                "poster_thumb_path",
+                "poster_card_path",
+                "poster_hero_path",
+                "poster_blurhash",
+                "poster_origin",
+            ]
+        } else {
+            cleared.iter().copied().filter(|column| *column != "backdrop_path").collect()
+        };
+        if cleared.contains(&"backdrop_path") {
+            columns.extend(["backdrop_path", "backdrop_origin"]);
+        }
+        let assignments: Vec<String> =
+            columns.iter().map(|column| format!("{column} = NULL")).collect();
+        sqlx::query(&format!("UPDATE {table} SET {} WHERE id = ?1", assignments.join(", ")))
+            .bind(row.id)
+            .execute(pool)
+            .await?;
+
+        if poster_lost && row.poster_origin.as_deref() == Some("manual") {
+            eprintln!("manual poster for {kind} {} is missing or corrupt; cleared", row.id);
+        }
+        let backdrop_lost = cleared.contains(&"backdrop_path");
+        let variants_lost = !poster_lost && cleared.len() > usize::from(backdrop_lost);
+        if let (true, Some(poster_path)) = (variants_lost, row.poster_path.as_deref()) {
+            // The poster itself is fine, so no provider is needed to get the
+            // variants back, whether the title is linked, locked or neither.
+            match posters::store_variants(pool, posters_dir, kind, row.id, poster_path).await {
+                Ok(()) => {
+                    report.rerendered += 1;
+                    if !backdrop_lost {
+                        continue;
+                    }
+                }
+                Err(error) => eprintln!("poster variants for {kind} {} failed: {error}", row.id),
+            }
+        }
+        if row.provider.is_none() {
+            report.not_requeued += 1;
+        } else if row.metadata_locked == 0 {
+            report.requeued += requeue(pool, kind, table, row.id).await?;
+        } else if poster_lost {
+            match redownload_poster(pool, http, posters_dir, kind, row.id).await {
+                Ok(true) => report.redownloaded += 1,
+                Ok(false) => report.not_requeued += 1,
+                Err(error) => {
+                    eprintln!("poster download for locked {kind} {} failed: {error}", row.id);
+                    report.not_requeued += 1;
+                }
+            }
+        } else {
+            report.not_requeued += 1;
+        }
+    }
+
+    Ok(())
+}
+
+async fn verify_collections(
+    pool: &SqlitePool,
+    posters_dir: &Path,
+    report: &mut PosterCacheReport,
+) -> AppResult<()> {
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, poster_path FROM collections WHERE poster_path IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (id, poster_path) in rows {
+        let Some(path) = managed_path(posters_dir, &poster_path) else {
+            continue;
+        };
+        if is_readable_image(&path).await {
+            continue;
+        }
+        // The next match of one of its parts downloads it again.
+        sqlx::query("UPDATE collections SET poster_path = NULL WHERE id = ?1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        report.broken += 1;
+    }
+
+    Ok(())
+}
+
+/// Queues a linked, unlocked title for a refresh. Returns 1 if a job was
+/// added.
+async fn requeue(pool: &SqlitePool, kind: &str, table: &str, id: i64) -> AppResult<u64> {
+    let result = sqlx::query(&format!(
+        "INSERT INTO metadata_jobs (kind, media_id, priority)
+         SELECT ?1, id, ?2 FROM {table}
+         WHERE id = ?3 AND provider IS NOT NULL AND metadata_locked = 0
+         ON CONFLICT(kind, media_id) DO NOTHING"
+    ))
+    .bind(kind)
+    .bind(PRIORITY_REFRESH)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Downloads a locked title's poster from its linked provider, writing
+/// the poster and nothing else. False when the provider has none.
+async fn redownload_poster(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    kind: &str,
+    id: i64,
+) -> AppResult<bool> {
+    let candidate = preview::current_candidate(pool, kind, id).await?;
+    let proposed = preview::fetch(pool, http, kind, id, &candidate).await?;
+    if proposed.poster.is_none() {
+        return Ok(false);
+    }
+    preview::apply_fields(pool, http, posters_dir, kind, id, &proposed, &["poster".to_string()])
+        .await?;
+    Ok(true)
+}
+
+async fn remove_orphans(
+    pool: &SqlitePool,
+    posters_dir: &Path,
+    report: &mut PosterCacheReport,
+) -> AppResult<()> {
+    let referenced = referenced_files(pool, posters_dir).await?;
+
+    let mut entries = match tokio::fs::read_dir(posters_dir).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+    let cutoff = SystemTime::now() - Duration::from_secs(GRACE_SECS);
+
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if referenced.contains(&name) {
+            continue;
+        }
+        if metadata.modified().map(|modified| modified > cutoff).unwrap_or(true) {
+            continue;
+        }
+        match tokio::fs::remove_file(entry.path()).await {
+            Ok(()) => {
+                report.files_removed += 1;
+                report.bytes_reclaimed += metadata.len();
+            }
+            Err(error) => eprintln!("could not remove orphaned poster {name}: {error}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// File names in `posters_dir` that some row points at.
+async fn referenced_files(pool: &SqlitePool, posters_dir: &Path) -> AppResult<HashSet<String>> {
+    let mut values: Vec<Option<String>> = Vec::new();
+    for table in ["movies", "shows"] {
+        for row in title_posters(pool, table).await? {
+            values.extend([
+                row.poster_path,
+                row.poster_thumb_path,
+                row.poster_card_path,
+                row.poster_hero_path,
+                row.backdrop_path,
+            ]);
+        }
+    }
+    let collection_posters: Vec<Option<String>> =
+        sqlx::query_scalar("SELECT poster_path FROM collections").fetch_all(pool).await?;
+    values.extend(collection_posters);
+
+    Ok(values
+        .into_iter()
+        .flatten()
+        .filter_map(|value| managed_path(posters_dir, &value))
+        .filter_map(|path| path.file_name().map(|name| name.to_string_lossy().to_string()))
+        .collect())
+}
+
+/// Where a stored poster value lives, when it's in `posters_dir`: bare
+/// filenames (synced posters) and absolute paths inside it (manual
+/// uploads). `None` for anything elsewhere.
+fn managed_path(posters_dir: &Path, stored: &str) -> Option<PathBuf> {
+    let path = Path::new(stored);
+    if !path.is_absolute() {
+        return Some(posters_dir.join(path));
+    }
+    (path.parent() == Some(posters_dir)).then(|| path.to_path_buf())
+}
+
+/// True when the file exists and decodes as a JPEG, PNG or WebP image,
+/// the formats providers serve and manual uploads accept. The header
+/// alone isn't enough: an interrupted download starts right but is cut
+/// short.
+async fn is_readable_image(path: &Path) -> bool {
+    let path = path.to_path_buf();
+    // Decoding is CPU-bound; keep it off the async runtime.
+    tokio::task::spawn_blocking(move || decodes(&path)).await.unwrap_or(false)
+}
+
+fn decodes(path: &Path) -> bool {
+    let Ok(reader) = image::io::Reader::open(path).and_then(|reader| reader.with_guessed_format())
+    else {
+        return false;
+    };
+    reader.decode().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::fake_server::{FakeServer, POSTER_BYTES};
+    use crate::queries as app_queries;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const JPEG: &[u8] = POSTER_BYTES;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    fn write_old(path: &Path, bytes: &[u8]) {
+        std::fs::write(path, bytes).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(GRACE_SECS * 2);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_images_that_decode_are_readable() {
+        let dir = std::env::temp_dir().join(format!("rustflix-poster-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = dir.join("poster.png");
+        image::DynamicImage::new_rgb8(4, 6).save(&png).unwrap();
+        let cases: [(&str, &[u8]); 3] = [
+            ("truncated.jpg", &JPEG[..JPEG.len() / 2]),
+            ("header-only.jpg", &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10, b'J', b'F', b'I', b'F']),
+            ("page.jpg", b"<html><body>"),
+        ];
+        for (name, bytes) in cases {
+            std::fs::write(dir.join(name), bytes).unwrap();
+        }
+        std::fs::write(dir.join("poster.jpg"), JPEG).unwrap();
+
+        assert!(is_readable_image(&dir.join("poster.jpg")).await);
+        assert!(is_readable_image(&png).await);
+        for (name, _) in cases {
+            assert!(!is_readable_image(&dir.join(name)).await, "{name}");
+        }
+        assert!(!is_readable_image(&dir.join("missing.jpg")).await);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn orphans_are_removed_and_broken_posters_requeued() {
+        let pool = fresh_pool().await;
+        let dir = std::env::temp_dir().join(format!(
+            "rustflix-poster-cache-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let manual = dir.join("movie-3.png");
+        sqlx::query(
+            "INSERT INTO movies
+                 (id, library_id, title, path, provider, provider_id,
+                  poster_path, poster_origin, poster_thumb_path, backdrop_path, backdrop_origin)
+             VALUES
+                 (1, 1, 'Fine', '/movies/1.mkv', 'tmdb', '1', 'movie-1.jpg', 'tmdb',
+                  'movie-1.thumb.jpg', 'movie-1-backdrop.jpg', 'tmdb'),
+                 (2, 1, 'Missing', '/movies/2.mkv', 'tmdb', '2', 'movie-2.jpg', 'tmdb',
+                  'movie-2.thumb.jpg', 'movie-2-backdrop.jpg', 'tmdb'),
+                 (3, 1, 'Manual', '/movies/3.mkv', NULL, NULL, ?1, 'manual', NULL, NULL, NULL),
+                 (4, 1, 'Folder', '/movies/4.mkv', NULL, NULL, '/movies/poster.jpg', 'auto',
+                  NULL, NULL, NULL)",
+        )
+        .bind(manual.to_string_lossy().to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        write_old(&dir.join("movie-1.jpg"), JPEG);
+        write_old(&dir.join("movie-1.thumb.jpg"), JPEG);
+        write_old(&dir.join("movie-1-backdrop.jpg"), JPEG);
+        write_old(&dir.join("movie-2.thumb.jpg"), JPEG);
+        write_old(&manual, b"<html>not an image</html>");
+        write_old(&dir.join("movie-9.jpg"), &[0xAB; 100]);
+        // Too new to be collected: may be a download in progress.
+        std::fs::write(dir.join("movie-10.jpg"), JPEG).unwrap();
+
+        let http = ProviderHttp::new(reqwest::Client::new(), pool.clone());
+        let report = run(&pool, &http, &dir).await.unwrap();
+        assert_eq!(report.broken, 3);
+        assert_eq!(report.requeued, 1);
+        // The unlinked manual poster has nowhere to come back from.
+        assert_eq!(report.not_requeued, 1);
+        assert_eq!(report.files_removed, 3);
+        assert_eq!(
+            report.bytes_reclaimed,
+            (JPEG.len() + "<html>not an image</html>".len() + 100) as u64
+        );
+
+        type Row = (i64, Option<String>, Option<String>, Option<String>);
+        let rows: Vec<Row> = sqlx::query_as(
+            "SELECT id, poster_path, poster_origin, poster_thumb_path FROM movies ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows[0].1.as_deref(), Some("movie-1.jpg"));
+        assert_eq!(rows[0].3.as_deref(), Some("movie-1.thumb.jpg"));
+        assert_eq!(rows[1], (2, None, None, None));
+        assert_eq!(rows[2], (3, None, None, None));
+        assert_eq!(rows[3].1.as_deref(), Some("/movies/poster.jpg"));
+        let backdrops: Vec<(Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT backdrop_path, backdrop_origin FROM movies WHERE id <= 2 ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(backdrops[0], (Some("movie-1-backdrop.jpg".into()), Some("tmdb".into())));
+        assert_eq!(backdrops[1], (None, None));
+
+        let queued: Vec<i64> = sqlx::query_scalar("SELECT media_id FROM metadata_jobs")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, vec![2]);
+
+        let mut left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["movie-1-backdrop.jpg", "movie-1.jpg", "movie-1.thumb.jpg", "movie-10.jpg"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn locked_titles_download_only_their_poster() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let pool = fresh_pool().await;
+        let dir = std::env::temp_dir().join(format!(
+            "rustflix-poster-cache-locked-{}-{}",
+            std::process::id(),
+            server.base_url().rsplit(':').next().unwrap()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies
+                 (id, library_id, title, overview, path, provider, provider_id,
+                  metadata_locked, poster_path, poster_origin)
+             VALUES (1, 1, 'Edited', 'Hand-written', '/movies/1.mkv', 'tmdb', '603', 1,
+                     'movie-1.jpg', 'tmdb')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        app_queries::set_app_setting(&pool, "tmdb_api_key", "test-key").await.unwrap();
+        let http = ProviderHttp::new(reqwest::Client::new(), pool.clone())
+            .with_endpoints(server.endpoints());
+
+        let report = run(&pool, &http, &dir).await.unwrap();
+
+        assert_eq!((report.broken, report.requeued, report.redownloaded), (1, 0, 1));
+        assert_eq!(report.not_requeued, 0);
+        type Row = (String, Option<String>, Option<String>, Option<String>, i64);
+        let row: Row = sqlx::query_as(
+            "SELECT title, overview, poster_path, poster_origin, metadata_locked
+             FROM movies WHERE id = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            row,
+            (
+                "Edited".to_string(),
+                Some("Hand-written".to_string()),
+                Some("movie-1.jpg".to_string()),
+                Some("tmdb".to_string()),
+                1
+            )
+        );
+        assert_eq!(std::fs::read(dir.join("movie-1.jpg")).unwrap(), POSTER_BYTES);
+        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM metadata_jobs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn broken_variants_are_rendered_from_the_poster() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let pool = fresh_pool().await;
+        let dir = std::env::temp_dir().join(format!(
+            "rustflix-poster-cache-variants-{}-{}",
+            std::process::id(),
+            server.base_url().rsplit(':').next().unwrap()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies
+                 (id, library_id, title, path, provider, provider_id, metadata_locked,
+                  poster_path, poster_origin, poster_thumb_path, poster_card_path)
+             VALUES
+                 (1, 1, 'Locked', '/movies/1.mkv', 'tmdb', '603', 1, 'movie-1.jpg', 'tmdb',
+                  'movie-1.thumb.jpg', NULL),
+                 (2, 1, 'Linked', '/movies/2.mkv', 'tmdb', '604', 0, 'movie-2.jpg', 'tmdb',
+                  NULL, 'movie-2.card.jpg')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for id in [1, 2] {
+            write_old(&dir.join(format!("movie-{id}.jpg")), JPEG);
+        }
+        write_old(&dir.join("movie-1.thumb.jpg"), &JPEG[..JPEG.len() / 2]);
+        let http = ProviderHttp::new(reqwest::Client::new(), pool.clone())
+            .with_endpoints(server.endpoints());
+
+        let report = run(&pool, &http, &dir).await.unwrap();
+
+        assert_eq!((report.broken, report.rerendered), (2, 2));
+        assert_eq!((report.requeued, report.redownloaded, report.not_requeued), (0, 0, 0));
+        type Row = (String, Option<String>, Option<String>, Option<String>);
+        let rows: Vec<Row> = sqlx::query_as(
+            "SELECT poster_path, poster_thumb_path, poster_card_path, poster_hero_path
+             FROM movies ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        for (id, (poster, thumb, card, hero)) in (1..).zip(rows) {
+            assert_eq!(poster, format!("movie-{id}.jpg"));
+            for variant in [thumb, card, hero] {
+                assert!(is_readable_image(&dir.join(variant.unwrap())).await);
+            }
+        }
+        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM metadata_jobs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, 0);
+        assert!(server.requests().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,226 @@
+//! Poster variants. Next to the poster itself, each title gets a thumb,
+//! card and hero copy sized for where it's shown, plus a blurhash the UI
+//! paints while they load. The poster on disk is decoded once and resized
+//! locally, so synced posters and manual uploads both get variants without
+//! further provider requests. Variants are always written as JPEG. Folder
+//! posters the scanner picks up are used as found; the UI falls back to
+//! `poster_path` for them.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::http::ProviderHttp;
+use crate::metadata::imdb;
+use crate::metadata::tmdb;
+use crate::metadata::worker::PosterDownload;
+
+const JPEG_QUALITY: u8 = 85;
+
+/// Blurhash components across and down; more rows than columns suits a
+/// portrait poster.
+const BLURHASH_COMPONENTS: (u32, u32) = (3, 4);
+
+/// The hash only encodes a few colour gradients, so it's computed from a
+/// tiny copy rather than the full poster.
+const BLURHASH_SOURCE_SIZE: u32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Small grids and lists.
+    Thumb,
+    /// Poster cards.
+    Card,
+    /// Detail page header.
+    Hero,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 3] = [Variant::Thumb, Variant::Card, Variant::Hero];
+
+    fn name(self) -> &'static str {
+        match self {
+            Variant::Thumb => "thumb",
+            Variant::Card => "card",
+            Variant::Hero => "hero",
+        }
+    }
+
+    /// Maximum width; narrower posters are re-encoded at their own size.
+    fn width(self) -> u32 {
+        match self {
+            Variant::Thumb => 185,
+            Variant::Card => 342,
+            Variant::Hero => 780,
+        }
+    }
+}
+
+/// `movie-42.thumb.jpg`. Named after the title rather than the poster,
+/// whose name for a manual upload is whatever the user picked.
+pub fn variant_filename(kind: &str, media_id: i64, variant: Variant) -> String {
+    format!("{kind}-{media_id}.{}.jpg", variant.name())
+}
+
+/// Downloads a poster into `posters_dir` under its filename.
+pub async fn download(
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    download: &PosterDownload,
+) -> AppResult<()> {
+    let dest = posters_dir.join(&download.filename);
+    match download.size {
+        None => tmdb::download_poster(http, &download.url, &dest).await,
+        Some(size) => imdb::download_poster(http, &download.url, &dest, size).await,
+    }
+}
+
+/// What [`render`] produced: one filename per entry of [`Variant::ALL`],
+/// None where writing it failed.
+struct Rendered {
+    variants: Vec<Option<String>>,
+    blurhash: Option<String>,
+}
+
+impl Rendered {
+    fn empty() -> Self {
+        Self {
+            variants: vec![None; Variant::ALL.len()],
+            blurhash: None,
+        }
+    }
+}
+
+/// Renders the variants and blurhash of the poster the title points at
+/// and records them. `poster_path` is the stored value: a filename in
+/// `posters_dir` for synced posters, an absolute path for manual ones.
+/// Best-effort like the poster itself: a variant that fails to write stays
+/// NULL and the UI uses the full poster instead. Skipped if the title's
+/// poster changed meanwhile.
+pub async fn store_variants(
+    pool: &SqlitePool,
+    posters_dir: &Path,
+    kind: &str,
+    media_id: i64,
+    poster_path: &str,
+) -> AppResult<()> {
+    let table = match kind {
+        "movie" => "movies",
+        "show" => "shows",
+        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+    };
+
+    let source = posters_dir.join(poster_path);
+    let dir = posters_dir.to_path_buf();
+    let owned_kind = kind.to_string();
+    // Decoding and resizing is CPU-bound; keep it off the async runtime.
+    let rendered =
+        tokio::task::spawn_blocking(move || render(&source, &dir, &owned_kind, media_id))
+            .await
+            .map_err(|error| AppError::Other(error.to_string()))?;
+    // An undecodable poster still clears whatever the previous one left.
+    let (rendered, result) = match rendered {
+        Ok(rendered) => (rendered, Ok(())),
+        Err(error) => (Rendered::empty(), Err(error)),
+    };
+
+    sqlx::query(&format!(
+        "UPDATE {table}
+         SET poster_thumb_path = ?1, poster_card_path = ?2, poster_hero_path = ?3,
+             poster_blurhash = ?4
+         WHERE id = ?5 AND poster_path = ?6"
+    ))
+    .bind(&rendered.variants[0])
+    .bind(&rendered.variants[1])
+    .bind(&rendered.variants[2])
+    .bind(&rendered.blurhash)
+    .bind(media_id)
+    .bind(poster_path)
+    .execute(pool)
+    .await?;
+
+    result
+}
+
+fn render(source: &Path, posters_dir: &Path, kind: &str, media_id: i64) -> AppResult<Rendered> {
+    // Sniffed from the bytes: provider posters don't always match their
+    // extension.
+    let poster = image::io::Reader::open(source)?
+        .with_guessed_format()?
+        .decode()
+        .map_err(|error| AppError::Other(format!("poster {source:?} can't be decoded: {error}")))?;
+
+    let mut variants = Vec::new();
+    for variant in Variant::ALL {
+        let filename = variant_filename(kind, media_id, variant);
+        let dest = posters_dir.join(&filename);
+        let result = if poster.width() > variant.width() {
+            write_jpeg(&poster.resize(variant.width(), u32::MAX, FilterType::Lanczos3), &dest)
+        } else {
+            write_jpeg(&poster, &dest)
+        };
+        match result {
+            Ok(()) => variants.push(Some(filename)),
+            Err(error) => {
+                eprintln!("poster {} failed for {dest:?}: {error}", variant.name());
+                variants.push(None);
+            }
+        }
+    }
+
+    let small = poster.thumbnail(BLURHASH_SOURCE_SIZE, BLURHASH_SOURCE_SIZE).to_rgba8();
+    let (components_x, components_y) = BLURHASH_COMPONENTS;
+    let blurhash =
+        blurhash::encode(components_x, components_y, small.width(), small.height(), &small)
+            .ok();
+
+    Ok(Rendered { variants, blurhash })
+}
+
+fn write_jpeg(image: &DynamicImage, dest: &Path) -> AppResult<()> {
+    let file = BufWriter::new(File::create(dest)?);
+    JpegEncoder::new_with_quality(file, JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|error| AppError::Other(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    #[test]
+    fn variant_filenames_are_keyed_by_title() {
+        assert_eq!(variant_filename("movie", 42, Variant::Thumb), "movie-42.thumb.jpg");
+        assert_eq!(variant_filename("show", 7, Variant::Hero), "show-7.hero.jpg");
+    }
+
+    #[test]
+    fn variants_are_resized_locally_and_never_upscaled() {
+        let dir = std::env::temp_dir().join(format!("rustflix-posters-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("upload.png");
+        DynamicImage::new_rgb8(500, 750).save(&source).unwrap();
+
+        let rendered = render(&source, &dir, "movie", 42).unwrap();
+        let widths: Vec<(u32, u32)> = rendered
+            .variants
+            .iter()
+            .map(|filename| {
+                image::open(dir.join(filename.as_deref().unwrap())).unwrap().dimensions()
+            })
+            .collect();
+        assert_eq!(widths, vec![(185, 278), (342, 513), (500, 750)]);
+        assert!(rendered.blurhash.is_some());
+
+        std::fs::write(&source, b"not an image").unwrap();
+        assert!(render(&source, &dir, "movie", 42).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
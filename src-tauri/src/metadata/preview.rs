@@ -0,0 +1,514 @@
+//! Refresh preview. Fetches a candidate's details and diffs them
+//! against the title field by field before anything is written, so a
+//! wrong link can be caught before it replaces the overview and poster.
+//! Accepting everything links the title like `link_metadata`; picking
+//! fields writes only those and locks the row, as a manual edit does.
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::http::ProviderHttp;
+use crate::metadata::imdb::{self, PosterSize, TitleNode};
+use crate::metadata::people::{self, CreditInput};
+use crate::metadata::ratings::{self, RatingInput};
+use crate::metadata::tmdb::{self, TmdbMovieDetails, TmdbShowDetails};
+use crate::metadata::worker::PosterDownload;
+use crate::metadata::{apply, genres, locale, posters};
+use crate::queries as app_queries;
+
+/// Previewed fields, in display order.
+pub const FIELDS: [&str; 7] = ["title", "year", "overview", "genres", "cast", "rating", "poster"];
+
+/// Cast members shown in the diff, like `top_cast`.
+const CAST_SHOWN: usize = 10;
+
+/// A provider title to preview or apply.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Candidate {
+    pub provider: String,
+    pub provider_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub current: Value,
+    pub proposed: Value,
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Preview {
+    pub kind: String,
+    pub media_id: i64,
+    pub candidate: Candidate,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// A provider payload reduced to the previewed fields.
+#[derive(Debug, Clone, Default)]
+pub struct Proposed {
+    pub provider: String,
+    pub title: Option<String>,
+    pub year: Option<i32>,
+    pub overview: Option<String>,
+    pub genres: Vec<String>,
+    pub credits: Vec<CreditInput>,
+    /// `top_cast` JSON for the row.
+    pub cast_json: String,
+    pub rating: Option<RatingInput>,
+    /// Where the poster would be downloaded from, for display.
+    pub poster_url: Option<String>,
+    pub poster: Option<PosterDownload>,
+}
+
+impl Proposed {
+    fn cast_names(&self) -> Vec<String> {
+        self.credits
+            .iter()
+            .filter(|credit| credit.role == "cast")
+            .take(CAST_SHOWN)
+            .map(|credit| credit.name.clone())
+            .collect()
+    }
+}
+
+pub fn from_tmdb_movie(
+    details: &TmdbMovieDetails,
+    image_base: &str,
+    media_id: i64,
+) -> Proposed {
+    Proposed {
+        provider: "tmdb".to_string(),
+        title: Some(details.title.clone()),
+        year: parse_year(details.release_date.as_deref()),
+        overview: details.overview.clone().filter(|overview| !overview.is_empty()),
+        genres: details.genres.iter().map(|genre| genre.name.clone()).collect(),
+        credits: people::from_tmdb(details.credits.as_ref(), image_base),
+        cast_json: apply::build_cast_json(details.credits.as_ref().map(|credits| &credits.cast)),
+        rating: ratings::from_tmdb(details.vote_average, details.vote_count),
+        poster_url: details.poster_path.as_ref().map(|path| format!("{image_base}{path}")),
+        poster: tmdb_poster(details.poster_path.as_deref(), "movie", media_id),
+    }
+}
+
+pub fn from_tmdb_show(details: &TmdbShowDetails, image_base: &str, media_id: i64) -> Proposed {
+    Proposed {
+        provider: "tmdb".to_string(),
+        title: Some(details.name.clone()),
+        year: parse_year(details.first_air_date.as_deref()),
+        overview: details.overview.clone().filter(|overview| !overview.is_empty()),
+        genres: details.genres.iter().map(|genre| genre.name.clone()).collect(),
+        credits: people::from_tmdb(details.credits.as_ref(), image_base),
+        cast_json: apply::build_cast_json(details.credits.as_ref().map(|credits| &credits.cast)),
+        rating: ratings::from_tmdb(details.vote_average, details.vote_count),
+        poster_url: details.poster_path.as_ref().map(|path| format!("{image_base}{path}")),
+        poster: tmdb_poster(details.poster_path.as_deref(), "show", media_id),
+    }
+}
+
+pub fn from_imdb(details: &TitleNode, kind: &str, media_id: i64) -> Proposed {
+    let image = details.primary_image.as_ref().map(|image| image.url.clone());
+    Proposed {
+        provider: "imdb".to_string(),
+        title: details.title_text.as_ref().map(|text| text.text.clone()),
+        year: details.release_year.as_ref().and_then(|release| release.year),
+        overview: details
+            .plot
+            .as_ref()
+            .and_then(|plot| plot.plot_text.as_ref())
+            .and_then(|text| text.plain_text.clone()),
+        genres: details
+            .genres
+            .as_ref()
+            .map(|wrapper| wrapper.genres.iter().map(|genre| genre.text.clone()).collect())
+            .unwrap_or_default(),
+        credits: people::from_imdb(&details.principal_credits),
+        cast_json: apply::build_imdb_cast_json(&details.principal_credits),
+        rating: ratings::from_imdb(details.ratings_summary.as_ref()),
+        poster_url: image.clone(),
+        poster: image.map(|url| PosterDownload {
+            url,
+            filename: format!("{kind}-{media_id}.jpg"),
+            size: Some(PosterSize::Small),
+        }),
+    }
+}
+
+fn tmdb_poster(poster_path: Option<&str>, kind: &str, media_id: i64) -> Option<PosterDownload> {
+    let path = poster_path?;
+    let extension = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("jpg").to_lowercase();
+    Some(PosterDownload {
+        url: path.to_string(),
+        filename: format!("{kind}-{media_id}.{extension}"),
+        size: None,
+    })
+}
+
+fn parse_year(date: Option<&str>) -> Option<i32> {
+    date.and_then(|date| date.get(0..4)).and_then(|year| year.parse().ok())
+}
+
+fn table(kind: &str) -> AppResult<&'static str> {
+    match kind {
+        "movie" => Ok("movies"),
+        "show" => Ok("shows"),
+        other => Err(AppError::Other(format!("unknown kind: {other}"))),
+    }
+}
+
+/// Fetches the candidate's details in the scrape language.
+pub async fn fetch(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    kind: &str,
+    media_id: i64,
+    candidate: &Candidate,
+) -> AppResult<Proposed> {
+    let language = locale::scrape_language(pool).await?;
+    let image_base = &http.endpoints().tmdb_image;
+    let id = candidate.provider_id.as_str();
+    match candidate.provider.as_str() {
+        "tmdb" => {
+            let api_key = app_queries::get_app_setting(pool, "tmdb_api_key")
+                .await?
+                .ok_or_else(|| AppError::Other("no TMDB key configured".to_string()))?;
+            match kind {
+                "movie" => {
+                    let details = tmdb::fetch_movie_details(http, &api_key, id, &language).await?;
+                    Ok(from_tmdb_movie(&details, image_base, media_id))
+                }
+                "show" => {
+                    let details = tmdb::fetch_show_details(http, &api_key, id, &language).await?;
+                    Ok(from_tmdb_show(&details, image_base, media_id))
+                }
+                other => Err(AppError::Other(format!("unknown kind: {other}"))),
+            }
+        }
+        "imdb" => {
+            let details = match kind {
+                "movie" => imdb::fetch_movie_details(http, id, &language).await?,
+                "show" => imdb::fetch_show_details(http, id, &language).await?,
+                other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+            };
+            Ok(from_imdb(&details, kind, media_id))
+        }
+        other => Err(AppError::Other(format!("unknown provider: {other}"))),
+    }
+}
+
+/// The title's current link, for previewing a refresh.
+pub async fn current_candidate(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+) -> AppResult<Candidate> {
+    let table = table(kind)?;
+    let row: Option<(Option<String>, Option<String>)> =
+        sqlx::query_as(&format!("SELECT provider, provider_id FROM {table} WHERE id = ?1"))
+            .bind(media_id)
+            .fetch_optional(pool)
+            .await?;
+    match row {
+        Some((Some(provider), Some(provider_id))) => Ok(Candidate { provider, provider_id }),
+        Some(_) => Err(AppError::Other(format!("{kind} {media_id} is not linked"))),
+        None => Err(AppError::MediaNotFound(media_id)),
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CurrentRow {
+    title: String,
+    year: Option<i32>,
+    overview: Option<String>,
+    genres: Option<String>,
+    top_cast: Option<String>,
+    rating: Option<f64>,
+    poster_path: Option<String>,
+    poster_origin: Option<String>,
+}
+
+/// Field-by-field diff of `proposed` against the row. A provider's
+/// poster can't be compared with the downloaded file, so it counts as a
+/// change unless the current poster is manual, which sync keeps.
+pub async fn diff(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    proposed: &Proposed,
+) -> AppResult<Vec<FieldDiff>> {
+    let table = table(kind)?;
+    let row: Option<CurrentRow> = sqlx::query_as(&format!(
+        "SELECT title, year, overview, genres, top_cast, rating, poster_path, poster_origin
+         FROM {table} WHERE id = ?1"
+    ))
+    .bind(media_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some(current) = row else {
+        return Err(AppError::MediaNotFound(media_id));
+    };
+
+    let current_genres: Vec<String> = current
+        .genres
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+    let current_cast: Vec<String> = current
+        .top_cast
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<Vec<Value>>(json).ok())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|member| member.get("name")?.as_str().map(str::to_string))
+        .take(CAST_SHOWN)
+        .collect();
+    let proposed_rating = proposed.rating.map(|rating| rating.value * 10.0 / rating.scale);
+
+    let fields = vec![
+        field("title", json!(current.title), json!(proposed.title)),
+        field("year", json!(current.year), json!(proposed.year)),
+        field("overview", json!(current.overview), json!(proposed.overview)),
+        field("genres", json!(current_genres), json!(proposed.genres)),
+        field("cast", json!(current_cast), json!(proposed.cast_names())),
+        field("rating", json!(current.rating), json!(proposed_rating)),
+        FieldDiff {
+            field: "poster".to_string(),
+            current: json!(current.poster_path),
+            proposed: json!(proposed.poster_url),
+            changed: proposed.poster.is_some()
+                && current.poster_origin.as_deref() != Some("manual"),
+        },
+    ];
+    Ok(fields)
+}
+
+fn field(name: &str, current: Value, proposed: Value) -> FieldDiff {
+    let changed = match (&current, &proposed) {
+        (Value::Number(current), Value::Number(proposed)) => {
+            (current.as_f64().unwrap_or(0.0) - proposed.as_f64().unwrap_or(0.0)).abs() > 1e-6
+        }
+        _ => current != proposed,
+    };
+    FieldDiff {
+        field: name.to_string(),
+        current,
+        proposed,
+        changed,
+    }
+}
+
+/// Writes the picked `fields` of `proposed` and locks the row so sync
+/// leaves the rest alone. The poster is downloaded before anything is
+/// written; its variants follow best-effort.
+pub async fn apply_fields(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    kind: &str,
+    media_id: i64,
+    proposed: &Proposed,
+    fields: &[String],
+) -> AppResult<()> {
+    let table = table(kind)?;
+    if let Some(unknown) = fields.iter().find(|field| !FIELDS.contains(&field.as_str())) {
+        return Err(AppError::Other(format!("unknown field: {unknown}")));
+    }
+    let picked = |name: &str| fields.iter().any(|field| field == name);
+    let provider = proposed.provider.as_str();
+
+    let poster = proposed.poster.as_ref().filter(|_| picked("poster"));
+    if let Some(download) = poster {
+        posters::download(http, posters_dir, download).await?;
+    }
+
+    let mut tx = pool.begin().await?;
+    let genres_json = serde_json::to_string(&proposed.genres).unwrap_or_else(|_| "[]".to_string());
+    let rating = proposed.rating.map(|rating| rating.value * 10.0 / rating.scale);
+    // Title, year and overview keep their value when the provider has none.
+    let updated = sqlx::query(&format!(
+        "UPDATE {table} SET
+             title = CASE WHEN ?1 THEN COALESCE(?2, title) ELSE title END,
+             year = CASE WHEN ?3 THEN COALESCE(?4, year) ELSE year END,
+             overview = CASE WHEN ?5 THEN COALESCE(?6, overview) ELSE overview END,
+             genres = CASE WHEN ?7 THEN ?8 ELSE genres END,
+             top_cast = CASE WHEN ?9 THEN ?10 ELSE top_cast END,
+             rating = CASE WHEN ?11 THEN ?12 ELSE rating END,
+             metadata_locked = 1
+         WHERE id = ?13"
+    ))
+    .bind(picked("title"))
+    .bind(proposed.title.as_deref())
+    .bind(picked("year"))
+    .bind(proposed.year)
+    .bind(picked("overview"))
+    .bind(proposed.overview.as_deref())
+    .bind(picked("genres"))
+    .bind(&genres_json)
+    .bind(picked("cast"))
+    .bind(&proposed.cast_json)
+    .bind(picked("rating"))
+    .bind(rating)
+    .bind(media_id)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::MediaNotFound(media_id));
+    }
+
+    if picked("genres") {
+        genres::store_in_tx(&mut tx, kind, media_id, provider, &proposed.genres).await?;
+    }
+    if picked("cast") {
+        people::store_in_tx(&mut tx, kind, media_id, provider, &proposed.credits).await?;
+    }
+    if picked("rating") {
+        ratings::store_in_tx(&mut tx, kind, media_id, provider, proposed.rating).await?;
+    }
+    if let Some(download) = poster {
+        sqlx::query(&format!(
+            "UPDATE {table} SET poster_path = ?1, poster_origin = ?2,
+                 poster_thumb_path = NULL, poster_card_path = NULL, poster_hero_path = NULL,
+                 poster_blurhash = NULL
+             WHERE id = ?3"
+        ))
+        .bind(&download.filename)
+        .bind(provider)
+        .bind(media_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    if let Some(download) = poster {
+        if let Err(error) =
+            posters::store_variants(pool, posters_dir, kind, media_id, &download.filename).await
+        {
+            eprintln!("poster variants for {kind} {media_id} failed: {error}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::fake_server::{FakeServer, TMDB_MOVIE_DETAILS};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    /// An unlinked "The Matrix (1999)" with a hand-written overview.
+    async fn seed_movie(pool: &SqlitePool) {
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/tmp', 'movies')")
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, year, overview, genres, path)
+             VALUES (1, 1, 'The Matrix', 1999, 'Kept.', '[\"Action\"]',
+                     '/tmp/The Matrix (1999).mkv')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn diff_flags_only_the_fields_that_would_change() {
+        let pool = fresh_pool().await;
+        seed_movie(&pool).await;
+        let details: TmdbMovieDetails = serde_json::from_str(TMDB_MOVIE_DETAILS).unwrap();
+        let proposed = from_tmdb_movie(&details, "https://image.tmdb.org/t/p/w500", 1);
+
+        let fields = diff(&pool, "movie", 1, &proposed).await.unwrap();
+        let changed: Vec<(&str, bool)> =
+            fields.iter().map(|field| (field.field.as_str(), field.changed)).collect();
+        assert_eq!(
+            changed,
+            vec![
+                ("title", false),
+                ("year", false),
+                ("overview", true),
+                ("genres", true),
+                ("cast", true),
+                ("rating", true),
+                ("poster", true),
+            ]
+        );
+        assert_eq!(fields[3].proposed, json!(["Action", "Science Fiction"]));
+
+        sqlx::query("UPDATE movies SET poster_path = 'mine.jpg', poster_origin = 'manual'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let fields = diff(&pool, "movie", 1, &proposed).await.unwrap();
+        assert!(!fields[6].changed);
+    }
+
+    #[tokio::test]
+    async fn applying_picked_fields_writes_only_those_and_locks_the_row() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let pool = fresh_pool().await;
+        seed_movie(&pool).await;
+        app_queries::set_app_setting(&pool, "tmdb_api_key", "test-key").await.unwrap();
+        let http = ProviderHttp::new(reqwest::Client::new(), pool.clone())
+            .with_endpoints(server.endpoints());
+        let posters_dir = std::env::temp_dir().join(format!(
+            "rustflix-preview-{}-{}",
+            std::process::id(),
+            server.base_url().rsplit(':').next().unwrap()
+        ));
+
+        let candidate = Candidate {
+            provider: "tmdb".to_string(),
+            provider_id: "603".to_string(),
+        };
+        let proposed = fetch(&pool, &http, "movie", 1, &candidate).await.unwrap();
+        let unknown = ["plot".to_string()];
+        assert!(apply_fields(&pool, &http, &posters_dir, "movie", 1, &proposed, &unknown)
+            .await
+            .is_err());
+
+        let picked = ["genres".to_string(), "poster".to_string()];
+        apply_fields(&pool, &http, &posters_dir, "movie", 1, &proposed, &picked)
+            .await
+            .unwrap();
+
+        let (overview, genres, poster, locked, provider): (
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            i64,
+            Option<String>,
+        ) = sqlx::query_as(
+            "SELECT overview, genres, poster_path, metadata_locked, provider
+             FROM movies WHERE id = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(overview.as_deref(), Some("Kept."));
+        assert_eq!(genres.as_deref(), Some(r#"["Action","Science Fiction"]"#));
+        assert_eq!(poster.as_deref(), Some("movie-1.jpg"));
+        assert_eq!(locked, 1);
+        assert_eq!(provider, None);
+        assert!(posters_dir.join("movie-1.jpg").exists());
+
+        let _ = std::fs::remove_dir_all(&posters_dir);
+    }
+}
@@ -2,8 +2,9 @@
 
 use sqlx::SqlitePool;
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 use crate::metadata::dispatch::ParkReason;
+use crate::metadata::{aliases, certifications, external_ids, genres, review};
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct MetadataJob {
@@ -13,9 +14,19 @@ pub struct MetadataJob {
     pub attempts: i64,
     #[allow(dead_code)]
     pub last_error: Option<String>,
-    pub next_attempt_at: i64,
+    /// Set by `force_enqueue` and the refresh scheduler: provider calls
+    /// revalidate cached responses instead of trusting their TTL.
+    pub revalidate: bool,
 }
 
+/// Priority for jobs created by a scan or a user action. `next_due`
+/// drains higher priorities first.
+pub const PRIORITY_NORMAL: i64 = 0;
+
+/// Priority for jobs created by the stale-metadata refresh scheduler, so
+/// a large refresh batch never delays posters for freshly scanned titles.
+pub const PRIORITY_REFRESH: i64 = -10;
+
 pub async fn enqueue(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<()> {
     sqlx::query(
         "INSERT INTO metadata_jobs (kind, media_id) VALUES (?1, ?2)
@@ -31,38 +42,292 @@ pub async fn enqueue(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<
 
 /// Force-enqueue resets an existing job (or inserts a new one) so the
 /// worker treats it as fresh. Used by "Refresh metadata" and "Unlink".
+/// Also promotes a queued background refresh back to normal priority,
+/// and marks the job to revalidate cached provider responses.
 pub async fn force_enqueue(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<()> {
+    let mut conn = pool.acquire().await?;
+    force_enqueue_in_tx(&mut conn, kind, media_id).await
+}
+
+pub async fn force_enqueue_in_tx(
+    conn: &mut sqlx::SqliteConnection,
+    kind: &str,
+    media_id: i64,
+) -> AppResult<()> {
     sqlx::query(
-        "INSERT INTO metadata_jobs (kind, media_id) VALUES (?1, ?2)
+        "INSERT INTO metadata_jobs (kind, media_id, revalidate) VALUES (?1, ?2, 1)
          ON CONFLICT(kind, media_id) DO UPDATE SET
              attempts = 0,
              next_attempt_at = strftime('%s','now'),
-             last_error = NULL",
+             last_error = NULL,
+             priority = MAX(priority, ?3),
+             revalidate = 1",
     )
     .bind(kind)
     .bind(media_id)
-    .execute(pool)
+    .bind(PRIORITY_NORMAL)
+    .execute(&mut *conn)
     .await?;
 
     Ok(())
 }
 
-/// Returns the next job whose next_attempt_at <= now and that isn't parked
-/// on either sentinel. None ⇒ queue is empty / fully parked.
-pub async fn next_due(pool: &SqlitePool) -> AppResult<Option<MetadataJob>> {
+/// Points a show/movie at `provider`/`provider_id`, unlocks it and
+/// force-enqueues it so the worker fetches details by id. Also records a
+/// title alias so the same scanned title links itself next time. Shared
+/// by `link_metadata` and the Needs-review batch approve.
+pub async fn link_in_tx(
+    conn: &mut sqlx::SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    provider: &str,
+    provider_id: &str,
+) -> AppResult<()> {
+    if !matches!(provider, "tmdb" | "imdb") {
+        return Err(AppError::Other(format!("unknown provider: {provider}")));
+    }
+
+    let table = match kind {
+        "show" => "shows",
+        "movie" => "movies",
+        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+    };
+
+    let row: Option<(String, Option<i32>)> =
+        sqlx::query_as(&format!("SELECT title, year FROM {table} WHERE id = ?1"))
+            .bind(media_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some((title, year)) = row else {
+        return Err(AppError::MediaNotFound(media_id));
+    };
+
+    sqlx::query(&format!(
+        "UPDATE {table} SET provider = ?2, provider_id = ?3, metadata_locked = 0
+         WHERE id = ?1"
+    ))
+    .bind(media_id)
+    .bind(provider)
+    .bind(provider_id)
+    .execute(&mut *conn)
+    .await?;
+
+    // Ids learned for the previous link may belong to another title.
+    external_ids::clear_in_tx(conn, kind, media_id).await?;
+    aliases::record_in_tx(conn, kind, &title, year, provider, provider_id).await?;
+    force_enqueue_in_tx(conn, kind, media_id).await?;
+    review::clear(conn, kind, media_id).await
+}
+
+/// Drops a show/movie's link, the alias a hand link recorded and
+/// everything the match wrote, unlocks it and force-enqueues it so the
+/// worker searches afresh. Backs `unlink_metadata`.
+pub async fn unlink_in_tx(
+    conn: &mut sqlx::SqliteConnection,
+    kind: &str,
+    media_id: i64,
+) -> AppResult<()> {
+    let (table, extras) = match kind {
+        "show" => (
+            "shows",
+            "first_air_date = NULL, end_year = NULL, networks = NULL,
+             next_episode_air_date = NULL,",
+        ),
+        "movie" => ("movies", "runtime_minutes = NULL, "),
+        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+    };
+
+    let row: Option<(String, Option<i32>)> =
+        sqlx::query_as(&format!("SELECT title, year FROM {table} WHERE id = ?1"))
+            .bind(media_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let Some((title, year)) = row else {
+        return Err(AppError::MediaNotFound(media_id));
+    };
+    aliases::forget_in_tx(conn, kind, &title, year).await?;
+
+    sqlx::query(&format!(
+        "UPDATE {table} SET
+             provider = NULL,
+             provider_id = NULL,
+             rating = NULL,
+             genres = NULL,
+             top_cast = NULL,
+             {extras}
+             metadata_synced_at = NULL,
+             metadata_locked = 0
+         WHERE id = ?1"
+    ))
+    .bind(media_id)
+    .execute(&mut *conn)
+    .await?;
+    if kind == "show" {
+        sqlx::query("DELETE FROM show_air_dates WHERE show_id = ?1")
+            .bind(media_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    sqlx::query("DELETE FROM ratings WHERE kind = ?1 AND media_id = ?2")
+        .bind(kind)
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+    // Out of genre filters and counts until the next match.
+    genres::clear_in_tx(conn, kind, media_id).await?;
+    // The wrong match's cast and crew, so the title leaves their
+    // filmographies.
+    sqlx::query("DELETE FROM credits WHERE kind = ?1 AND media_id = ?2")
+        .bind(kind)
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+    // Unrated until the next match, so parental controls stop going by
+    // the wrong title's rating.
+    certifications::store_in_tx(conn, kind, media_id, &[]).await?;
+    external_ids::clear_in_tx(conn, kind, media_id).await?;
+    force_enqueue_in_tx(conn, kind, media_id).await
+}
+
+/// Claims the next job whose next_attempt_at <= now and that isn't parked
+/// on either sentinel, highest priority first. Selecting and stamping
+/// `claimed_at` is one statement, so concurrent callers never get the
+/// same job. Claims older than `stale_after_secs` are considered
+/// abandoned. None ⇒ nothing claimable right now.
+pub async fn claim_next(
+    pool: &SqlitePool,
+    stale_after_secs: i64,
+) -> AppResult<Option<MetadataJob>> {
     let job: Option<MetadataJob> = sqlx::query_as(
-        "SELECT kind, media_id, attempts, last_error, next_attempt_at
+        "UPDATE metadata_jobs SET claimed_at = strftime('%s','now')
+         WHERE (kind, media_id) IN (
+             SELECT kind, media_id
+             FROM metadata_jobs
+             WHERE COALESCE(last_error, '') NOT IN ('tmdb_auth_required', 'no_provider_available')
+               AND attempts < 8
+               AND next_attempt_at <= strftime('%s','now')
+               AND (claimed_at IS NULL OR claimed_at <= strftime('%s','now') - ?1)
+             ORDER BY priority DESC, next_attempt_at ASC
+             LIMIT 1
+         )
+         RETURNING kind, media_id, attempts, last_error, revalidate",
+    )
+    .bind(stale_after_secs)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(job)
+}
+
+/// Re-stamps a live claim so `claim_next` never takes it for abandoned,
+/// however long the job runs. A no-op once the claim was released.
+pub async fn refresh_claim(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE metadata_jobs SET claimed_at = strftime('%s','now')
+         WHERE kind = ?1 AND media_id = ?2 AND claimed_at IS NOT NULL",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Hands a claimed job back to the queue. A no-op when the job was
+/// deleted while claimed.
+pub async fn release_claim(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<()> {
+    sqlx::query("UPDATE metadata_jobs SET claimed_at = NULL WHERE kind = ?1 AND media_id = ?2")
+        .bind(kind)
+        .bind(media_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Clears every claim. Run at worker startup: nothing can be in flight
+/// yet, so any claim left over belongs to a previous process.
+pub async fn release_all_claims(pool: &SqlitePool) -> AppResult<()> {
+    sqlx::query("UPDATE metadata_jobs SET claimed_at = NULL WHERE claimed_at IS NOT NULL")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Earliest `next_attempt_at` among unclaimed, unparked jobs still
+/// backing off, so the worker can sleep until then instead of waiting
+/// for a notify that may never come.
+pub async fn next_wake_at(pool: &SqlitePool) -> AppResult<Option<i64>> {
+    let at: Option<i64> = sqlx::query_scalar(
+        "SELECT MIN(next_attempt_at)
          FROM metadata_jobs
          WHERE COALESCE(last_error, '') NOT IN ('tmdb_auth_required', 'no_provider_available')
            AND attempts < 8
-           AND next_attempt_at <= strftime('%s','now')
-         ORDER BY next_attempt_at ASC
-         LIMIT 1",
+           AND claimed_at IS NULL",
     )
-    .fetch_optional(pool)
+    .fetch_one(pool)
     .await?;
 
-    Ok(job)
+    Ok(at)
+}
+
+/// Enqueues a low-priority refresh for every linked, unlocked row whose
+/// `metadata_synced_at` is older than its age threshold. Movies and ended
+/// shows use `ended_max_age_secs`; shows whose provider status isn't
+/// `Ended`/`Canceled` use `continuing_max_age_secs`. `None` disables that
+/// class. Rows that already have a job are left alone. Returns the number
+/// of jobs inserted. The jobs revalidate cached responses, since the
+/// point is to pick up what changed at the provider.
+pub async fn enqueue_stale(
+    pool: &SqlitePool,
+    ended_max_age_secs: Option<i64>,
+    continuing_max_age_secs: Option<i64>,
+) -> AppResult<u64> {
+    let mut inserted = 0;
+
+    if let Some(max_age) = ended_max_age_secs {
+        inserted += sqlx::query(
+            "INSERT INTO metadata_jobs (kind, media_id, priority, revalidate)
+             SELECT 'movie', id, ?2, 1 FROM movies
+             WHERE provider IS NOT NULL
+               AND metadata_locked = 0
+               AND metadata_synced_at IS NOT NULL
+               AND metadata_synced_at <= strftime('%s','now') - ?1
+             ON CONFLICT(kind, media_id) DO NOTHING",
+        )
+        .bind(max_age)
+        .bind(PRIORITY_REFRESH)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    for (max_age, continuing) in [(ended_max_age_secs, false), (continuing_max_age_secs, true)] {
+        let Some(max_age) = max_age else {
+            continue;
+        };
+
+        inserted += sqlx::query(
+            "INSERT INTO metadata_jobs (kind, media_id, priority, revalidate)
+             SELECT 'show', id, ?2, 1 FROM shows
+             WHERE provider IS NOT NULL
+               AND metadata_locked = 0
+               AND metadata_synced_at IS NOT NULL
+               AND metadata_synced_at <= strftime('%s','now') - ?1
+               AND (COALESCE(status, '') NOT IN ('Ended', 'Canceled')) = ?3
+             ON CONFLICT(kind, media_id) DO NOTHING",
+        )
+        .bind(max_age)
+        .bind(PRIORITY_REFRESH)
+        .bind(continuing)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+
+    Ok(inserted)
 }
 
 pub async fn park_with_reason(
@@ -100,6 +365,112 @@ pub async fn wake_parked(pool: &SqlitePool) -> AppResult<()> {
     Ok(())
 }
 
+/// A job that has failed at least once, with its media title. `title` is
+/// `None` when the show/movie row is gone (see `purge_orphaned`).
+#[derive(Debug, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct FailedJob {
+    pub kind: String,
+    pub media_id: i64,
+    pub title: Option<String>,
+    pub year: Option<i32>,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub next_attempt_at: i64,
+    /// `attempts` reached the cap; the worker won't touch it again.
+    pub dead_lettered: bool,
+}
+
+/// `(kind, media_id)` as sent by the frontend when acting on jobs.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct JobRef {
+    pub kind: String,
+    pub media_id: i64,
+}
+
+/// Failed and dead-lettered jobs, dead-lettered first, then by most
+/// recent failure.
+pub async fn list_failed(pool: &SqlitePool) -> AppResult<Vec<FailedJob>> {
+    let jobs: Vec<FailedJob> = sqlx::query_as(
+        "SELECT j.kind, j.media_id,
+                COALESCE(s.title, m.title) AS title,
+                COALESCE(s.year, m.year) AS year,
+                j.attempts, j.last_error, j.next_attempt_at,
+                j.attempts >= 8 AS dead_lettered
+         FROM metadata_jobs j
+         LEFT JOIN shows s ON j.kind = 'show' AND s.id = j.media_id
+         LEFT JOIN movies m ON j.kind = 'movie' AND m.id = j.media_id
+         WHERE j.attempts > 0
+         ORDER BY dead_lettered DESC, j.next_attempt_at DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(jobs)
+}
+
+/// Resets attempts and backoff so the worker retries right away. `None`
+/// retries every failed or dead-lettered job. Returns the number reset.
+pub async fn retry_failed(pool: &SqlitePool, jobs: Option<&[JobRef]>) -> AppResult<u64> {
+    const RESET: &str = "UPDATE metadata_jobs SET
+             attempts = 0,
+             next_attempt_at = strftime('%s','now'),
+             last_error = NULL";
+
+    let Some(jobs) = jobs else {
+        let result = sqlx::query(&format!("{RESET} WHERE attempts > 0"))
+            .execute(pool)
+            .await?;
+        return Ok(result.rows_affected());
+    };
+
+    let mut tx = pool.begin().await?;
+    let mut reset = 0;
+    for job in jobs {
+        reset += sqlx::query(&format!("{RESET} WHERE kind = ?1 AND media_id = ?2"))
+            .bind(&job.kind)
+            .bind(job.media_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    tx.commit().await?;
+
+    Ok(reset)
+}
+
+/// Drops the given jobs without touching their media. Unlinked titles
+/// then show up in Needs-review for a manual match. Returns the number
+/// removed.
+pub async fn dismiss(pool: &SqlitePool, jobs: &[JobRef]) -> AppResult<u64> {
+    let mut tx = pool.begin().await?;
+    let mut removed = 0;
+    for job in jobs {
+        removed += sqlx::query("DELETE FROM metadata_jobs WHERE kind = ?1 AND media_id = ?2")
+            .bind(&job.kind)
+            .bind(job.media_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    tx.commit().await?;
+
+    Ok(removed)
+}
+
+/// Deletes jobs whose show/movie row no longer exists. Returns the
+/// number removed.
+pub async fn purge_orphaned(pool: &SqlitePool) -> AppResult<u64> {
+    let result = sqlx::query(
+        "DELETE FROM metadata_jobs
+         WHERE (kind = 'show' AND NOT EXISTS (SELECT 1 FROM shows WHERE id = media_id))
+            OR (kind = 'movie' AND NOT EXISTS (SELECT 1 FROM movies WHERE id = media_id))",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 /// Exponential backoff: attempts++; next_attempt_at = now + min(60·2^attempts, 3600).
 pub async fn record_failure(
     pool: &SqlitePool,
@@ -127,6 +498,31 @@ pub async fn record_failure(
     Ok(())
 }
 
+/// Pushes a throttled job back by `delay_secs` without touching
+/// `attempts` — a provider saying "slow down" isn't the job's fault.
+pub async fn defer(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    delay_secs: i64,
+    message: &str,
+) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE metadata_jobs SET
+             next_attempt_at = strftime('%s','now') + ?3,
+             last_error = ?4
+         WHERE kind = ?1 AND media_id = ?2",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .bind(delay_secs)
+    .bind(message)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn delete_in_tx(
     conn: &mut sqlx::SqliteConnection,
     kind: &str,
@@ -144,6 +540,7 @@ pub async fn delete_in_tx(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metadata::people;
     use sqlx::sqlite::SqlitePoolOptions;
 
     async fn fresh_pool() -> SqlitePool {
@@ -178,13 +575,13 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn enqueue_then_next_due_returns_the_job() {
+    async fn enqueue_then_claim_next_returns_the_job() {
         let pool = fresh_pool().await;
         let show_id = seed_show(&pool).await;
 
         enqueue(&pool, "show", show_id).await.unwrap();
 
-        let job = next_due(&pool).await.unwrap().expect("a job");
+        let job = claim_next(&pool, 600).await.unwrap().expect("a job");
         assert_eq!(job.kind, "show");
         assert_eq!(job.media_id, show_id);
         assert_eq!(job.attempts, 0);
@@ -209,7 +606,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn park_with_tmdb_auth_excludes_job_from_next_due() {
+    async fn park_with_tmdb_auth_excludes_job_from_claim_next() {
         let pool = fresh_pool().await;
         let show_id = seed_show(&pool).await;
         enqueue(&pool, "show", show_id).await.unwrap();
@@ -218,12 +615,12 @@ mod tests {
             .await
             .unwrap();
 
-        let job = next_due(&pool).await.unwrap();
+        let job = claim_next(&pool, 600).await.unwrap();
         assert!(job.is_none(), "parked job should be excluded");
     }
 
     #[tokio::test]
-    async fn park_with_no_provider_excludes_job_from_next_due() {
+    async fn park_with_no_provider_excludes_job_from_claim_next() {
         let pool = fresh_pool().await;
         let show_id = seed_show(&pool).await;
         enqueue(&pool, "show", show_id).await.unwrap();
@@ -232,7 +629,7 @@ mod tests {
             .await
             .unwrap();
 
-        let job = next_due(&pool).await.unwrap();
+        let job = claim_next(&pool, 600).await.unwrap();
         assert!(job.is_none(), "parked job should be excluded");
     }
 
@@ -282,27 +679,88 @@ mod tests {
             .await
             .unwrap();
 
-        // Both rows should be excluded from next_due before wake.
-        assert!(next_due(&pool).await.unwrap().is_none());
+        // Both rows should be excluded from claim_next before wake.
+        assert!(claim_next(&pool, 600).await.unwrap().is_none());
 
         wake_parked(&pool).await.unwrap();
 
         // After wake, both rows should be available; pull one then the other.
-        let first = next_due(&pool).await.unwrap().expect("first job back");
+        let first = claim_next(&pool, 600).await.unwrap().expect("first job back");
         assert!(first.last_error.is_none());
 
         // Drain it so the second can surface.
         let mut conn = pool.acquire().await.unwrap();
-        delete_in_tx(&mut *conn, &first.kind, first.media_id)
+        delete_in_tx(&mut conn, &first.kind, first.media_id)
             .await
             .unwrap();
         drop(conn);
 
-        let second = next_due(&pool).await.unwrap().expect("second job back");
+        let second = claim_next(&pool, 600).await.unwrap().expect("second job back");
         assert!(second.last_error.is_none());
         assert_ne!((first.kind, first.media_id), (second.kind, second.media_id));
     }
 
+    #[tokio::test]
+    async fn claimed_job_is_not_handed_out_twice() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        enqueue(&pool, "show", show_id).await.unwrap();
+
+        let job = claim_next(&pool, 600).await.unwrap().expect("a job");
+        assert_eq!(job.media_id, show_id);
+        assert!(claim_next(&pool, 600).await.unwrap().is_none());
+
+        release_claim(&pool, "show", show_id).await.unwrap();
+        assert!(claim_next(&pool, 600).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn abandoned_claims_can_be_taken_again() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        enqueue(&pool, "show", show_id).await.unwrap();
+        sqlx::query("UPDATE metadata_jobs SET claimed_at = strftime('%s','now') - 3600")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(claim_next(&pool, 7200).await.unwrap().is_none());
+        assert!(claim_next(&pool, 600).await.unwrap().is_some());
+
+        // A heartbeat keeps a long-running claim from looking abandoned.
+        sqlx::query("UPDATE metadata_jobs SET claimed_at = strftime('%s','now') - 3600")
+            .execute(&pool)
+            .await
+            .unwrap();
+        refresh_claim(&pool, "show", show_id).await.unwrap();
+        assert!(claim_next(&pool, 600).await.unwrap().is_none());
+
+        release_all_claims(&pool).await.unwrap();
+        let claimed: Option<i64> = sqlx::query_scalar("SELECT claimed_at FROM metadata_jobs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(claimed.is_none());
+    }
+
+    #[tokio::test]
+    async fn next_wake_at_reports_backed_off_jobs() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        assert!(next_wake_at(&pool).await.unwrap().is_none());
+
+        enqueue(&pool, "show", show_id).await.unwrap();
+        defer(&pool, "show", show_id, 120, "rate_limited: tmdb").await.unwrap();
+
+        let now: i64 = sqlx::query_scalar("SELECT CAST(strftime('%s','now') AS INTEGER)")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let at = next_wake_at(&pool).await.unwrap().expect("a wake time");
+        assert!(at >= now + 119 && at <= now + 121);
+        assert!(claim_next(&pool, 600).await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn record_failure_increments_attempts_and_backs_off() {
         let pool = fresh_pool().await;
@@ -330,6 +788,191 @@ mod tests {
         assert!(next_attempt_at >= now + 60 && next_attempt_at <= now + 200);
     }
 
+    #[tokio::test]
+    async fn defer_pushes_back_without_counting_an_attempt() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        enqueue(&pool, "show", show_id).await.unwrap();
+
+        defer(&pool, "show", show_id, 30, "rate_limited: tmdb").await.unwrap();
+
+        let (attempts, next_attempt_at, last_error): (i64, i64, Option<String>) = sqlx::query_as(
+            "SELECT attempts, next_attempt_at, last_error FROM metadata_jobs
+             WHERE kind = 'show' AND media_id = ?1",
+        )
+        .bind(show_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let now: i64 = sqlx::query_scalar("SELECT CAST(strftime('%s','now') AS INTEGER)")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(attempts, 0);
+        assert_eq!(last_error.as_deref(), Some("rate_limited: tmdb"));
+        assert!(next_attempt_at >= now + 29 && next_attempt_at <= now + 31);
+    }
+
+    async fn dead_letter(pool: &SqlitePool, kind: &str, media_id: i64) {
+        sqlx::query(
+            "UPDATE metadata_jobs SET attempts = 8, last_error = 'boom'
+             WHERE kind = ?1 AND media_id = ?2",
+        )
+        .bind(kind)
+        .bind(media_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_failed_includes_titles_and_orphans() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        enqueue(&pool, "show", show_id).await.unwrap();
+        enqueue(&pool, "movie", 999).await.unwrap();
+        dead_letter(&pool, "show", show_id).await;
+        record_failure(&pool, "movie", 999, "gone").await.unwrap();
+
+        let jobs = list_failed(&pool).await.unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].title.as_deref(), Some("Test"));
+        assert!(jobs[0].dead_lettered);
+        assert!(jobs[1].title.is_none());
+        assert!(!jobs[1].dead_lettered);
+
+        assert_eq!(purge_orphaned(&pool).await.unwrap(), 1);
+        assert_eq!(list_failed(&pool).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_failed_resets_selected_or_all() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        enqueue(&pool, "show", show_id).await.unwrap();
+        enqueue(&pool, "movie", 7).await.unwrap();
+        dead_letter(&pool, "show", show_id).await;
+        dead_letter(&pool, "movie", 7).await;
+
+        let selected = [JobRef {
+            kind: "show".to_string(),
+            media_id: show_id,
+        }];
+        assert_eq!(retry_failed(&pool, Some(&selected)).await.unwrap(), 1);
+        let job = claim_next(&pool, 600).await.unwrap().expect("retried job");
+        assert_eq!((job.kind.as_str(), job.attempts), ("show", 0));
+
+        assert_eq!(retry_failed(&pool, None).await.unwrap(), 1);
+        assert!(list_failed(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dismissed_jobs_land_in_needs_review() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        enqueue(&pool, "show", show_id).await.unwrap();
+        dead_letter(&pool, "show", show_id).await;
+        assert!(crate::queries::list_needs_review(&pool).await.unwrap().is_empty());
+
+        let selected = [JobRef {
+            kind: "show".to_string(),
+            media_id: show_id,
+        }];
+        assert_eq!(dismiss(&pool, &selected).await.unwrap(), 1);
+
+        let review = crate::queries::list_needs_review(&pool).await.unwrap();
+        assert_eq!(review.len(), 1);
+        assert_eq!(review[0].id, show_id);
+    }
+
+    async fn mark_synced(pool: &SqlitePool, table: &str, id: i64, age_secs: i64, status: Option<&str>) {
+        let extra = if table == "shows" { ", status = ?3" } else { "" };
+        let sql = format!(
+            "UPDATE {table} SET provider = 'tmdb', provider_id = CAST(?1 AS TEXT),
+                 metadata_synced_at = strftime('%s','now') - ?2{extra}
+             WHERE id = ?1"
+        );
+        let mut query = sqlx::query(&sql).bind(id).bind(age_secs);
+        if table == "shows" {
+            query = query.bind(status);
+        }
+        query.execute(pool).await.expect("mark synced");
+    }
+
+    #[tokio::test]
+    async fn enqueue_stale_uses_shorter_age_for_continuing_shows() {
+        let pool = fresh_pool().await;
+        let continuing_id = seed_show(&pool).await;
+        sqlx::query(
+            "INSERT INTO shows (library_id, title, folder_path, fingerprint)
+             VALUES (1, 'Ended', '/tmp/ended', 'ended')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let ended_id: i64 = sqlx::query_scalar("SELECT last_insert_rowid()")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let ten_days = 10 * 86_400;
+        mark_synced(&pool, "shows", continuing_id, ten_days, Some("Returning Series")).await;
+        mark_synced(&pool, "shows", ended_id, ten_days, Some("Ended")).await;
+
+        let inserted = enqueue_stale(&pool, Some(30 * 86_400), Some(7 * 86_400))
+            .await
+            .unwrap();
+        assert_eq!(inserted, 1);
+
+        let job = claim_next(&pool, 600).await.unwrap().expect("a refresh job");
+        assert_eq!((job.kind.as_str(), job.media_id), ("show", continuing_id));
+    }
+
+    #[tokio::test]
+    async fn enqueue_stale_skips_locked_and_unlinked_rows() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        mark_synced(&pool, "shows", show_id, 90 * 86_400, None).await;
+        sqlx::query("UPDATE shows SET metadata_locked = 1 WHERE id = ?1")
+            .bind(show_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (library_id, title, path) VALUES (1, 'Unlinked', '/tmp/u.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let inserted = enqueue_stale(&pool, Some(86_400), Some(86_400)).await.unwrap();
+        assert_eq!(inserted, 0);
+    }
+
+    #[tokio::test]
+    async fn refresh_jobs_run_behind_scan_jobs() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        mark_synced(&pool, "shows", show_id, 90 * 86_400, Some("Ended")).await;
+        enqueue_stale(&pool, Some(86_400), None).await.unwrap();
+
+        sqlx::query(
+            "INSERT INTO movies (library_id, title, path) VALUES (1, 'New', '/tmp/new.mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let movie_id: i64 = sqlx::query_scalar("SELECT last_insert_rowid()")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        enqueue(&pool, "movie", movie_id).await.unwrap();
+
+        let job = claim_next(&pool, 600).await.unwrap().expect("a job");
+        assert_eq!((job.kind.as_str(), job.media_id), ("movie", movie_id));
+    }
+
     #[tokio::test]
     async fn force_enqueue_resets_existing_job() {
         let pool = fresh_pool().await;
@@ -340,8 +983,69 @@ mod tests {
 
         force_enqueue(&pool, "show", show_id).await.unwrap();
 
-        let job = next_due(&pool).await.unwrap().expect("a job");
+        let job = claim_next(&pool, 600).await.unwrap().expect("a job");
         assert_eq!(job.attempts, 0);
         assert!(job.last_error.is_none());
     }
+
+    #[tokio::test]
+    async fn unlink_clears_what_the_match_wrote() {
+        let pool = fresh_pool().await;
+        let show_id = seed_show(&pool).await;
+        sqlx::query(
+            "UPDATE shows SET provider = 'tmdb', provider_id = '1399', metadata_locked = 1
+             WHERE id = ?1",
+        )
+        .bind(show_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let rating = certifications::Certification {
+            country: "US".into(),
+            certification: "TV-MA".into(),
+        };
+        certifications::store_in_tx(&mut conn, "show", show_id, &[rating]).await.unwrap();
+        let actor = people::CreditInput {
+            person_provider_id: "17419".into(),
+            name: "Peter Dinklage".into(),
+            photo_url: None,
+            role: "cast",
+            job: None,
+            character: Some("Tyrion".into()),
+            order: 0,
+        };
+        people::store_in_tx(&mut conn, "show", show_id, "tmdb", &[actor]).await.unwrap();
+        genres::store_in_tx(&mut conn, "show", show_id, "tmdb", &["Drama".to_string()])
+            .await
+            .unwrap();
+
+        unlink_in_tx(&mut conn, "show", show_id).await.unwrap();
+        drop(conn);
+
+        let (provider, locked): (Option<String>, i64) =
+            sqlx::query_as("SELECT provider, metadata_locked FROM shows WHERE id = ?1")
+                .bind(show_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((provider, locked), (None, 0));
+        let left: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM certifications WHERE kind = 'show' AND media_id = ?1",
+        )
+        .bind(show_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(left, 0);
+        assert!(people::for_title(&pool, "show", show_id).await.unwrap().is_empty());
+        let genre_rows: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM show_genres WHERE show_id = ?1")
+                .bind(show_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(genre_rows, 0);
+        assert!(claim_next(&pool, 600).await.unwrap().is_some());
+    }
 }
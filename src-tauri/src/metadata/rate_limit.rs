@@ -0,0 +1,315 @@
+//! Per-provider token buckets for outgoing provider requests. Replaces
+//! the worker's fixed 250ms pacing: each network call takes a token from
+//! its provider's bucket, and a `429` (or TMDB's `X-RateLimit-*` headers)
+//! blocks the bucket until the provider says it's fine to continue.
+//! Nobody waits out such a block: `acquire` fails with the time left,
+//! so the worker defers the job and interactive commands fail fast.
+//!
+//! Cache hits in `metadata::http` never take a token.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::metadata::dispatch::Provider;
+use crate::models::RateLimitStatus;
+
+/// Used when a `429` arrives without a usable `Retry-After`.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+/// Upper bound on a provider-requested pause, so a bogus header can't
+/// park the worker's jobs for hours.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(15 * 60);
+
+pub struct RateLimiters {
+    tmdb: Mutex<Bucket>,
+    imdb: Mutex<Bucket>,
+}
+
+impl Default for RateLimiters {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            // TMDB documents roughly 50 req/s; stay well under it.
+            tmdb: Mutex::new(Bucket::new(20.0, 10.0, now)),
+            // IMDB's endpoints are undocumented; be gentle.
+            imdb: Mutex::new(Bucket::new(4.0, 2.0, now)),
+        }
+    }
+}
+
+impl RateLimiters {
+    fn bucket(&self, provider: Provider) -> &Mutex<Bucket> {
+        match provider {
+            Provider::Tmdb => &self.tmdb,
+            Provider::Imdb => &self.imdb,
+        }
+    }
+
+    /// Waits until `provider`'s bucket has a token, then takes it. While
+    /// the provider has asked us to back off, fails right away with the
+    /// time left instead of sleeping through it.
+    pub async fn acquire(&self, provider: Provider) -> Result<(), Duration> {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket(provider).lock().unwrap();
+                let now = Instant::now();
+                if let Some(remaining) = bucket.blocked_remaining(now) {
+                    return Err(remaining);
+                }
+                match bucket.try_take(now) {
+                    Ok(()) => return Ok(()),
+                    Err(wait) => wait,
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Feeds a response's status and headers back into the bucket. Returns
+    /// `true` when the response was a throttle.
+    pub fn observe(
+        &self,
+        provider: Provider,
+        status: reqwest::StatusCode,
+        headers: &HeaderMap,
+    ) -> bool {
+        let now = Instant::now();
+        let mut bucket = self.bucket(provider).lock().unwrap();
+
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let wait = parse_retry_after(headers).unwrap_or(DEFAULT_RETRY_AFTER);
+            bucket.block_for(wait.min(MAX_RETRY_AFTER), now);
+            return true;
+        }
+
+        if let Some(wait) = parse_tmdb_reset(headers, SystemTime::now()) {
+            bucket.block_for(wait.min(MAX_RETRY_AFTER), now);
+        }
+
+        false
+    }
+
+    /// How long `provider` is still blocked by a provider-requested pause.
+    pub fn blocked_for(&self, provider: Provider) -> Option<Duration> {
+        let bucket = self.bucket(provider).lock().unwrap();
+        bucket.blocked_remaining(Instant::now())
+    }
+
+    pub fn status(&self) -> Vec<RateLimitStatus> {
+        [Provider::Tmdb, Provider::Imdb]
+            .into_iter()
+            .map(|provider| {
+                let mut bucket = self.bucket(provider).lock().unwrap();
+                let now = Instant::now();
+                bucket.refill(now);
+                let blocked_until = bucket
+                    .blocked_remaining(now)
+                    .map(|remaining| unix_secs(SystemTime::now() + remaining));
+
+                RateLimitStatus {
+                    provider,
+                    capacity: bucket.capacity,
+                    available: bucket.tokens,
+                    refill_per_second: bucket.refill_per_second,
+                    blocked_until,
+                    throttle_count: bucket.throttle_count,
+                }
+            })
+            .collect()
+    }
+}
+
+struct Bucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+    throttle_count: u64,
+}
+
+impl Bucket {
+    fn new(capacity: f64, refill_per_second: f64, now: Instant) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+            tokens: capacity,
+            last_refill: now,
+            blocked_until: None,
+            throttle_count: 0,
+        }
+    }
+
+    fn blocked_remaining(&self, now: Instant) -> Option<Duration> {
+        self.blocked_until
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// `Ok` when a token was taken, otherwise how long to wait before
+    /// trying again.
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(until) = self.blocked_until {
+            if until > now {
+                return Err(until - now);
+            }
+            self.blocked_until = None;
+        }
+
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let missing = 1.0 - self.tokens;
+        Err(Duration::from_secs_f64(missing / self.refill_per_second))
+    }
+
+    /// Blocks the bucket for `wait` and empties it, so requests resume at
+    /// the refill rate rather than as a burst.
+    fn block_for(&mut self, wait: Duration, now: Instant) {
+        let until = now + wait;
+        if self.blocked_until.is_none_or(|current| current < until) {
+            self.blocked_until = Some(until);
+        }
+        self.tokens = 0.0;
+        self.last_refill = until;
+        self.throttle_count += 1;
+    }
+}
+
+/// `Retry-After` as delta-seconds. HTTP-date values aren't sent by TMDB
+/// or IMDB and fall back to the default.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// TMDB's legacy `X-RateLimit-Remaining: 0` + `X-RateLimit-Reset: <unix>`
+/// pair. Returns the time left until the reset when the window is spent.
+fn parse_tmdb_reset(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let remaining: u64 = headers
+        .get("x-ratelimit-remaining")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    if remaining > 0 {
+        return None;
+    }
+
+    let reset: u64 = headers
+        .get("x-ratelimit-reset")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let reset_at = UNIX_EPOCH + Duration::from_secs(reset);
+
+    Some(reset_at.duration_since(now).unwrap_or(Duration::from_secs(1)))
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_burst_then_paces() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(2.0, 4.0, start);
+
+        assert!(bucket.try_take(start).is_ok());
+        assert!(bucket.try_take(start).is_ok());
+        let wait = bucket.try_take(start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(250));
+
+        assert!(bucket.try_take(start + Duration::from_millis(250)).is_ok());
+    }
+
+    #[test]
+    fn block_for_holds_the_bucket_until_the_deadline() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(5.0, 5.0, start);
+
+        bucket.block_for(Duration::from_secs(3), start);
+        assert_eq!(bucket.throttle_count, 1);
+        assert_eq!(
+            bucket.try_take(start + Duration::from_secs(1)).unwrap_err(),
+            Duration::from_secs(2)
+        );
+
+        // Resumes empty: the first token arrives one refill interval after the block.
+        let after = start + Duration::from_secs(3);
+        assert!(bucket.try_take(after).is_err());
+        assert!(bucket.try_take(after + Duration::from_millis(200)).is_ok());
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn tmdb_reset_only_applies_when_window_is_spent() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "3".parse().unwrap());
+        headers.insert("x-ratelimit-reset", "1005".parse().unwrap());
+        assert_eq!(parse_tmdb_reset(&headers, now), None);
+
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        assert_eq!(parse_tmdb_reset(&headers, now), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn observe_reports_throttles() {
+        let limiters = RateLimiters::default();
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "30".parse().unwrap());
+
+        assert!(!limiters.observe(Provider::Imdb, reqwest::StatusCode::OK, &HeaderMap::new()));
+        assert!(limiters.observe(Provider::Imdb, reqwest::StatusCode::TOO_MANY_REQUESTS, &headers));
+        assert!(limiters.blocked_for(Provider::Imdb).is_some());
+        assert!(limiters.blocked_for(Provider::Tmdb).is_none());
+    }
+
+    #[tokio::test]
+    async fn acquire_fails_fast_while_blocked() {
+        let limiters = RateLimiters::default();
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "600".parse().unwrap());
+        limiters.observe(Provider::Tmdb, reqwest::StatusCode::TOO_MANY_REQUESTS, &headers);
+
+        let remaining = limiters.acquire(Provider::Tmdb).await.unwrap_err();
+        assert!(remaining > Duration::from_secs(590));
+        assert_eq!(limiters.acquire(Provider::Imdb).await, Ok(()));
+    }
+}
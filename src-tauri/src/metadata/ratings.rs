@@ -0,0 +1,248 @@
+//! Ratings per source. Every applied payload writes the linked
+//! provider's rating, and the worker then fetches the other provider's
+//! by IMDB id when `metadata_mode` uses it, so TMDB and IMDB ratings sit
+//! side by side instead of replacing each other in `rating`. Lists can
+//! show and sort by one source through `value_expr`.
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::http::ProviderHttp;
+use crate::metadata::imdb::{self, RatingsNode};
+
+/// A rating as parsed from a provider payload.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatingInput {
+    pub value: f64,
+    pub scale: f64,
+    pub votes: Option<i64>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct Rating {
+    pub source: String,
+    pub value: f64,
+    pub scale: f64,
+    pub votes: Option<i64>,
+    pub fetched_at: i64,
+}
+
+/// TMDB reports unrated titles as 0.0 with no votes; those get no row.
+pub fn from_tmdb(vote_average: Option<f64>, vote_count: Option<i64>) -> Option<RatingInput> {
+    let value = vote_average?;
+    if vote_count == Some(0) {
+        return None;
+    }
+    Some(RatingInput {
+        value,
+        scale: 10.0,
+        votes: vote_count,
+    })
+}
+
+/// IMDB likewise sends `voteCount: 0` for unreleased titles.
+pub fn from_imdb(summary: Option<&RatingsNode>) -> Option<RatingInput> {
+    let summary = summary?;
+    let votes = summary.vote_count.unwrap_or(0);
+    if votes == 0 {
+        return None;
+    }
+    Some(RatingInput {
+        value: summary.aggregate_rating?,
+        scale: 10.0,
+        votes: Some(votes),
+    })
+}
+
+/// Writes `source`'s rating for the title, or removes it when the
+/// provider no longer has one.
+pub async fn store_in_tx(
+    conn: &mut SqliteConnection,
+    kind: &str,
+    media_id: i64,
+    source: &str,
+    rating: Option<RatingInput>,
+) -> AppResult<()> {
+    let Some(rating) = rating else {
+        sqlx::query("DELETE FROM ratings WHERE kind = ?1 AND media_id = ?2 AND source = ?3")
+            .bind(kind)
+            .bind(media_id)
+            .bind(source)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(());
+    };
+
+    sqlx::query(
+        "INSERT INTO ratings (kind, media_id, source, value, scale, votes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(kind, media_id, source) DO UPDATE SET
+             value = excluded.value,
+             scale = excluded.scale,
+             votes = excluded.votes,
+             fetched_at = strftime('%s','now')",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .bind(source)
+    .bind(rating.value)
+    .bind(rating.scale)
+    .bind(rating.votes)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn store(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    source: &str,
+    rating: Option<RatingInput>,
+) -> AppResult<()> {
+    let mut conn = pool.acquire().await?;
+    store_in_tx(&mut conn, kind, media_id, source, rating).await
+}
+
+/// Every source's rating for a title, by source.
+pub async fn for_title(pool: &SqlitePool, kind: &str, media_id: i64) -> AppResult<Vec<Rating>> {
+    let rows = sqlx::query_as(
+        "SELECT source, value, scale, votes, fetched_at FROM ratings
+         WHERE kind = ?1 AND media_id = ?2
+         ORDER BY source",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Sources with at least one rating, for the list sort picker.
+pub async fn sources(pool: &SqlitePool) -> AppResult<Vec<String>> {
+    let rows = sqlx::query_scalar("SELECT DISTINCT source FROM ratings ORDER BY source")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows)
+}
+
+/// SQL expression for the title `{alias}.id`'s rating from the source
+/// bound at `?{param}`, on a 0–10 scale like `rating`.
+pub fn value_expr(kind: &str, alias: &str, param: usize) -> String {
+    format!(
+        "(SELECT r.value * 10.0 / r.scale FROM ratings r
+           WHERE r.kind = '{kind}' AND r.media_id = {alias}.id AND r.source = ?{param})"
+    )
+}
+
+/// The IMDB rating of the title with IMDB id `imdb_id`.
+pub async fn fetch_imdb(
+    http: &ProviderHttp,
+    kind: &str,
+    imdb_id: &str,
+    language: &str,
+) -> AppResult<Option<RatingInput>> {
+    let details = match kind {
+        "movie" => imdb::fetch_movie_details(http, imdb_id, language).await?,
+        "show" => imdb::fetch_show_details(http, imdb_id, language).await?,
+        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+    };
+    Ok(from_imdb(details.ratings_summary.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    #[test]
+    fn unrated_titles_have_no_rating() {
+        assert_eq!(from_tmdb(Some(0.0), Some(0)), None);
+        assert_eq!(from_tmdb(None, None), None);
+        assert_eq!(
+            from_tmdb(Some(8.2), Some(25000)),
+            Some(RatingInput { value: 8.2, scale: 10.0, votes: Some(25000) })
+        );
+        let unreleased = RatingsNode { aggregate_rating: None, vote_count: Some(0) };
+        assert_eq!(from_imdb(Some(&unreleased)), None);
+    }
+
+    #[tokio::test]
+    async fn sources_are_kept_side_by_side() {
+        let pool = fresh_pool().await;
+        let tmdb = RatingInput { value: 8.2, scale: 10.0, votes: Some(25000) };
+        let imdb = RatingInput { value: 8.7, scale: 10.0, votes: Some(2_000_000) };
+        store(&pool, "movie", 1, "tmdb", Some(tmdb)).await.unwrap();
+        store(&pool, "movie", 1, "imdb", Some(imdb)).await.unwrap();
+        store(&pool, "movie", 1, "tmdb", Some(RatingInput { value: 8.3, ..tmdb }))
+            .await
+            .unwrap();
+
+        let stored = for_title(&pool, "movie", 1).await.unwrap();
+        let values: Vec<(&str, f64)> =
+            stored.iter().map(|rating| (rating.source.as_str(), rating.value)).collect();
+        assert_eq!(values, vec![("imdb", 8.7), ("tmdb", 8.3)]);
+
+        store(&pool, "movie", 1, "imdb", None).await.unwrap();
+        assert_eq!(for_title(&pool, "movie", 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn lists_show_and_sort_by_the_chosen_source() {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path, rating) VALUES
+                 (1, 1, 'Alpha', '/movies/1.mkv', 9.0),
+                 (2, 1, 'Beta', '/movies/2.mkv', 6.0),
+                 (3, 1, 'Gamma', '/movies/3.mkv', 7.0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let imdb = |value| Some(RatingInput { value, scale: 10.0, votes: Some(10) });
+        store(&pool, "movie", 1, "imdb", imdb(5.5)).await.unwrap();
+        store(&pool, "movie", 2, "imdb", imdb(8.0)).await.unwrap();
+        // Letterboxd-style five-star scale, shown out of ten.
+        let five = RatingInput { value: 4.0, scale: 5.0, votes: None };
+        store(&pool, "movie", 3, "stars", Some(five)).await.unwrap();
+
+        let list = |source: Option<&str>| {
+            let view = crate::queries::RatingView {
+                source: source.map(str::to_string),
+                sort_by_rating: true,
+            };
+            let pool = pool.clone();
+            async move {
+                crate::queries::list_movies(&pool, &[], &view)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|movie| (movie.id, movie.rating))
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(list(None).await, vec![(1, Some(9.0)), (3, Some(7.0)), (2, Some(6.0))]);
+        assert_eq!(list(Some("imdb")).await, vec![(2, Some(8.0)), (1, Some(5.5)), (3, None)]);
+        assert_eq!(list(Some("stars")).await[0], (3, Some(8.0)));
+        assert_eq!(sources(&pool).await.unwrap(), vec!["imdb", "stars"]);
+    }
+}
@@ -0,0 +1,97 @@
+//! Stale-metadata refresh scheduler. Runs next to the worker: once an
+//! hour it re-enqueues linked rows whose `metadata_synced_at` is older
+//! than the configured age, at `PRIORITY_REFRESH` so they drain behind
+//! scan jobs. Continuing shows use their own (shorter) age so new
+//! seasons and ratings show up without a manual refresh.
+//!
+//! Controlled through `app_settings`:
+//!   * `metadata_refresh_days` — movies and ended shows (default 30).
+//!   * `metadata_refresh_continuing_days` — continuing shows (default 7).
+//!
+//! `0` disables refresh for that class.
+//!
+//! The same tick prunes `metadata_job_log` rows older than
+//! `metadata_job_log_days` (default 30), and `http_cache` entries that
+//! expired more than `CACHE_GRACE_SECS` ago.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::sync::Notify;
+use tokio::time::sleep;
+
+use crate::error::AppResult;
+use crate::metadata::{http, job_log, queries};
+use crate::queries as app_queries;
+
+/// Delay before the first pass, so the startup scan gets the queue first.
+const STARTUP_DELAY_SECS: u64 = 60;
+
+const TICK_SECS: u64 = 60 * 60;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// How long an expired cache entry is kept for offline fallback and
+/// `304` revalidation before the tick deletes it.
+const CACHE_GRACE_SECS: i64 = 30 * SECONDS_PER_DAY;
+
+pub fn spawn(pool: SqlitePool, notify: Arc<Notify>) {
+    tauri::async_runtime::spawn(async move {
+        sleep(Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        loop {
+            match enqueue_stale(&pool).await {
+                Ok(0) => {}
+                Ok(_) => notify.notify_one(),
+                Err(error) => eprintln!("metadata refresh pass failed: {error}"),
+            }
+            if let Err(error) = prune_job_log(&pool).await {
+                eprintln!("metadata job log prune failed: {error}");
+            }
+            if let Err(error) = http::prune_cache(&pool, CACHE_GRACE_SECS).await {
+                eprintln!("http cache prune failed: {error}");
+            }
+            sleep(Duration::from_secs(TICK_SECS)).await;
+        }
+    });
+}
+
+/// One scheduler pass. Reads the refresh settings and enqueues whatever
+/// is due. Returns the number of jobs added. Skipped entirely while
+/// `metadata_mode` is `off`.
+pub async fn enqueue_stale(pool: &SqlitePool) -> AppResult<u64> {
+    let mode = app_queries::get_app_setting(pool, "metadata_mode").await?;
+    if mode.as_deref() == Some("off") {
+        return Ok(0);
+    }
+
+    let ended_days = read_days(pool, "metadata_refresh_days").await?;
+    let continuing_days = read_days(pool, "metadata_refresh_continuing_days").await?;
+
+    queries::enqueue_stale(
+        pool,
+        ended_days.map(|days| days * SECONDS_PER_DAY),
+        continuing_days.map(|days| days * SECONDS_PER_DAY),
+    )
+    .await
+}
+
+/// Drops job-log rows past the retention window. Returns the number
+/// removed.
+pub async fn prune_job_log(pool: &SqlitePool) -> AppResult<u64> {
+    let days = read_days(pool, "metadata_job_log_days").await?.unwrap_or(1);
+    job_log::prune(pool, days * SECONDS_PER_DAY).await
+}
+
+/// `None` when the setting is `0` (disabled). Missing or unparsable
+/// values fall back to `default_for`.
+async fn read_days(pool: &SqlitePool, key: &str) -> AppResult<Option<i64>> {
+    let stored = app_queries::get_app_setting(pool, key).await?;
+    let days = stored
+        .as_deref()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .or_else(|| app_queries::default_for(key).and_then(|value| value.parse().ok()))
+        .unwrap_or(0);
+
+    Ok((days > 0).then_some(days))
+}
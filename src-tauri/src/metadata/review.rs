@@ -0,0 +1,155 @@
+//! Stored review candidates. When the worker searches a provider but
+//! `pick_confident_match` declines, the best few results are kept in
+//! `review_candidates` so Needs-review can offer them inline.
+
+use std::collections::HashMap;
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::error::AppResult;
+use crate::metadata::dispatch::Provider;
+use crate::metadata::matching::MatchCandidate;
+
+/// How many candidates are kept per provider.
+pub const MAX_CANDIDATES_PER_PROVIDER: usize = 5;
+
+const OVERVIEW_SNIPPET_CHARS: usize = 240;
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct ReviewCandidate {
+    pub provider: String,
+    pub provider_id: String,
+    pub title: String,
+    pub year: Option<i32>,
+    pub poster_url: Option<String>,
+    pub overview: Option<String>,
+    pub score: f64,
+}
+
+impl ReviewCandidate {
+    pub fn from_ranked((candidate, score): (MatchCandidate, f64)) -> Self {
+        Self {
+            provider: candidate.provider.as_str().to_string(),
+            provider_id: candidate.provider_id,
+            title: candidate.title,
+            year: candidate.year,
+            poster_url: candidate.poster_url,
+            overview: candidate.overview.map(|text| snippet(&text)),
+            score,
+        }
+    }
+}
+
+/// Replaces `provider`'s stored candidates for one title. An empty slice
+/// just clears them (the provider found nothing this time).
+pub async fn replace_for_provider(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    provider: Provider,
+    candidates: &[ReviewCandidate],
+) -> AppResult<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM review_candidates WHERE kind = ?1 AND media_id = ?2 AND provider = ?3",
+    )
+    .bind(kind)
+    .bind(media_id)
+    .bind(provider.as_str())
+    .execute(&mut *tx)
+    .await?;
+
+    for (rank, candidate) in candidates.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO review_candidates
+                 (kind, media_id, provider, provider_id, rank, title, year,
+                  poster_url, overview, score)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(kind, media_id, provider, provider_id) DO NOTHING",
+        )
+        .bind(kind)
+        .bind(media_id)
+        .bind(provider.as_str())
+        .bind(&candidate.provider_id)
+        .bind(rank as i64)
+        .bind(&candidate.title)
+        .bind(candidate.year)
+        .bind(candidate.poster_url.as_deref())
+        .bind(candidate.overview.as_deref())
+        .bind(candidate.score)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Drops every stored candidate for one title. Called once it's linked.
+pub async fn clear(conn: &mut SqliteConnection, kind: &str, media_id: i64) -> AppResult<()> {
+    sqlx::query("DELETE FROM review_candidates WHERE kind = ?1 AND media_id = ?2")
+        .bind(kind)
+        .bind(media_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Every stored candidate, keyed by `(kind, media_id)`, best first across
+/// providers.
+pub async fn load_all(
+    pool: &SqlitePool,
+) -> AppResult<HashMap<(String, i64), Vec<ReviewCandidate>>> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        kind: String,
+        media_id: i64,
+        #[sqlx(flatten)]
+        candidate: ReviewCandidate,
+    }
+
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT kind, media_id, provider, provider_id, title, year, poster_url,
+                overview, score
+         FROM review_candidates
+         ORDER BY kind, media_id, score DESC, rank ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut grouped: HashMap<(String, i64), Vec<ReviewCandidate>> = HashMap::new();
+    for row in rows {
+        grouped
+            .entry((row.kind, row.media_id))
+            .or_default()
+            .push(row.candidate);
+    }
+    Ok(grouped)
+}
+
+/// First `OVERVIEW_SNIPPET_CHARS` characters, cut at a word boundary.
+fn snippet(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= OVERVIEW_SNIPPET_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(OVERVIEW_SNIPPET_CHARS).collect();
+    let trimmed = cut.rsplit_once(' ').map(|(head, _)| head).unwrap_or(&cut);
+    format!("{}…", trimmed.trim_end_matches([',', '.', ';', ':']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippet_cuts_long_text_at_a_word() {
+        let long = "word ".repeat(100);
+        let cut = snippet(&long);
+        assert!(cut.ends_with('…'));
+        assert!(cut.chars().count() <= OVERVIEW_SNIPPET_CHARS + 1);
+        assert_eq!(snippet("Short."), "Short.");
+    }
+}
@@ -1,17 +1,26 @@
 //! TMDB v3 client. Stays narrow: only the calls the worker needs.
 
 use std::path::Path;
+use std::time::Duration;
 
-use reqwest::{Client, StatusCode};
+use reqwest::StatusCode;
 use serde::Deserialize;
 use tokio::io::AsyncWriteExt;
 
 use crate::error::{AppError, AppResult};
 use crate::metadata::dispatch::Provider;
+use crate::metadata::http::{Fetched, ProviderHttp};
+use crate::metadata::locale::{self, LocalizedText};
 use crate::metadata::matching::MatchCandidate;
 
-const API_BASE: &str = "https://api.themoviedb.org/3";
-const IMAGE_BASE: &str = "https://image.tmdb.org/t/p/w500";
+/// Production defaults; the live values come from `ProviderHttp::endpoints`.
+pub const API_BASE: &str = "https://api.themoviedb.org/3";
+pub const IMAGE_BASE: &str = "https://image.tmdb.org/t/p/w500";
+
+const SEARCH_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DETAILS_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Air dates move; season listings are cached for a day only.
+const SEASON_TTL: Duration = Duration::from_secs(24 * 60 * 60);
 
 #[derive(Debug, Deserialize)]
 struct SearchEnvelope<T> {
@@ -23,6 +32,8 @@ pub struct TmdbMovieResult {
     pub id: i64,
     pub title: String,
     pub release_date: Option<String>,
+    pub poster_path: Option<String>,
+    pub overview: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -30,18 +41,35 @@ pub struct TmdbShowResult {
     pub id: i64,
     pub name: String,
     pub first_air_date: Option<String>,
+    pub poster_path: Option<String>,
+    pub overview: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TmdbCredits {
     pub cast: Vec<TmdbCastMember>,
+    #[serde(default)]
+    pub crew: Vec<TmdbCrewMember>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct TmdbCastMember {
+    pub id: i64,
     pub name: String,
     pub character: Option<String>,
     pub order: i64,
+    pub profile_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TmdbCrewMember {
+    pub id: i64,
+    pub name: String,
+    /// "Directing", "Writing", "Sound", …
+    pub department: String,
+    /// "Director", "Screenplay", "Novel", …
+    pub job: String,
+    pub profile_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,50 +77,218 @@ pub struct TmdbGenre {
     pub name: String,
 }
 
+/// `belongs_to_collection` on movie details.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TmdbCollectionRef {
+    pub id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TmdbCollection {
+    pub id: i64,
+    pub name: String,
+    pub overview: Option<String>,
+    pub poster_path: Option<String>,
+    #[serde(default)]
+    pub parts: Vec<TmdbCollectionPart>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TmdbCollectionPart {
+    pub id: i64,
+    pub title: String,
+    pub overview: Option<String>,
+    pub release_date: Option<String>,
+    pub poster_path: Option<String>,
+}
+
+/// `append_to_response=release_dates` on movie details.
+#[derive(Debug, Deserialize)]
+pub struct TmdbReleaseDates {
+    #[serde(default)]
+    pub results: Vec<TmdbCountryReleases>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TmdbCountryReleases {
+    pub iso_3166_1: String,
+    #[serde(default)]
+    pub release_dates: Vec<TmdbRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TmdbRelease {
+    #[serde(default)]
+    pub certification: String,
+    /// 1 premiere, 2 limited theatrical, 3 theatrical, 4 digital, …
+    #[serde(rename = "type")]
+    pub release_type: Option<i64>,
+}
+
+/// `append_to_response=content_ratings` on TV details.
+#[derive(Debug, Deserialize)]
+pub struct TmdbContentRatings {
+    #[serde(default)]
+    pub results: Vec<TmdbContentRating>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TmdbContentRating {
+    pub iso_3166_1: String,
+    #[serde(default)]
+    pub rating: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TmdbMovieDetails {
     pub id: i64,
-    #[allow(dead_code)]
     pub title: String,
     pub overview: Option<String>,
     pub release_date: Option<String>,
     pub vote_average: Option<f64>,
+    #[serde(default)]
+    pub vote_count: Option<i64>,
+    #[serde(default)]
+    pub imdb_id: Option<String>,
     pub runtime: Option<i64>,
     pub poster_path: Option<String>,
+    /// Wide artwork for the detail page header.
+    #[serde(default)]
+    pub backdrop_path: Option<String>,
+    #[serde(default)]
+    pub belongs_to_collection: Option<TmdbCollectionRef>,
     pub genres: Vec<TmdbGenre>,
     pub credits: Option<TmdbCredits>,
+    #[serde(default)]
+    pub release_dates: Option<TmdbReleaseDates>,
+    /// `append_to_response=external_ids`.
+    #[serde(default)]
+    pub external_ids: Option<TmdbExternalIds>,
+    /// Title/overview per fetched language; filled by `fetch_movie_details`.
+    #[serde(skip)]
+    pub localized: Vec<LocalizedText>,
+}
+
+/// Other databases' ids for a title; unknown ones are null or "".
+#[derive(Debug, Deserialize, Default)]
+pub struct TmdbExternalIds {
+    #[serde(default)]
+    pub imdb_id: Option<String>,
+    /// TV only.
+    #[serde(default)]
+    pub tvdb_id: Option<i64>,
+    #[serde(default)]
+    pub wikidata_id: Option<String>,
+}
+
+/// A title found by its IMDB id (`/find`).
+#[derive(Debug, Deserialize, Clone)]
+pub struct TmdbFindResult {
+    pub id: i64,
+    #[serde(default)]
+    pub vote_average: Option<f64>,
+    #[serde(default)]
+    pub vote_count: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FindEnvelope {
+    #[serde(default)]
+    movie_results: Vec<TmdbFindResult>,
+    #[serde(default)]
+    tv_results: Vec<TmdbFindResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TmdbNetwork {
+    pub name: String,
+}
+
+/// `next_episode_to_air` / `last_episode_to_air` on TV details, and the
+/// episodes of a season.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TmdbEpisodeAir {
+    pub season_number: i64,
+    pub episode_number: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub air_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TmdbSeason {
+    #[serde(default)]
+    episodes: Vec<TmdbEpisodeAir>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TmdbShowDetails {
     pub id: i64,
-    #[allow(dead_code)]
     pub name: String,
     pub overview: Option<String>,
     pub first_air_date: Option<String>,
+    /// "Returning Series", "Ended", "Canceled", "In Production", …
+    pub status: Option<String>,
+    #[serde(default)]
+    pub last_air_date: Option<String>,
+    #[serde(default)]
+    pub vote_count: Option<i64>,
+    /// `append_to_response=external_ids`.
+    #[serde(default)]
+    pub external_ids: Option<TmdbExternalIds>,
+    #[serde(default)]
+    pub networks: Vec<TmdbNetwork>,
+    #[serde(default)]
+    pub next_episode_to_air: Option<TmdbEpisodeAir>,
+    #[serde(default)]
+    pub last_episode_to_air: Option<TmdbEpisodeAir>,
     pub vote_average: Option<f64>,
     pub poster_path: Option<String>,
+    /// Wide artwork for the detail page header.
+    #[serde(default)]
+    pub backdrop_path: Option<String>,
     pub genres: Vec<TmdbGenre>,
     pub credits: Option<TmdbCredits>,
+    #[serde(default)]
+    pub content_ratings: Option<TmdbContentRatings>,
+    /// Title/overview per fetched language; filled by `fetch_show_details`.
+    #[serde(skip)]
+    pub localized: Vec<LocalizedText>,
+    /// Episodes of the season on air; filled by `fetch_show_details`.
+    #[serde(skip)]
+    pub season_episodes: Vec<TmdbEpisodeAir>,
+}
+
+impl TmdbShowDetails {
+    pub fn imdb_id(&self) -> Option<&str> {
+        self.external_ids.as_ref()?.imdb_id.as_deref().filter(|id| !id.is_empty())
+    }
+
+    /// False once TMDB considers the show finished.
+    pub fn is_airing(&self) -> bool {
+        !matches!(self.status.as_deref(), Some("Ended") | Some("Canceled"))
+    }
 }
 
 pub async fn search_movie(
-    client: &Client,
+    http: &ProviderHttp,
     api_key: &str,
     title: &str,
     year: Option<i32>,
+    language: &str,
 ) -> AppResult<Vec<MatchCandidate>> {
-    let mut request = client
-        .get(format!("{API_BASE}/search/movie"))
-        .query(&[("api_key", api_key), ("query", title)]);
+    let mut request = http
+        .client()
+        .get(format!("{}/search/movie", http.endpoints().tmdb_api))
+        .query(&[("api_key", api_key), ("query", title), ("language", language)]);
     if let Some(year_value) = year {
         let year_string = year_value.to_string();
         request = request.query(&[("year", year_string.as_str())]);
     }
 
-    let response = request.send().await.map_err(http_err)?;
-    let envelope: SearchEnvelope<TmdbMovieResult> =
-        parse_response(response, "search/movie").await?;
+    let response = send(http, request, language, SEARCH_TTL).await?;
+    let envelope: SearchEnvelope<TmdbMovieResult> = parse_response(response, "search/movie")?;
 
     Ok(envelope
         .results
@@ -102,27 +298,30 @@ pub async fn search_movie(
             provider_id: raw.id.to_string(),
             title: raw.title,
             year: parse_year(raw.release_date.as_deref()),
+            poster_url: poster_url(http, raw.poster_path.as_deref()),
+            overview: raw.overview.filter(|text| !text.is_empty()),
         })
         .collect())
 }
 
 pub async fn search_show(
-    client: &Client,
+    http: &ProviderHttp,
     api_key: &str,
     title: &str,
     year: Option<i32>,
+    language: &str,
 ) -> AppResult<Vec<MatchCandidate>> {
-    let mut request = client
-        .get(format!("{API_BASE}/search/tv"))
-        .query(&[("api_key", api_key), ("query", title)]);
+    let mut request = http
+        .client()
+        .get(format!("{}/search/tv", http.endpoints().tmdb_api))
+        .query(&[("api_key", api_key), ("query", title), ("language", language)]);
     if let Some(year_value) = year {
         let year_string = year_value.to_string();
         request = request.query(&[("first_air_date_year", year_string.as_str())]);
     }
 
-    let response = request.send().await.map_err(http_err)?;
-    let envelope: SearchEnvelope<TmdbShowResult> =
-        parse_response(response, "search/tv").await?;
+    let response = send(http, request, language, SEARCH_TTL).await?;
+    let envelope: SearchEnvelope<TmdbShowResult> = parse_response(response, "search/tv")?;
 
     Ok(envelope
         .results
@@ -132,49 +331,188 @@ pub async fn search_show(
             provider_id: raw.id.to_string(),
             title: raw.name,
             year: parse_year(raw.first_air_date.as_deref()),
+            poster_url: poster_url(http, raw.poster_path.as_deref()),
+            overview: raw.overview.filter(|text| !text.is_empty()),
         })
         .collect())
 }
 
+/// Details in `language`. When that has no overview, English is fetched
+/// too and fills the gap; both end up in `localized`.
 pub async fn fetch_movie_details(
-    client: &Client,
+    http: &ProviderHttp,
     api_key: &str,
     tmdb_id: &str,
+    language: &str,
 ) -> AppResult<TmdbMovieDetails> {
-    let response = client
-        .get(format!("{API_BASE}/movie/{tmdb_id}"))
-        .query(&[("api_key", api_key), ("append_to_response", "credits")])
-        .send()
-        .await
-        .map_err(http_err)?;
+    let mut details: TmdbMovieDetails =
+        fetch_details(http, api_key, "movie", tmdb_id, language).await?;
+    details.localized =
+        vec![LocalizedText::new(language, Some(&details.title), details.overview.as_deref())];
 
-    parse_response(response, "movie/details").await
+    if details.localized[0].overview.is_none() && !locale::is_fallback(language) {
+        let english: TmdbMovieDetails =
+            fetch_details(http, api_key, "movie", tmdb_id, locale::FALLBACK_LANGUAGE).await?;
+        let fallback = LocalizedText::new(
+            locale::FALLBACK_LANGUAGE,
+            Some(&english.title),
+            english.overview.as_deref(),
+        );
+        details.overview = fallback.overview.clone();
+        details.localized.push(fallback);
+    }
+
+    Ok(details)
 }
 
+/// Show counterpart of `fetch_movie_details`.
 pub async fn fetch_show_details(
-    client: &Client,
+    http: &ProviderHttp,
     api_key: &str,
     tmdb_id: &str,
+    language: &str,
 ) -> AppResult<TmdbShowDetails> {
-    let response = client
-        .get(format!("{API_BASE}/tv/{tmdb_id}"))
-        .query(&[("api_key", api_key), ("append_to_response", "credits")])
-        .send()
-        .await
-        .map_err(http_err)?;
+    let mut details: TmdbShowDetails = fetch_details(http, api_key, "tv", tmdb_id, language).await?;
+    details.localized =
+        vec![LocalizedText::new(language, Some(&details.name), details.overview.as_deref())];
+
+    if details.localized[0].overview.is_none() && !locale::is_fallback(language) {
+        let english: TmdbShowDetails =
+            fetch_details(http, api_key, "tv", tmdb_id, locale::FALLBACK_LANGUAGE).await?;
+        let fallback = LocalizedText::new(
+            locale::FALLBACK_LANGUAGE,
+            Some(&english.name),
+            english.overview.as_deref(),
+        );
+        details.overview = fallback.overview.clone();
+        details.localized.push(fallback);
+    }
+
+    // Best-effort: a show without its season listing still applies, the
+    // calendar just has less to show.
+    let airing_season = details
+        .next_episode_to_air
+        .as_ref()
+        .or(details.last_episode_to_air.as_ref())
+        .map(|episode| episode.season_number)
+        .filter(|_| details.is_airing());
+    if let Some(season) = airing_season {
+        match fetch_season(http, api_key, tmdb_id, season, language).await {
+            Ok(episodes) => details.season_episodes = episodes,
+            Err(error) => eprintln!("tmdb season {season} of {tmdb_id} failed: {error}"),
+        }
+    }
+
+    Ok(details)
+}
+
+async fn fetch_season(
+    http: &ProviderHttp,
+    api_key: &str,
+    tmdb_id: &str,
+    season: i64,
+    language: &str,
+) -> AppResult<Vec<TmdbEpisodeAir>> {
+    let request = http
+        .client()
+        .get(format!("{}/tv/{tmdb_id}/season/{season}", http.endpoints().tmdb_api))
+        .query(&[("api_key", api_key), ("language", language)]);
+    let response = send(http, request, language, SEASON_TTL).await?;
+    let season: TmdbSeason = parse_response(response, "tv/season")?;
+
+    Ok(season.episodes)
+}
 
-    parse_response(response, "tv/details").await
+async fn fetch_details<T: for<'de> Deserialize<'de>>(
+    http: &ProviderHttp,
+    api_key: &str,
+    media: &str,
+    tmdb_id: &str,
+    language: &str,
+) -> AppResult<T> {
+    let append = match media {
+        "movie" => "credits,release_dates,external_ids",
+        _ => "credits,content_ratings,external_ids",
+    };
+    let request = http
+        .client()
+        .get(format!("{}/{media}/{tmdb_id}", http.endpoints().tmdb_api))
+        .query(&[
+            ("api_key", api_key),
+            ("append_to_response", append),
+            ("language", language),
+        ]);
+    let response = send(http, request, language, DETAILS_TTL).await?;
+
+    parse_response(response, &format!("{media}/details"))
+}
+
+/// The TMDB movie (`kind` "movie") or show with IMDB id `imdb_id`.
+pub async fn find_by_imdb_id(
+    http: &ProviderHttp,
+    api_key: &str,
+    kind: &str,
+    imdb_id: &str,
+) -> AppResult<Option<TmdbFindResult>> {
+    let request = http
+        .client()
+        .get(format!("{}/find/{imdb_id}", http.endpoints().tmdb_api))
+        .query(&[("api_key", api_key), ("external_source", "imdb_id")]);
+    let response = send(http, request, locale::FALLBACK_LANGUAGE, DETAILS_TTL).await?;
+    let envelope: FindEnvelope = parse_response(response, "find")?;
+
+    let results = match kind {
+        "movie" => envelope.movie_results,
+        _ => envelope.tv_results,
+    };
+    Ok(results.into_iter().next())
+}
+
+/// A collection with all its parts, owned or not.
+pub async fn fetch_collection(
+    http: &ProviderHttp,
+    api_key: &str,
+    collection_id: i64,
+    language: &str,
+) -> AppResult<TmdbCollection> {
+    let request = http
+        .client()
+        .get(format!("{}/collection/{collection_id}", http.endpoints().tmdb_api))
+        .query(&[("api_key", api_key), ("language", language)]);
+    let response = send(http, request, language, DETAILS_TTL).await?;
+
+    parse_response(response, "collection")
 }
 
 /// Downloads `poster_path` (a relative TMDB path like `/abc.jpg`) into
 /// `dest`. Streams the response body to keep memory bounded.
 pub async fn download_poster(
-    client: &Client,
+    http: &ProviderHttp,
+    poster_path: &str,
+    dest: &Path,
+) -> AppResult<()> {
+    let url = format!("{}{poster_path}", http.endpoints().tmdb_image);
+    download(http, &url, dest).await
+}
+
+/// Like `download_poster`, at another TMDB image width (`w1280`, …) in
+/// place of the configured one.
+pub async fn download_poster_at(
+    http: &ProviderHttp,
     poster_path: &str,
+    size: &str,
     dest: &Path,
 ) -> AppResult<()> {
-    let url = format!("{IMAGE_BASE}{poster_path}");
-    let mut response = client.get(&url).send().await.map_err(http_err)?;
+    let base = &http.endpoints().tmdb_image;
+    let base = match base.rsplit_once('/') {
+        Some((head, _)) => format!("{head}/{size}"),
+        None => base.clone(),
+    };
+    download(http, &format!("{base}{poster_path}"), dest).await
+}
+
+async fn download(http: &ProviderHttp, url: &str, dest: &Path) -> AppResult<()> {
+    let mut response = http.client().get(url).send().await.map_err(http_err)?;
 
     if !response.status().is_success() {
         return Err(AppError::Other(format!(
@@ -197,30 +535,50 @@ pub async fn download_poster(
     Ok(())
 }
 
+pub fn poster_url(http: &ProviderHttp, poster_path: Option<&str>) -> Option<String> {
+    poster_path.map(|path| format!("{}{path}", http.endpoints().tmdb_image))
+}
+
 fn http_err(error: reqwest::Error) -> AppError {
     AppError::Other(format!("tmdb http: {error}"))
 }
 
-async fn parse_response<T: for<'de> Deserialize<'de>>(
-    response: reqwest::Response,
+async fn send(
+    http: &ProviderHttp,
+    request: reqwest::RequestBuilder,
+    language: &str,
+    ttl: Duration,
+) -> AppResult<Fetched> {
+    let request = request.build().map_err(http_err)?;
+    http.send_cached(request, Provider::Tmdb, language, ttl)
+        .await
+        .map_err(http_err)
+}
+
+fn parse_response<T: for<'de> Deserialize<'de>>(
+    response: Fetched,
     endpoint: &str,
 ) -> AppResult<T> {
-    let status = response.status();
+    let status = response.status;
     if status == StatusCode::UNAUTHORIZED {
         return Err(AppError::Other(format!(
             "auth_required: {endpoint} returned 401"
         )));
     }
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(AppError::Other(format!(
+            "rate_limited: tmdb {endpoint} returned 429{}",
+            response.retry_hint()
+        )));
+    }
     if !status.is_success() {
-        let body = response.text().await.unwrap_or_default();
+        let body = String::from_utf8_lossy(&response.body);
         return Err(AppError::Other(format!(
             "tmdb {endpoint} returned {status}: {body}"
         )));
     }
 
-    response
-        .json::<T>()
-        .await
+    serde_json::from_slice::<T>(&response.body)
         .map_err(|error| AppError::Other(format!("tmdb {endpoint} parse: {error}")))
 }
 
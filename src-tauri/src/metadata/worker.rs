@@ -1,32 +1,57 @@
-//! Background worker that drains `metadata_jobs`. One task, sequential,
-//! 250ms pacing between requests. Waits on a Notify when the queue is
-//! empty, when there's no API key, or when every job is parked on
-//! auth_required.
+//! Background worker that drains `metadata_jobs`. A supervisor loop
+//! claims due jobs (`queries::claim_next`) and runs up to
+//! `metadata_concurrency` of them at once; a claim guarantees no two
+//! tasks ever work the same `(kind, media_id)`, and a running task keeps
+//! its claim alive with a heartbeat. Request pacing lives in
+//! `metadata::rate_limit`: every provider call waits on that provider's
+//! token bucket, shared by all tasks. Waits on a Notify (or the next
+//! backoff deadline) when the queue is empty, when there's no API key, or
+//! when every job is parked on auth_required.
 //!
 //! Per job: read `metadata_mode`, ask `providers_for_mode` for the walk,
-//! call each provider with 250ms intra-walk pacing, then classify the
-//! end-of-walk outcome (matched / saw_tmdb_auth → park / has_error →
-//! backoff / no_error → delete).
+//! call each provider, then classify the end-of-walk outcome (matched /
+//! saw_tmdb_auth → park / rate-limited → defer / has_error → backoff /
+//! no_error → delete).
+//!
+//! `spawn` also starts the stale-metadata refresh scheduler
+//! (`metadata::refresh`), which feeds low-priority jobs into the same queue.
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use sqlx::SqlitePool;
-use tauri::{AppHandle, Manager};
 use tokio::sync::Notify;
+use tokio::task::JoinSet;
 use tokio::time::sleep;
 
 use crate::error::{AppError, AppResult};
 use crate::metadata::dispatch::{providers_for_mode, ParkReason, Provider};
-use crate::metadata::{apply, imdb, matching, queries, tmdb};
+use crate::metadata::http::ProviderHttp;
+use crate::metadata::{
+    aliases, apply, backdrops, collections, external_ids, imdb, job_log, locale, matching, people,
+    poster_cache, posters, queries, ratings, refresh, review, tmdb,
+};
 use crate::queries as app_queries;
 
-const PACING_MS: u64 = 250;
+/// Deferral used when a provider throttled us but the bucket has no
+/// pause recorded (e.g. a `429` with `Retry-After: 0`).
+const RATE_LIMIT_DEFER_SECS: i64 = 60;
+
+/// Upper bound for `metadata_concurrency`; matches `queries::validate`.
+const MAX_CONCURRENCY: usize = 16;
+
+/// How often a running job re-stamps its claim.
+const CLAIM_HEARTBEAT_SECS: u64 = 60;
 
-/// Mirrors the `attempts < 8` filter inside `queries::next_due`. Kept
-/// in worker code so the loop can short-circuit before doing any work
-/// for a job that should already be off the queue.
-const MAX_ATTEMPTS: i64 = 8;
+/// A claim not re-stamped for this long belongs to a task that died.
+/// Several missed heartbeats, so a slow database write can't make a live
+/// job look abandoned; no job length is assumed.
+const CLAIM_TIMEOUT_SECS: i64 = 5 * 60;
+
+/// Longest idle sleep, so jobs that become due without a notify (backoff
+/// expiry, deferred throttles) are still picked up.
+const IDLE_POLL_SECS: i64 = 5 * 60;
 
 /// Outcome of attempting one provider against one job.
 enum Outcome {
@@ -38,6 +63,7 @@ enum Outcome {
 /// the discriminator: `None` means "TMDB path, route through
 /// `tmdb::download_poster`"; `Some(PosterSize)` means "IMDB path, route
 /// through `imdb::download_poster`".
+#[derive(Debug, Clone)]
 pub struct PosterDownload {
     pub url: String,
     pub filename: String,
@@ -47,18 +73,29 @@ pub struct PosterDownload {
 /// Match result from a per-kind dispatcher (e.g. `dispatch_tmdb_movie`).
 enum MatchOutcome {
     NoMatch,
-    Matched { poster: Option<PosterDownload> },
+    Matched {
+        poster: Option<PosterDownload>,
+        backdrop: Option<PosterDownload>,
+        /// Collection row id and TMDB poster path, when the collection
+        /// has no poster yet.
+        collection_poster: Option<(i64, String)>,
+        /// For the other provider's rating.
+        imdb_id: Option<String>,
+    },
 }
 
-pub fn spawn(pool: SqlitePool, http: reqwest::Client, app: AppHandle) -> Arc<Notify> {
+pub fn spawn(pool: SqlitePool, http: ProviderHttp, posters_dir: PathBuf) -> Arc<Notify> {
     let notify = Arc::new(Notify::new());
     let notify_clone = notify.clone();
 
+    refresh::spawn(pool.clone(), notify.clone());
+    poster_cache::spawn(pool.clone(), http.clone(), posters_dir.clone(), notify.clone());
+
     // Tauri's setup closure runs outside a tokio runtime context, so a bare
     // `tokio::spawn` panics. `tauri::async_runtime::spawn` is the same
     // multi-threaded tokio runtime Tauri uses for command handlers.
     tauri::async_runtime::spawn(async move {
-        if let Err(error) = run(pool, http, app, notify_clone).await {
+        if let Err(error) = run(pool, http, posters_dir, notify_clone).await {
             eprintln!("metadata worker exited with error: {error}");
         }
     });
@@ -68,183 +105,374 @@ pub fn spawn(pool: SqlitePool, http: reqwest::Client, app: AppHandle) -> Arc<Not
 
 async fn run(
     pool: SqlitePool,
-    http: reqwest::Client,
-    app: AppHandle,
+    http: ProviderHttp,
+    posters_dir: PathBuf,
     notify: Arc<Notify>,
 ) -> AppResult<()> {
-    loop {
-        let mode = app_queries::get_app_setting(&pool, "metadata_mode")
-            .await?
-            .unwrap_or_else(|| "prefer_tmdb".to_string());
+    queries::release_all_claims(&pool).await?;
 
-        let api_key = app_queries::get_app_setting(&pool, "tmdb_api_key").await?;
-        let key_at_job_start = api_key.clone();
+    let mut in_flight = JoinSet::new();
 
-        let Some(job) = queries::next_due(&pool).await? else {
-            notify.notified().await;
-            continue;
-        };
+    loop {
+        // Re-read every pass so a settings change applies without a restart.
+        let limit = read_concurrency(&pool).await?;
 
-        if job.attempts >= MAX_ATTEMPTS {
-            sleep(Duration::from_secs(60)).await;
+        if in_flight.len() >= limit {
+            in_flight.join_next().await;
             continue;
         }
 
-        let now = unix_now();
-        if job.next_attempt_at > now {
-            let wait = Duration::from_secs((job.next_attempt_at - now) as u64);
-            tokio::select! {
-                _ = sleep(wait) => {},
-                _ = notify.notified() => {},
-            }
+        if let Some(job) = queries::claim_next(&pool, CLAIM_TIMEOUT_SECS).await? {
+            let pool = pool.clone();
+            let http = if job.revalidate { http.revalidating() } else { http.clone() };
+            let posters_dir = posters_dir.clone();
+            in_flight.spawn(async move {
+                run_claimed(&pool, &http, &posters_dir, &job).await;
+            });
             continue;
         }
 
-        // Fast path: hand-linked rows bypass mode dispatch. The user's
-        // pick (or a prior successful link) is the source of truth.
-        if let Some(linked) = read_linked_provider(&pool, &job).await? {
-            let key_for_call = api_key.as_deref().unwrap_or("");
+        // Nothing claimable: sleep until the next backed-off job is due, a
+        // notify arrives, or an in-flight job finishes (it may have been
+        // the only thing blocking the queue).
+        let wait = match queries::next_wake_at(&pool).await? {
+            Some(at) => Duration::from_secs((at - unix_now()).clamp(1, IDLE_POLL_SECS) as u64),
+            None => Duration::from_secs(IDLE_POLL_SECS as u64),
+        };
+        tokio::select! {
+            _ = notify.notified() => {},
+            _ = sleep(wait) => {},
+            Some(_) = in_flight.join_next(), if !in_flight.is_empty() => {},
+        }
+    }
+}
 
-            match dispatch_provider(linked, &pool, &http, &app, key_for_call, &job).await {
-                Ok(Outcome::Matched) => {
-                    sleep(Duration::from_millis(PACING_MS)).await;
-                    continue;
+/// Runs a claimed job, keeping its claim alive meanwhile, then releases
+/// it. A job that errors out is backed off first: released as it is, it
+/// would be due again at once and claimed straight back.
+async fn run_claimed(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    job: &queries::MetadataJob,
+) {
+    let work = process_job(pool, http, posters_dir, job);
+    tokio::pin!(work);
+    let result = loop {
+        tokio::select! {
+            result = &mut work => break result,
+            _ = sleep(Duration::from_secs(CLAIM_HEARTBEAT_SECS)) => {
+                let refreshed = queries::refresh_claim(pool, &job.kind, job.media_id).await;
+                if let Err(error) = refreshed {
+                    eprintln!("metadata job heartbeat failed: {error}");
                 }
-                Ok(Outcome::NoMatch) => {
-                    let mut tx = pool.begin().await?;
-                    queries::delete_in_tx(&mut *tx, &job.kind, job.media_id).await?;
-                    tx.commit().await?;
+            }
+        }
+    };
+    if let Err(error) = result {
+        eprintln!("metadata job {} {} failed: {error}", job.kind, job.media_id);
+        let message = error.to_string();
+        if let Err(error) = queries::record_failure(pool, &job.kind, job.media_id, &message).await
+        {
+            eprintln!("metadata job backoff failed: {error}");
+        }
+    }
+    if let Err(error) = queries::release_claim(pool, &job.kind, job.media_id).await {
+        eprintln!("metadata job release failed: {error}");
+    }
+}
 
-                    sleep(Duration::from_millis(PACING_MS)).await;
-                    continue;
+/// One claimed job, start to finish. Errors are reported by the caller;
+/// the claim is released either way.
+async fn process_job(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    job: &queries::MetadataJob,
+) -> AppResult<()> {
+    let mode = app_queries::get_app_setting(pool, "metadata_mode")
+        .await?
+        .unwrap_or_else(|| "prefer_tmdb".to_string());
+
+    let api_key = app_queries::get_app_setting(pool, "tmdb_api_key").await?;
+    let key_at_job_start = api_key.clone();
+    let language = locale::scrape_language(pool).await?;
+
+    // Fast path: linked rows bypass mode dispatch. The user's pick (or a
+    // prior successful link) is the source of truth; only a link the
+    // worker made moves, when the mode dropped that provider for one the
+    // title's id is known on.
+    let linked = match read_linked_provider(pool, job).await? {
+        Some(linked) if !moves_provider(pool, job, linked, &mode, api_key.is_some()).await? => {
+            Some(linked)
+        }
+        _ => None,
+    };
+    if let Some(linked) = linked {
+        let key_for_call = api_key.as_deref().unwrap_or("");
+
+        match dispatch_provider(linked, pool, http, posters_dir, key_for_call, &language, job)
+            .await
+        {
+            Ok(Outcome::Matched) => return Ok(()),
+            Ok(Outcome::NoMatch) => {
+                let mut tx = pool.begin().await?;
+                queries::delete_in_tx(&mut tx, &job.kind, job.media_id).await?;
+                tx.commit().await?;
+                return Ok(());
+            }
+            Err(error) => {
+                let error_string = error.to_string();
+                if is_rate_limited(&error_string) {
+                    defer_rate_limited(pool, http, job, linked, &error_string).await?;
+                    return Ok(());
                 }
-                Err(error) => {
-                    let error_string = error.to_string();
-                    if error_string.starts_with("auth_required")
-                        || error_string.starts_with("tmdb_auth_required")
-                    {
-                        let key_now =
-                            app_queries::get_app_setting(&pool, "tmdb_api_key").await?;
-                        if key_now == key_at_job_start {
-                            app_queries::set_app_setting(&pool, "tmdb_auth_bad", "1").await?;
-                        }
+                if error_string.starts_with("auth_required")
+                    || error_string.starts_with("tmdb_auth_required")
+                {
+                    let key_now =
+                        app_queries::get_app_setting(pool, "tmdb_api_key").await?;
+                    if key_now == key_at_job_start {
+                        app_queries::set_app_setting(pool, "tmdb_auth_bad", "1").await?;
                     }
-                    queries::record_failure(
-                        &pool,
-                        &job.kind,
-                        job.media_id,
-                        &error_string,
-                    )
-                    .await?;
-
-                    sleep(Duration::from_millis(PACING_MS)).await;
-                    continue;
                 }
+                queries::record_failure(
+                    pool,
+                    &job.kind,
+                    job.media_id,
+                    &error_string,
+                )
+                .await?;
+                return Ok(());
             }
         }
+    }
 
-        let providers = match providers_for_mode(&mode, api_key.is_some()) {
-            Ok(list) => list,
-            Err(reason) => {
-                queries::park_with_reason(&pool, &job.kind, job.media_id, reason).await?;
-                continue;
-            }
-        };
-
-        if providers.is_empty() {
-            // mode == "off" — drain the queue.
-            let mut tx = pool.begin().await?;
-            queries::delete_in_tx(&mut *tx, &job.kind, job.media_id).await?;
-            tx.commit().await?;
-            continue;
+    let providers = match providers_for_mode(&mode, api_key.is_some()) {
+        Ok(list) => list,
+        Err(reason) => {
+            queries::park_with_reason(pool, &job.kind, job.media_id, reason).await?;
+            return Ok(());
         }
+    };
 
-        let mut last_err: Option<AppError> = None;
-        let mut saw_tmdb_auth = false;
-        let mut matched = false;
+    if providers.is_empty() {
+        // mode == "off" — drain the queue.
+        let mut tx = pool.begin().await?;
+        queries::delete_in_tx(&mut tx, &job.kind, job.media_id).await?;
+        tx.commit().await?;
+        return Ok(());
+    }
 
-        for (index, provider) in providers.iter().enumerate() {
-            if index > 0 {
-                sleep(Duration::from_millis(PACING_MS)).await;
+    let mut last_err: Option<AppError> = None;
+    let mut saw_tmdb_auth = false;
+    let mut rate_limited: Option<(Provider, String)> = None;
+    let mut matched = false;
+
+    for provider in providers.iter() {
+        let key_for_call = api_key.as_deref().unwrap_or("");
+        let dispatched =
+            dispatch_provider(*provider, pool, http, posters_dir, key_for_call, &language, job)
+                .await;
+        match dispatched {
+            Ok(Outcome::Matched) => {
+                matched = true;
+                break;
             }
-
-            let key_for_call = api_key.as_deref().unwrap_or("");
-            match dispatch_provider(*provider, &pool, &http, &app, key_for_call, &job).await {
-                Ok(Outcome::Matched) => {
-                    matched = true;
-                    break;
+            Ok(Outcome::NoMatch) => continue,
+            Err(error) => {
+                let error_string = error.to_string();
+                if is_rate_limited(&error_string) {
+                    rate_limited = Some((*provider, error_string));
+                    continue;
                 }
-                Ok(Outcome::NoMatch) => continue,
-                Err(error) => {
-                    let error_string = error.to_string();
-                    if error_string.starts_with("auth_required")
-                        || error_string.starts_with("tmdb_auth_required")
-                    {
-                        saw_tmdb_auth = true;
-                        let key_now =
-                            app_queries::get_app_setting(&pool, "tmdb_api_key").await?;
-                        if key_now == key_at_job_start {
-                            app_queries::set_app_setting(&pool, "tmdb_auth_bad", "1").await?;
-                        }
+                if error_string.starts_with("auth_required")
+                    || error_string.starts_with("tmdb_auth_required")
+                {
+                    saw_tmdb_auth = true;
+                    let key_now =
+                        app_queries::get_app_setting(pool, "tmdb_api_key").await?;
+                    if key_now == key_at_job_start {
+                        app_queries::set_app_setting(pool, "tmdb_auth_bad", "1").await?;
                     }
-                    last_err = Some(error);
-                    continue;
                 }
+                last_err = Some(error);
+                continue;
             }
         }
+    }
 
-        if matched {
-            // dispatch_tmdb_* already committed apply + delete_in_tx in one tx.
-        } else if saw_tmdb_auth {
-            queries::park_with_reason(
-                &pool,
-                &job.kind,
-                job.media_id,
-                ParkReason::TmdbAuthRequired,
-            )
-            .await?;
-        } else if let Some(error) = last_err {
-            queries::record_failure(&pool, &job.kind, job.media_id, &error.to_string()).await?;
-        } else {
-            let mut tx = pool.begin().await?;
-            queries::delete_in_tx(&mut *tx, &job.kind, job.media_id).await?;
-            tx.commit().await?;
-        }
-
-        sleep(Duration::from_millis(PACING_MS)).await;
+    if matched {
+        // dispatch_tmdb_* already committed apply + delete_in_tx in one tx.
+    } else if saw_tmdb_auth {
+        queries::park_with_reason(
+            pool,
+            &job.kind,
+            job.media_id,
+            ParkReason::TmdbAuthRequired,
+        )
+        .await?;
+    } else if let Some((provider, message)) = rate_limited {
+        defer_rate_limited(pool, http, job, provider, &message).await?;
+    } else if let Some(error) = last_err {
+        queries::record_failure(pool, &job.kind, job.media_id, &error.to_string()).await?;
+    } else {
+        let mut tx = pool.begin().await?;
+        queries::delete_in_tx(&mut tx, &job.kind, job.media_id).await?;
+        tx.commit().await?;
     }
+
+    Ok(())
+}
+
+/// `metadata_concurrency`, falling back to `default_for`.
+async fn read_concurrency(pool: &SqlitePool) -> AppResult<usize> {
+    let stored = app_queries::get_app_setting(pool, "metadata_concurrency").await?;
+    let limit = stored
+        .as_deref()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .or_else(|| {
+            app_queries::default_for("metadata_concurrency").and_then(|value| value.parse().ok())
+        })
+        .unwrap_or(1);
+
+    Ok(limit.clamp(1, MAX_CONCURRENCY))
+}
+
+fn is_rate_limited(error: &str) -> bool {
+    error.starts_with("rate_limited")
 }
 
+/// Re-schedules a throttled job for when `provider` reopens. Doesn't
+/// count as an attempt, so throttling can never dead-letter a job.
+async fn defer_rate_limited(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    job: &queries::MetadataJob,
+    provider: Provider,
+    message: &str,
+) -> AppResult<()> {
+    let delay = http
+        .blocked_for(provider)
+        .map(|wait| wait.as_secs().max(1) as i64)
+        .unwrap_or(RATE_LIMIT_DEFER_SECS);
+
+    queries::defer(pool, &job.kind, job.media_id, delay, message).await
+}
+
+/// What a dispatcher saw on its way to an outcome, for the job log.
+/// Filled in as it goes, so a failed call still records how far it got.
+#[derive(Debug, Default)]
+struct AttemptTrace {
+    query: Option<String>,
+    candidate_count: Option<i64>,
+    chosen_id: Option<String>,
+    /// Best candidates when the search found no confident match.
+    ranked: Vec<(matching::MatchCandidate, f64)>,
+}
+
+/// Runs one provider against one job and appends the attempt to
+/// `metadata_job_log`. A failed log write is reported, never propagated.
 async fn dispatch_provider(
     provider: Provider,
     pool: &SqlitePool,
-    http: &reqwest::Client,
-    app: &AppHandle,
+    http: &ProviderHttp,
+    posters_dir: &Path,
     api_key: &str,
+    language: &str,
     job: &queries::MetadataJob,
 ) -> AppResult<Outcome> {
-    match provider {
-        Provider::Tmdb => dispatch_tmdb(pool, http, app, api_key, job).await,
-        Provider::Imdb => dispatch_imdb(pool, http, app, job).await,
+    let started = Instant::now();
+    let mut trace = AttemptTrace::default();
+
+    let result = match provider {
+        Provider::Tmdb => {
+            dispatch_tmdb(pool, http, posters_dir, api_key, language, job, &mut trace).await
+        }
+        Provider::Imdb => {
+            dispatch_imdb(pool, http, posters_dir, api_key, language, job, &mut trace).await
+        }
+    };
+
+    if let Err(error) = record_review_candidates(pool, job, provider, &result, &mut trace).await {
+        eprintln!("review candidates update failed: {error}");
+    }
+
+    let (outcome, error) = match &result {
+        Ok(Outcome::Matched) => (job_log::OUTCOME_MATCHED, None),
+        Ok(Outcome::NoMatch) => (job_log::OUTCOME_NO_MATCH, None),
+        Err(error) => {
+            let message = error.to_string();
+            let outcome = if is_rate_limited(&message) {
+                job_log::OUTCOME_RATE_LIMITED
+            } else if message.starts_with("auth_required")
+                || message.starts_with("tmdb_auth_required")
+            {
+                job_log::OUTCOME_AUTH_REQUIRED
+            } else {
+                job_log::OUTCOME_ERROR
+            };
+            (outcome, Some(message))
+        }
+    };
+
+    let entry = job_log::NewLogEntry {
+        kind: &job.kind,
+        media_id: job.media_id,
+        provider: provider.as_str(),
+        query: trace.query,
+        candidate_count: trace.candidate_count,
+        chosen_id: trace.chosen_id,
+        outcome,
+        error,
+        duration_ms: started.elapsed().as_millis() as i64,
+    };
+    if let Err(error) = job_log::append(pool, &entry).await {
+        eprintln!("metadata job log write failed: {error}");
+    }
+
+    result
+}
+
+/// Keeps the ranked candidates of an unmatched search for Needs-review,
+/// and drops any stored ones once the title is matched.
+async fn record_review_candidates(
+    pool: &SqlitePool,
+    job: &queries::MetadataJob,
+    provider: Provider,
+    result: &AppResult<Outcome>,
+    trace: &mut AttemptTrace,
+) -> AppResult<()> {
+    match result {
+        Ok(Outcome::NoMatch) if trace.candidate_count.is_some() => {
+            let candidates: Vec<review::ReviewCandidate> = trace
+                .ranked
+                .drain(..)
+                .map(review::ReviewCandidate::from_ranked)
+                .collect();
+            review::replace_for_provider(pool, &job.kind, job.media_id, provider, &candidates)
+                .await
+        }
+        Ok(Outcome::Matched) => {
+            let mut conn = pool.acquire().await?;
+            review::clear(&mut conn, &job.kind, job.media_id).await
+        }
+        _ => Ok(()),
     }
 }
 
 async fn dispatch_tmdb(
     pool: &SqlitePool,
-    http: &reqwest::Client,
-    app: &AppHandle,
+    http: &ProviderHttp,
+    posters_dir: &Path,
     api_key: &str,
+    language: &str,
     job: &queries::MetadataJob,
+    trace: &mut AttemptTrace,
 ) -> AppResult<Outcome> {
-    let posters_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|error| AppError::Other(format!("app_data_dir: {error}")))?
-        .join("posters");
-
     let result = match job.kind.as_str() {
-        "movie" => dispatch_tmdb_movie(pool, http, api_key, job.media_id).await?,
-        "show" => dispatch_tmdb_show(pool, http, api_key, job.media_id).await?,
+        "movie" => dispatch_tmdb_movie(pool, http, api_key, language, job.media_id, trace).await?,
+        "show" => dispatch_tmdb_show(pool, http, api_key, language, job.media_id, trace).await?,
         other => {
             return Err(AppError::Other(format!("unknown job kind: {other}")));
         }
@@ -252,46 +480,210 @@ async fn dispatch_tmdb(
 
     match result {
         MatchOutcome::NoMatch => Ok(Outcome::NoMatch),
-        MatchOutcome::Matched { poster } => {
+        MatchOutcome::Matched {
+            poster,
+            backdrop,
+            collection_poster,
+            imdb_id,
+        } => {
             if let Some(download) = poster {
                 let dest = posters_dir.join(&download.filename);
                 // Best-effort: a failed poster download doesn't invalidate
                 // the already-committed text metadata.
-                if let Err(error) = tmdb::download_poster(http, &download.url, &dest).await {
+                let result = match tmdb::download_poster(http, &download.url, &dest).await {
+                    Ok(()) => {
+                        posters::store_variants(
+                            pool,
+                            posters_dir,
+                            &job.kind,
+                            job.media_id,
+                            &download.filename,
+                        )
+                        .await
+                    }
+                    Err(error) => Err(error),
+                };
+                if let Err(error) = result {
                     eprintln!("tmdb poster download failed for {dest:?}: {error}");
                 }
             }
+            if let Some(download) = backdrop {
+                if let Err(error) = backdrops::download(http, posters_dir, &download).await {
+                    eprintln!("backdrop download failed for {}: {error}", download.filename);
+                }
+            }
+            if let Some((collection_id, poster_path)) = collection_poster {
+                download_collection_poster(pool, http, posters_dir, collection_id, &poster_path)
+                    .await;
+            }
+            if let Some(imdb_id) = imdb_id {
+                store_other_provider(pool, http, api_key, language, job, Provider::Imdb, &imdb_id)
+                    .await;
+            }
             Ok(Outcome::Matched)
         }
     }
 }
 
+/// Best-effort, like posters: stores `provider`'s rating for a title
+/// linked to the other one, when `metadata_mode` uses `provider` too.
+/// TMDB is looked up by IMDB id, which also records the TMDB id.
+async fn store_other_provider(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    api_key: &str,
+    language: &str,
+    job: &queries::MetadataJob,
+    provider: Provider,
+    imdb_id: &str,
+) {
+    let mode = match app_queries::get_app_setting(pool, "metadata_mode").await {
+        Ok(mode) => mode.unwrap_or_else(|| "prefer_tmdb".to_string()),
+        Err(error) => {
+            eprintln!("metadata mode read failed: {error}");
+            return;
+        }
+    };
+    let in_mode = providers_for_mode(&mode, !api_key.is_empty())
+        .is_ok_and(|providers| providers.contains(&provider));
+    if !in_mode {
+        return;
+    }
+
+    let source = provider.as_str();
+    let result = match provider {
+        Provider::Tmdb => store_tmdb_cross_reference(pool, http, api_key, job, imdb_id).await,
+        Provider::Imdb => match ratings::fetch_imdb(http, &job.kind, imdb_id, language).await {
+            Ok(rating) => ratings::store(pool, &job.kind, job.media_id, source, rating).await,
+            Err(error) => Err(error),
+        },
+    };
+    if let Err(error) = result {
+        eprintln!("{source} rating for {} {} failed: {error}", job.kind, job.media_id);
+    }
+}
+
+async fn store_tmdb_cross_reference(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    api_key: &str,
+    job: &queries::MetadataJob,
+    imdb_id: &str,
+) -> AppResult<()> {
+    let found = tmdb::find_by_imdb_id(http, api_key, &job.kind, imdb_id).await?;
+    if let Some(found) = &found {
+        let id = external_ids::ExternalId {
+            source: "tmdb".to_string(),
+            external_id: found.id.to_string(),
+        };
+        external_ids::store(pool, &job.kind, job.media_id, &[id]).await?;
+    }
+    let rating = found.and_then(|found| ratings::from_tmdb(found.vote_average, found.vote_count));
+    ratings::store(pool, &job.kind, job.media_id, "tmdb", rating).await
+}
+
+/// Best-effort, like movie posters; the collection keeps no poster on
+/// failure and the next match of one of its parts tries again.
+async fn download_collection_poster(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    collection_id: i64,
+    poster_path: &str,
+) {
+    let extension = Path::new(poster_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("jpg");
+    let filename = format!("collection-{collection_id}.{extension}");
+    let dest = posters_dir.join(&filename);
+
+    let result = match tmdb::download_poster(http, poster_path, &dest).await {
+        Ok(()) => collections::set_poster(pool, collection_id, &filename).await,
+        Err(error) => Err(error),
+    };
+    if let Err(error) = result {
+        eprintln!("collection poster download failed for {dest:?}: {error}");
+    }
+}
+
 async fn dispatch_tmdb_movie(
     pool: &SqlitePool,
-    http: &reqwest::Client,
+    http: &ProviderHttp,
     api_key: &str,
+    language: &str,
     movie_id: i64,
+    trace: &mut AttemptTrace,
 ) -> AppResult<MatchOutcome> {
-    let row: Option<(i64, String, Option<i32>)> = sqlx::query_as(
-        "SELECT metadata_locked, title, year FROM movies WHERE id = ?1",
+    let row: Option<(i64, String, Option<i32>, Option<String>)> = sqlx::query_as(
+        "SELECT metadata_locked, title, year,
+                CASE WHEN provider = 'tmdb' THEN provider_id END
+         FROM movies WHERE id = ?1",
     )
     .bind(movie_id)
     .fetch_optional(pool)
     .await?;
 
-    let Some((locked, title, year)) = row else {
+    let Some((locked, title, year, linked_id)) = row else {
         return Ok(MatchOutcome::NoMatch);
     };
     if locked != 0 {
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let candidates = tmdb::search_movie(http, api_key, &title, year).await?;
-    let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
-        return Ok(MatchOutcome::NoMatch);
+    // Already linked to this provider (hand-link or scheduled refresh), or
+    // the title has a learned alias: fetch by id instead of searching.
+    let known = match known_provider_id(
+        pool,
+        "movie",
+        Provider::Tmdb,
+        &title,
+        year,
+        linked_id,
+        trace,
+    )
+    .await?
+    {
+        Some(provider_id) => Some(provider_id),
+        None => tmdb_id_by_cross_reference(pool, http, api_key, "movie", movie_id, trace).await?,
+    };
+    let provider_id = match known {
+        Some(provider_id) => provider_id,
+        None => {
+            trace.query = Some(describe_query(&title, year));
+            let candidates = tmdb::search_movie(http, api_key, &title, year, language).await?;
+            trace.candidate_count = Some(candidates.len() as i64);
+            let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
+                trace.ranked = matching::rank(
+                    &title,
+                    year,
+                    &candidates,
+                    review::MAX_CANDIDATES_PER_PROVIDER,
+                );
+                return Ok(MatchOutcome::NoMatch);
+            };
+            pick.provider_id.clone()
+        }
     };
+    trace.chosen_id = Some(provider_id.clone());
+
+    let details = tmdb::fetch_movie_details(http, api_key, &provider_id, language).await?;
 
-    let details = tmdb::fetch_movie_details(http, api_key, &pick.provider_id).await?;
+    // Best-effort like the poster, except throttling: a 429 defers the
+    // whole job so the collection isn't silently skipped.
+    let collection = match &details.belongs_to_collection {
+        Some(reference) => {
+            match tmdb::fetch_collection(http, api_key, reference.id, language).await {
+                Ok(collection) => Some(collection),
+                Err(error) if is_rate_limited(&error.to_string()) => return Err(error),
+                Err(error) => {
+                    eprintln!("tmdb collection {} fetch failed: {error}", reference.id);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
 
     let mut tx = pool.begin().await?;
 
@@ -308,11 +700,34 @@ async fn dispatch_tmdb_movie(
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let download_ext = apply::apply_movie_details(&mut *tx, movie_id, &details).await?;
+    let download_ext = apply::apply_movie_details(&mut tx, movie_id, &details).await?;
+    locale::store_in_tx(&mut tx, "movie", movie_id, &details.localized).await?;
+    let image_base = &http.endpoints().tmdb_image;
+    let credits = people::from_tmdb(details.credits.as_ref(), image_base);
+    people::store_in_tx(&mut tx, "movie", movie_id, "tmdb", &credits).await?;
+    let backdrop = backdrops::apply_in_tx(
+        &mut tx,
+        "movie",
+        movie_id,
+        "tmdb",
+        details.backdrop_path.as_deref(),
+    )
+    .await?;
+    let mut collection_poster = None;
+    if let Some(collection) = &collection {
+        let (collection_id, needs_poster) =
+            collections::store_tmdb_in_tx(&mut tx, collection, image_base).await?;
+        if needs_poster {
+            collection_poster = collection
+                .poster_path
+                .clone()
+                .map(|poster_path| (collection_id, poster_path));
+        }
+    }
 
     // Merged delete: apply + delete go in one tx so a concurrent
     // re-enqueue between the two writes can't be silently dropped.
-    queries::delete_in_tx(&mut *tx, "movie", movie_id).await?;
+    queries::delete_in_tx(&mut tx, "movie", movie_id).await?;
     tx.commit().await?;
 
     Ok(MatchOutcome::Matched {
@@ -324,35 +739,73 @@ async fn dispatch_tmdb_movie(
             }),
             _ => None,
         },
+        backdrop,
+        collection_poster,
+        imdb_id: details.imdb_id.filter(|id| !id.is_empty()),
     })
 }
 
 async fn dispatch_tmdb_show(
     pool: &SqlitePool,
-    http: &reqwest::Client,
+    http: &ProviderHttp,
     api_key: &str,
+    language: &str,
     show_id: i64,
+    trace: &mut AttemptTrace,
 ) -> AppResult<MatchOutcome> {
-    let row: Option<(i64, String, Option<i32>)> = sqlx::query_as(
-        "SELECT metadata_locked, title, year FROM shows WHERE id = ?1",
+    let row: Option<(i64, String, Option<i32>, Option<String>)> = sqlx::query_as(
+        "SELECT metadata_locked, title, year,
+                CASE WHEN provider = 'tmdb' THEN provider_id END
+         FROM shows WHERE id = ?1",
     )
     .bind(show_id)
     .fetch_optional(pool)
     .await?;
 
-    let Some((locked, title, year)) = row else {
+    let Some((locked, title, year, linked_id)) = row else {
         return Ok(MatchOutcome::NoMatch);
     };
     if locked != 0 {
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let candidates = tmdb::search_show(http, api_key, &title, year).await?;
-    let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
-        return Ok(MatchOutcome::NoMatch);
+    // Already linked to this provider (hand-link or scheduled refresh), or
+    // the title has a learned alias: fetch by id instead of searching.
+    let known = match known_provider_id(
+        pool,
+        "show",
+        Provider::Tmdb,
+        &title,
+        year,
+        linked_id,
+        trace,
+    )
+    .await?
+    {
+        Some(provider_id) => Some(provider_id),
+        None => tmdb_id_by_cross_reference(pool, http, api_key, "show", show_id, trace).await?,
     };
+    let provider_id = match known {
+        Some(provider_id) => provider_id,
+        None => {
+            trace.query = Some(describe_query(&title, year));
+            let candidates = tmdb::search_show(http, api_key, &title, year, language).await?;
+            trace.candidate_count = Some(candidates.len() as i64);
+            let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
+                trace.ranked = matching::rank(
+                    &title,
+                    year,
+                    &candidates,
+                    review::MAX_CANDIDATES_PER_PROVIDER,
+                );
+                return Ok(MatchOutcome::NoMatch);
+            };
+            pick.provider_id.clone()
+        }
+    };
+    trace.chosen_id = Some(provider_id.clone());
 
-    let details = tmdb::fetch_show_details(http, api_key, &pick.provider_id).await?;
+    let details = tmdb::fetch_show_details(http, api_key, &provider_id, language).await?;
 
     let mut tx = pool.begin().await?;
 
@@ -367,11 +820,23 @@ async fn dispatch_tmdb_show(
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let download_ext = apply::apply_show_details(&mut *tx, show_id, &details).await?;
+    let download_ext = apply::apply_show_details(&mut tx, show_id, &details).await?;
+    locale::store_in_tx(&mut tx, "show", show_id, &details.localized).await?;
+    let credits = people::from_tmdb(details.credits.as_ref(), &http.endpoints().tmdb_image);
+    people::store_in_tx(&mut tx, "show", show_id, "tmdb", &credits).await?;
+    let backdrop = backdrops::apply_in_tx(
+        &mut tx,
+        "show",
+        show_id,
+        "tmdb",
+        details.backdrop_path.as_deref(),
+    )
+    .await?;
 
-    queries::delete_in_tx(&mut *tx, "show", show_id).await?;
+    queries::delete_in_tx(&mut tx, "show", show_id).await?;
     tx.commit().await?;
 
+    let imdb_id = details.imdb_id().map(str::to_string);
     Ok(MatchOutcome::Matched {
         poster: match (download_ext, details.poster_path) {
             (Some(extension), Some(poster_path)) => Some(PosterDownload {
@@ -381,24 +846,24 @@ async fn dispatch_tmdb_show(
             }),
             _ => None,
         },
+        backdrop,
+        collection_poster: None,
+        imdb_id,
     })
 }
 
 async fn dispatch_imdb(
     pool: &SqlitePool,
-    http: &reqwest::Client,
-    app: &AppHandle,
+    http: &ProviderHttp,
+    posters_dir: &Path,
+    api_key: &str,
+    language: &str,
     job: &queries::MetadataJob,
+    trace: &mut AttemptTrace,
 ) -> AppResult<Outcome> {
-    let posters_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|error| AppError::Other(format!("app_data_dir: {error}")))?
-        .join("posters");
-
     let result = match job.kind.as_str() {
-        "movie" => dispatch_imdb_movie(pool, http, job.media_id).await?,
-        "show" => dispatch_imdb_show(pool, http, job.media_id).await?,
+        "movie" => dispatch_imdb_movie(pool, http, language, job.media_id, trace).await?,
+        "show" => dispatch_imdb_show(pool, http, language, job.media_id, trace).await?,
         other => {
             return Err(AppError::Other(format!("unknown job kind: {other}")));
         }
@@ -406,16 +871,43 @@ async fn dispatch_imdb(
 
     match result {
         MatchOutcome::NoMatch => Ok(Outcome::NoMatch),
-        MatchOutcome::Matched { poster } => {
+        MatchOutcome::Matched {
+            poster,
+            backdrop,
+            imdb_id,
+            ..
+        } => {
             if let Some(download) = poster {
                 let dest = posters_dir.join(&download.filename);
                 let size = download
                     .size
                     .unwrap_or(crate::metadata::imdb::PosterSize::Small);
-                if let Err(error) = imdb::download_poster(http, &download.url, &dest, size).await {
+                let result = match imdb::download_poster(http, &download.url, &dest, size).await {
+                    Ok(()) => {
+                        posters::store_variants(
+                            pool,
+                            posters_dir,
+                            &job.kind,
+                            job.media_id,
+                            &download.filename,
+                        )
+                        .await
+                    }
+                    Err(error) => Err(error),
+                };
+                if let Err(error) = result {
                     eprintln!("imdb poster download failed for {dest:?}: {error}");
                 }
             }
+            if let Some(download) = backdrop {
+                if let Err(error) = backdrops::download(http, posters_dir, &download).await {
+                    eprintln!("backdrop download failed for {}: {error}", download.filename);
+                }
+            }
+            if let Some(imdb_id) = imdb_id {
+                store_other_provider(pool, http, api_key, language, job, Provider::Tmdb, &imdb_id)
+                    .await;
+            }
             Ok(Outcome::Matched)
         }
     }
@@ -423,29 +915,64 @@ async fn dispatch_imdb(
 
 async fn dispatch_imdb_movie(
     pool: &SqlitePool,
-    http: &reqwest::Client,
+    http: &ProviderHttp,
+    language: &str,
     movie_id: i64,
+    trace: &mut AttemptTrace,
 ) -> AppResult<MatchOutcome> {
-    let row: Option<(i64, String, Option<i32>)> = sqlx::query_as(
-        "SELECT metadata_locked, title, year FROM movies WHERE id = ?1",
+    let row: Option<(i64, String, Option<i32>, Option<String>)> = sqlx::query_as(
+        "SELECT metadata_locked, title, year,
+                CASE WHEN provider = 'imdb' THEN provider_id END
+         FROM movies WHERE id = ?1",
     )
     .bind(movie_id)
     .fetch_optional(pool)
     .await?;
 
-    let Some((locked, title, year)) = row else {
+    let Some((locked, title, year, linked_id)) = row else {
         return Ok(MatchOutcome::NoMatch);
     };
     if locked != 0 {
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let candidates = imdb::search_movie(http, &title, year).await?;
-    let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
-        return Ok(MatchOutcome::NoMatch);
+    // Already linked to this provider (hand-link or scheduled refresh), or
+    // the title has a learned alias: fetch by id instead of searching.
+    let known = match known_provider_id(
+        pool,
+        "movie",
+        Provider::Imdb,
+        &title,
+        year,
+        linked_id,
+        trace,
+    )
+    .await?
+    {
+        Some(provider_id) => Some(provider_id),
+        None => external_provider_id(pool, "movie", movie_id, Provider::Imdb, trace).await?,
+    };
+    let provider_id = match known {
+        Some(provider_id) => provider_id,
+        None => {
+            trace.query = Some(describe_query(&title, year));
+            let candidates = imdb::search_movie(http, &title, year, language).await?;
+            trace.candidate_count = Some(candidates.len() as i64);
+            let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
+                trace.ranked = matching::rank(
+                    &title,
+                    year,
+                    &candidates,
+                    review::MAX_CANDIDATES_PER_PROVIDER,
+                );
+                return Ok(MatchOutcome::NoMatch);
+            };
+            pick.provider_id.clone()
+        }
     };
+    trace.chosen_id = Some(provider_id.clone());
 
-    let details = imdb::fetch_movie_details(http, &pick.provider_id).await?;
+    let details = imdb::fetch_movie_details(http, &provider_id, language).await?;
 
     let mut tx = pool.begin().await?;
 
@@ -460,9 +987,20 @@ async fn dispatch_imdb_movie(
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let download_target = apply::apply_imdb_movie_details(&mut *tx, movie_id, &details).await?;
+    let download_target = apply::apply_imdb_movie_details(&mut tx, movie_id, &details).await?;
+    locale::store_in_tx(&mut tx, "movie", movie_id, &details.localized).await?;
+    let credits = people::from_imdb(&details.principal_credits);
+    people::store_in_tx(&mut tx, "movie", movie_id, "imdb", &credits).await?;
+    let backdrop = backdrops::apply_in_tx(
+        &mut tx,
+        "movie",
+        movie_id,
+        "imdb",
+        backdrops::from_imdb(&details),
+    )
+    .await?;
 
-    queries::delete_in_tx(&mut *tx, "movie", movie_id).await?;
+    queries::delete_in_tx(&mut tx, "movie", movie_id).await?;
     tx.commit().await?;
 
     Ok(MatchOutcome::Matched {
@@ -471,34 +1009,72 @@ async fn dispatch_imdb_movie(
             filename,
             size: Some(size),
         }),
+        backdrop,
+        collection_poster: None,
+        imdb_id: Some(provider_id),
     })
 }
 
 async fn dispatch_imdb_show(
     pool: &SqlitePool,
-    http: &reqwest::Client,
+    http: &ProviderHttp,
+    language: &str,
     show_id: i64,
+    trace: &mut AttemptTrace,
 ) -> AppResult<MatchOutcome> {
-    let row: Option<(i64, String, Option<i32>)> = sqlx::query_as(
-        "SELECT metadata_locked, title, year FROM shows WHERE id = ?1",
+    let row: Option<(i64, String, Option<i32>, Option<String>)> = sqlx::query_as(
+        "SELECT metadata_locked, title, year,
+                CASE WHEN provider = 'imdb' THEN provider_id END
+         FROM shows WHERE id = ?1",
     )
     .bind(show_id)
     .fetch_optional(pool)
     .await?;
 
-    let Some((locked, title, year)) = row else {
+    let Some((locked, title, year, linked_id)) = row else {
         return Ok(MatchOutcome::NoMatch);
     };
     if locked != 0 {
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let candidates = imdb::search_show(http, &title, year).await?;
-    let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
-        return Ok(MatchOutcome::NoMatch);
+    // Already linked to this provider (hand-link or scheduled refresh), or
+    // the title has a learned alias: fetch by id instead of searching.
+    let known = match known_provider_id(
+        pool,
+        "show",
+        Provider::Imdb,
+        &title,
+        year,
+        linked_id,
+        trace,
+    )
+    .await?
+    {
+        Some(provider_id) => Some(provider_id),
+        None => external_provider_id(pool, "show", show_id, Provider::Imdb, trace).await?,
     };
+    let provider_id = match known {
+        Some(provider_id) => provider_id,
+        None => {
+            trace.query = Some(describe_query(&title, year));
+            let candidates = imdb::search_show(http, &title, year, language).await?;
+            trace.candidate_count = Some(candidates.len() as i64);
+            let Some(pick) = matching::pick_confident_match(&title, year, &candidates) else {
+                trace.ranked = matching::rank(
+                    &title,
+                    year,
+                    &candidates,
+                    review::MAX_CANDIDATES_PER_PROVIDER,
+                );
+                return Ok(MatchOutcome::NoMatch);
+            };
+            pick.provider_id.clone()
+        }
+    };
+    trace.chosen_id = Some(provider_id.clone());
 
-    let details = imdb::fetch_show_details(http, &pick.provider_id).await?;
+    let details = imdb::fetch_show_details(http, &provider_id, language).await?;
 
     let mut tx = pool.begin().await?;
 
@@ -513,9 +1089,20 @@ async fn dispatch_imdb_show(
         return Ok(MatchOutcome::NoMatch);
     }
 
-    let download_target = apply::apply_imdb_show_details(&mut *tx, show_id, &details).await?;
+    let download_target = apply::apply_imdb_show_details(&mut tx, show_id, &details).await?;
+    locale::store_in_tx(&mut tx, "show", show_id, &details.localized).await?;
+    let credits = people::from_imdb(&details.principal_credits);
+    people::store_in_tx(&mut tx, "show", show_id, "imdb", &credits).await?;
+    let backdrop = backdrops::apply_in_tx(
+        &mut tx,
+        "show",
+        show_id,
+        "imdb",
+        backdrops::from_imdb(&details),
+    )
+    .await?;
 
-    queries::delete_in_tx(&mut *tx, "show", show_id).await?;
+    queries::delete_in_tx(&mut tx, "show", show_id).await?;
     tx.commit().await?;
 
     Ok(MatchOutcome::Matched {
@@ -524,6 +1111,9 @@ async fn dispatch_imdb_show(
             filename,
             size: Some(size),
         }),
+        backdrop,
+        collection_poster: None,
+        imdb_id: Some(provider_id),
     })
 }
 
@@ -552,9 +1142,810 @@ async fn read_linked_provider(
     })
 }
 
+/// True when `mode` doesn't use the `linked` provider but uses one the
+/// title can be fetched from by id: a known id there, or for TMDB a
+/// known IMDB id to find it by. Never for a hand-linked title.
+async fn moves_provider(
+    pool: &SqlitePool,
+    job: &queries::MetadataJob,
+    linked: Provider,
+    mode: &str,
+    has_tmdb_key: bool,
+) -> AppResult<bool> {
+    let providers = providers_for_mode(mode, has_tmdb_key).unwrap_or_default();
+    if providers.is_empty() || providers.contains(&linked) {
+        return Ok(false);
+    }
+    if is_hand_linked(pool, job, linked).await? {
+        return Ok(false);
+    }
+    for provider in providers {
+        let mut sources = vec![provider.as_str()];
+        if provider == Provider::Tmdb {
+            sources.push(Provider::Imdb.as_str());
+        }
+        for source in sources {
+            if external_ids::lookup(pool, &job.kind, job.media_id, source).await?.is_some() {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// True when the title's link was made by hand: the alias the link
+/// recorded for its scanned title still points at the linked id.
+async fn is_hand_linked(
+    pool: &SqlitePool,
+    job: &queries::MetadataJob,
+    linked: Provider,
+) -> AppResult<bool> {
+    let table = match job.kind.as_str() {
+        "movie" => "movies",
+        "show" => "shows",
+        _ => return Ok(false),
+    };
+
+    let row: Option<(String, Option<i32>, Option<String>)> = sqlx::query_as(&format!(
+        "SELECT title, year, provider_id FROM {table} WHERE id = ?1"
+    ))
+    .bind(job.media_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((title, year, Some(provider_id))) = row else {
+        return Ok(false);
+    };
+
+    let alias = aliases::lookup(pool, &job.kind, &title, year, linked).await?;
+    Ok(alias.as_deref() == Some(provider_id.as_str()))
+}
+
+/// The provider id to fetch without searching: the row's own link to
+/// `provider`, else a `title_aliases` entry for the scanned title.
+/// Records which one was used in the trace query.
+async fn known_provider_id(
+    pool: &SqlitePool,
+    kind: &str,
+    provider: Provider,
+    title: &str,
+    year: Option<i32>,
+    linked_id: Option<String>,
+    trace: &mut AttemptTrace,
+) -> AppResult<Option<String>> {
+    if let Some(provider_id) = linked_id {
+        trace.query = Some(format!("id:{provider_id}"));
+        return Ok(Some(provider_id));
+    }
+
+    let alias = aliases::lookup(pool, kind, title, year, provider).await?;
+    if let Some(provider_id) = &alias {
+        trace.query = Some(format!("alias:{provider_id}"));
+    }
+    Ok(alias)
+}
+
+/// The title's `provider` id learned from another provider, when it
+/// isn't linked to `provider` itself.
+async fn external_provider_id(
+    pool: &SqlitePool,
+    kind: &str,
+    media_id: i64,
+    provider: Provider,
+    trace: &mut AttemptTrace,
+) -> AppResult<Option<String>> {
+    let external = external_ids::lookup(pool, kind, media_id, provider.as_str()).await?;
+    if let Some(provider_id) = &external {
+        trace.query = Some(format!("external:{provider_id}"));
+    }
+    Ok(external)
+}
+
+/// Like `external_provider_id`, and failing that the TMDB title found
+/// by the IMDB id, so an IMDB-linked title moves over without a fuzzy
+/// search.
+async fn tmdb_id_by_cross_reference(
+    pool: &SqlitePool,
+    http: &ProviderHttp,
+    api_key: &str,
+    kind: &str,
+    media_id: i64,
+    trace: &mut AttemptTrace,
+) -> AppResult<Option<String>> {
+    if let Some(tmdb_id) = external_provider_id(pool, kind, media_id, Provider::Tmdb, trace).await?
+    {
+        return Ok(Some(tmdb_id));
+    }
+    let Some(imdb_id) = external_ids::lookup(pool, kind, media_id, "imdb").await? else {
+        return Ok(None);
+    };
+    trace.query = Some(format!("find:{imdb_id}"));
+    let found = tmdb::find_by_imdb_id(http, api_key, kind, &imdb_id).await?;
+    Ok(found.map(|found| found.id.to_string()))
+}
+
+/// `Title (Year)`, or just `Title` — the search as the log shows it.
+fn describe_query(title: &str, year: Option<i32>) -> String {
+    match year {
+        Some(year) => format!("{title} ({year})"),
+        None => title.to_string(),
+    }
+}
+
 fn unix_now() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs() as i64)
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::fake_server::{FakeResponse, FakeServer, POSTER_BYTES};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    struct Harness {
+        pool: SqlitePool,
+        http: ProviderHttp,
+        posters_dir: PathBuf,
+        movie_id: i64,
+    }
+
+    impl Drop for Harness {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.posters_dir);
+        }
+    }
+
+    /// A library with one unlinked "The Matrix (1999)" movie, already
+    /// enqueued, and a `ProviderHttp` pointed at `server`.
+    async fn harness(server: &FakeServer, mode: &str) -> Harness {
+        let pool = fresh_pool().await;
+        sqlx::query("INSERT INTO libraries (id, path, kind) VALUES (1, '/tmp', 'movies')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (library_id, title, year, path)
+             VALUES (1, 'The Matrix', 1999, '/tmp/The Matrix (1999).mkv')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let movie_id: i64 = sqlx::query_scalar("SELECT last_insert_rowid()")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        app_queries::set_app_setting(&pool, "metadata_mode", mode).await.unwrap();
+        app_queries::set_app_setting(&pool, "tmdb_api_key", "test-key").await.unwrap();
+        queries::enqueue(&pool, "movie", movie_id).await.unwrap();
+
+        let http = ProviderHttp::new(reqwest::Client::new(), pool.clone())
+            .with_endpoints(server.endpoints());
+        let posters_dir = std::env::temp_dir().join(format!(
+            "rustflix-posters-{}-{}",
+            std::process::id(),
+            server.base_url().rsplit(':').next().unwrap()
+        ));
+
+        Harness {
+            pool,
+            http,
+            posters_dir,
+            movie_id,
+        }
+    }
+
+    /// Claims the next job and runs it, like one pass of the supervisor.
+    async fn run_next(harness: &Harness) {
+        let job = queries::claim_next(&harness.pool, CLAIM_TIMEOUT_SECS)
+            .await
+            .unwrap()
+            .expect("a due job");
+        process_job(&harness.pool, &harness.http, &harness.posters_dir, &job)
+            .await
+            .unwrap();
+        queries::release_claim(&harness.pool, &job.kind, job.media_id)
+            .await
+            .unwrap();
+    }
+
+    async fn job_row(pool: &SqlitePool, movie_id: i64) -> Option<(i64, Option<String>)> {
+        sqlx::query_as(
+            "SELECT attempts, last_error FROM metadata_jobs
+             WHERE kind = 'movie' AND media_id = ?1",
+        )
+        .bind(movie_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn tmdb_movie_is_matched_applied_and_poster_downloaded() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "tmdb_only").await;
+
+        run_next(&harness).await;
+
+        let (provider, provider_id, runtime, poster_path): (
+            Option<String>,
+            Option<String>,
+            Option<i64>,
+            Option<String>,
+        ) = sqlx::query_as(
+            "SELECT provider, provider_id, runtime_minutes, poster_path FROM movies WHERE id = ?1",
+        )
+        .bind(harness.movie_id)
+        .fetch_one(&harness.pool)
+        .await
+        .unwrap();
+        assert_eq!(provider.as_deref(), Some("tmdb"));
+        assert_eq!(provider_id.as_deref(), Some("603"));
+        assert_eq!(runtime, Some(136));
+        let genres: Vec<String> = sqlx::query_scalar(
+            "SELECT g.slug FROM movie_genres j JOIN genres g ON g.id = j.genre_id
+             WHERE j.movie_id = ?1 ORDER BY g.slug",
+        )
+        .bind(harness.movie_id)
+        .fetch_all(&harness.pool)
+        .await
+        .unwrap();
+        assert_eq!(genres, vec!["action", "science-fiction"]);
+        assert_eq!(
+            certifications_of(&harness.pool, harness.movie_id).await,
+            vec![("DE".to_string(), "16".to_string()), ("US".to_string(), "R".to_string())]
+        );
+
+        let poster = harness.posters_dir.join(poster_path.expect("poster path"));
+        assert_eq!(std::fs::read(poster).unwrap(), POSTER_BYTES);
+        assert_poster_variants(&harness).await;
+        assert_eq!(server.hits("/t/p/w342/f89U3ADr1oiB1s9GkdPOEpXUk5H.jpg"), 0);
+        assert_backdrop(&harness, "tmdb").await;
+        assert_eq!(server.hits("/t/p/w1280/fNG7i7RqMErkcqhohV2a6cV1Ehy.jpg"), 1);
+        assert!(job_row(&harness.pool, harness.movie_id).await.is_none());
+        assert!(server
+            .requests()
+            .iter()
+            .all(|request| !request.path().starts_with("/graphql")));
+
+        let log = job_log::list_for_media(&harness.pool, "movie", harness.movie_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].outcome, job_log::OUTCOME_MATCHED);
+        assert_eq!(log[0].query.as_deref(), Some("The Matrix (1999)"));
+        assert_eq!(log[0].candidate_count, Some(2));
+        assert_eq!(log[0].chosen_id.as_deref(), Some("603"));
+    }
+
+    async fn ratings_of(pool: &SqlitePool, movie_id: i64) -> Vec<(String, f64)> {
+        ratings::for_title(pool, "movie", movie_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|rating| (rating.source, rating.value))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn tmdb_match_also_stores_the_imdb_rating_when_the_mode_uses_imdb() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "prefer_tmdb").await;
+
+        run_next(&harness).await;
+
+        assert_eq!(
+            ratings_of(&harness.pool, harness.movie_id).await,
+            vec![("imdb".to_string(), 8.7), ("tmdb".to_string(), 8.2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn imdb_match_finds_the_tmdb_rating_by_imdb_id() {
+        let server = FakeServer::with_provider_fixtures().await;
+        server.route(
+            "GET",
+            "/3/find/tt0133093",
+            FakeResponse::json(
+                r#"{ "movie_results": [{ "id": 603, "vote_average": 8.2, "vote_count": 25000 }],
+                     "tv_results": [] }"#,
+            ),
+        );
+        let harness = harness(&server, "prefer_imdb").await;
+
+        run_next(&harness).await;
+
+        assert_eq!(
+            ratings_of(&harness.pool, harness.movie_id).await,
+            vec![("imdb".to_string(), 8.7), ("tmdb".to_string(), 8.2)]
+        );
+        assert_eq!(server.hits("/3/find/tt0133093"), 1);
+        assert_eq!(
+            external_ids_of(&harness.pool, harness.movie_id).await,
+            vec![("imdb".to_string(), "tt0133093".to_string()), ("tmdb".to_string(), "603".into())]
+        );
+    }
+
+    async fn external_ids_of(pool: &SqlitePool, movie_id: i64) -> Vec<(String, String)> {
+        external_ids::for_title(pool, "movie", movie_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|id| (id.source, id.external_id))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn imdb_linked_title_is_linked_on_tmdb_by_find_without_searching() {
+        let server = FakeServer::with_provider_fixtures().await;
+        server.route(
+            "GET",
+            "/3/find/tt0133093",
+            FakeResponse::json(r#"{ "movie_results": [{ "id": 603 }], "tv_results": [] }"#),
+        );
+        let harness = harness(&server, "tmdb_only").await;
+        sqlx::query("UPDATE movies SET provider = 'imdb', provider_id = 'tt0133093' WHERE id = ?1")
+            .bind(harness.movie_id)
+            .execute(&harness.pool)
+            .await
+            .unwrap();
+        let imdb = external_ids::ExternalId {
+            source: "imdb".into(),
+            external_id: "tt0133093".into(),
+        };
+        external_ids::store(&harness.pool, "movie", harness.movie_id, &[imdb]).await.unwrap();
+
+        run_next(&harness).await;
+
+        let (provider, provider_id): (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT provider, provider_id FROM movies WHERE id = ?1")
+                .bind(harness.movie_id)
+                .fetch_one(&harness.pool)
+                .await
+                .unwrap();
+        assert_eq!((provider.as_deref(), provider_id.as_deref()), (Some("tmdb"), Some("603")));
+        assert_eq!(server.hits("/3/search/movie"), 0);
+        assert_eq!(
+            external_ids_of(&harness.pool, harness.movie_id).await,
+            vec![
+                ("imdb".to_string(), "tt0133093".to_string()),
+                ("tmdb".to_string(), "603".to_string()),
+                ("wikidata".to_string(), "Q83495".to_string()),
+            ]
+        );
+        let log = job_log::list_for_media(&harness.pool, "movie", harness.movie_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(log[0].query.as_deref(), Some("find:tt0133093"));
+    }
+
+    #[tokio::test]
+    async fn hand_linked_title_keeps_its_provider_when_the_mode_drops_it() {
+        let server = FakeServer::with_provider_fixtures().await;
+        server.route(
+            "GET",
+            "/3/find/tt0133093",
+            FakeResponse::json(r#"{ "movie_results": [{ "id": 603 }], "tv_results": [] }"#),
+        );
+        let harness = harness(&server, "tmdb_only").await;
+        let mut conn = harness.pool.acquire().await.unwrap();
+        queries::link_in_tx(&mut conn, "movie", harness.movie_id, "imdb", "tt0133093")
+            .await
+            .unwrap();
+        drop(conn);
+        run_next(&harness).await;
+        // The IMDB fetch records the title's IMDB id, which tmdb_only
+        // could find it by.
+        assert!(!external_ids_of(&harness.pool, harness.movie_id).await.is_empty());
+
+        queries::force_enqueue(&harness.pool, "movie", harness.movie_id).await.unwrap();
+        run_next(&harness).await;
+
+        let (provider, provider_id): (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT provider, provider_id FROM movies WHERE id = ?1")
+                .bind(harness.movie_id)
+                .fetch_one(&harness.pool)
+                .await
+                .unwrap();
+        assert_eq!((provider.as_deref(), provider_id.as_deref()), (Some("imdb"), Some("tt0133093")));
+        assert_eq!(server.hits("/3/search/movie"), 0);
+    }
+
+    #[tokio::test]
+    async fn switching_to_imdb_fetches_a_tmdb_linked_title_by_its_imdb_id() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "tmdb_only").await;
+        run_next(&harness).await;
+
+        app_queries::set_app_setting(&harness.pool, "metadata_mode", "imdb_only").await.unwrap();
+        queries::force_enqueue(&harness.pool, "movie", harness.movie_id).await.unwrap();
+        run_next(&harness).await;
+
+        let provider_id: Option<String> =
+            sqlx::query_scalar("SELECT provider_id FROM movies WHERE id = ?1")
+                .bind(harness.movie_id)
+                .fetch_one(&harness.pool)
+                .await
+                .unwrap();
+        assert_eq!(provider_id.as_deref(), Some("tt0133093"));
+        assert_eq!(server.hits("/suggestion/t/the_matrix_1999.json"), 0);
+        let log = job_log::list_for_media(&harness.pool, "movie", harness.movie_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(log[0].query.as_deref(), Some("external:tt0133093"));
+    }
+
+    #[tokio::test]
+    async fn tmdb_movie_match_records_its_collection() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "tmdb_only").await;
+
+        run_next(&harness).await;
+
+        let listed = collections::list(&harness.pool).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        let collection = &listed[0];
+        assert_eq!(collection.name, "The Matrix Collection");
+        assert_eq!((collection.owned_count, collection.part_count), (1, 4));
+        assert_eq!(collection.parts[0].movie_id, Some(harness.movie_id));
+        let poster = harness
+            .posters_dir
+            .join(collection.poster_path.as_deref().expect("collection poster"));
+        assert_eq!(std::fs::read(poster).unwrap(), POSTER_BYTES);
+    }
+
+    #[tokio::test]
+    async fn imdb_movie_is_matched_applied_and_poster_downloaded() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "imdb_only").await;
+
+        run_next(&harness).await;
+
+        let (provider, provider_id, poster_path): (Option<String>, Option<String>, Option<String>) =
+            sqlx::query_as("SELECT provider, provider_id, poster_path FROM movies WHERE id = ?1")
+                .bind(harness.movie_id)
+                .fetch_one(&harness.pool)
+                .await
+                .unwrap();
+        assert_eq!(provider.as_deref(), Some("imdb"));
+        assert_eq!(provider_id.as_deref(), Some("tt0133093"));
+
+        let poster = harness.posters_dir.join(poster_path.expect("poster path"));
+        assert_eq!(std::fs::read(poster).unwrap(), POSTER_BYTES);
+        assert_eq!(server.hits("/images/M/matrix@._V1_SX500_.jpg"), 1);
+        assert_poster_variants(&harness).await;
+        assert_eq!(server.hits("/images/M/matrix@._V1_QL90_UX1280_.jpg"), 0);
+        assert_backdrop(&harness, "imdb").await;
+        assert_eq!(server.hits("/images/M/matrix-still@._V1_QL90_UX1280_.jpg"), 1);
+
+        let credits = people::for_title(&harness.pool, "movie", harness.movie_id)
+            .await
+            .unwrap();
+        let names: Vec<(&str, &str)> = credits
+            .iter()
+            .map(|credit| (credit.role.as_str(), credit.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("cast", "Keanu Reeves"),
+                ("cast", "Laurence Fishburne"),
+                ("director", "Lana Wachowski"),
+            ]
+        );
+        assert_eq!(
+            certifications_of(&harness.pool, harness.movie_id).await,
+            vec![("GB".to_string(), "15".to_string()), ("US".to_string(), "R".to_string())]
+        );
+    }
+
+    async fn assert_backdrop(harness: &Harness, provider: &str) {
+        let (path, origin): (Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT backdrop_path, backdrop_origin FROM movies WHERE id = ?1",
+        )
+        .bind(harness.movie_id)
+        .fetch_one(&harness.pool)
+        .await
+        .unwrap();
+        let filename = format!("movie-{}-backdrop.jpg", harness.movie_id);
+        assert_eq!(path.as_deref(), Some(filename.as_str()));
+        assert_eq!(origin.as_deref(), Some(provider));
+        assert_eq!(std::fs::read(harness.posters_dir.join(filename)).unwrap(), POSTER_BYTES);
+    }
+
+    async fn assert_poster_variants(harness: &Harness) {
+        let (thumb, card, hero, blurhash): (
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        ) = sqlx::query_as(
+            "SELECT poster_thumb_path, poster_card_path, poster_hero_path, poster_blurhash
+             FROM movies WHERE id = ?1",
+        )
+        .bind(harness.movie_id)
+        .fetch_one(&harness.pool)
+        .await
+        .unwrap();
+        let filename = format!("movie-{}", harness.movie_id);
+        assert_eq!(
+            (thumb.clone(), card.clone(), hero.clone()),
+            (
+                Some(format!("{filename}.thumb.jpg")),
+                Some(format!("{filename}.card.jpg")),
+                Some(format!("{filename}.hero.jpg")),
+            )
+        );
+        assert!(blurhash.is_some());
+        // Rendered from the downloaded poster, not fetched again.
+        let poster = image::load_from_memory(POSTER_BYTES).unwrap();
+        for variant in [thumb, card, hero].into_iter().flatten() {
+            let rendered = image::open(harness.posters_dir.join(variant)).unwrap();
+            assert_eq!(
+                (rendered.width(), rendered.height()),
+                (poster.width(), poster.height())
+            );
+        }
+    }
+
+    async fn certifications_of(pool: &SqlitePool, movie_id: i64) -> Vec<(String, String)> {
+        sqlx::query_as(
+            "SELECT country, certification FROM certifications
+             WHERE kind = 'movie' AND media_id = ?1 ORDER BY country",
+        )
+        .bind(movie_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn tmdb_auth_failure_parks_the_job() {
+        let server = FakeServer::with_provider_fixtures().await;
+        server.route("GET", "/3/search/movie", FakeResponse::status(401));
+        let harness = harness(&server, "tmdb_only").await;
+
+        run_next(&harness).await;
+
+        let (attempts, last_error) = job_row(&harness.pool, harness.movie_id)
+            .await
+            .expect("job kept");
+        assert_eq!(attempts, 0);
+        assert_eq!(last_error.as_deref(), Some("tmdb_auth_required"));
+        assert_eq!(
+            app_queries::get_app_setting(&harness.pool, "tmdb_auth_bad")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("1")
+        );
+    }
+
+    #[tokio::test]
+    async fn throttled_requests_are_deferred_without_an_attempt() {
+        let server = FakeServer::with_provider_fixtures().await;
+        server.route(
+            "GET",
+            "/3/search/movie",
+            FakeResponse::status(429).with_header("Retry-After", "0"),
+        );
+        let harness = harness(&server, "tmdb_only").await;
+
+        run_next(&harness).await;
+
+        let (attempts, last_error) = job_row(&harness.pool, harness.movie_id)
+            .await
+            .expect("job kept");
+        assert_eq!(attempts, 0);
+        assert!(last_error.unwrap().starts_with("rate_limited"));
+        let log = job_log::list_for_media(&harness.pool, "movie", harness.movie_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(log[0].outcome, job_log::OUTCOME_RATE_LIMITED);
+        assert_eq!(server.hits("/3/search/movie"), 1);
+        assert!(queries::claim_next(&harness.pool, CLAIM_TIMEOUT_SECS)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn missing_poster_still_applies_metadata() {
+        let server = FakeServer::with_provider_fixtures().await;
+        server.route(
+            "GET",
+            "/t/p/w500/f89U3ADr1oiB1s9GkdPOEpXUk5H.jpg",
+            FakeResponse::status(404),
+        );
+        let harness = harness(&server, "tmdb_only").await;
+
+        run_next(&harness).await;
+
+        let overview: Option<String> =
+            sqlx::query_scalar("SELECT overview FROM movies WHERE id = ?1")
+                .bind(harness.movie_id)
+                .fetch_one(&harness.pool)
+                .await
+                .unwrap();
+        assert!(overview.unwrap().contains("computer hacker"));
+        assert!(job_row(&harness.pool, harness.movie_id).await.is_none());
+        let poster = harness.posters_dir.join(format!("movie-{}.jpg", harness.movie_id));
+        assert!(!poster.exists());
+    }
+
+    #[tokio::test]
+    async fn unmatched_search_keeps_candidates_for_review() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "tmdb_only").await;
+        sqlx::query("UPDATE movies SET title = 'Matrix Revisited' WHERE id = ?1")
+            .bind(harness.movie_id)
+            .execute(&harness.pool)
+            .await
+            .unwrap();
+
+        run_next(&harness).await;
+
+        let review = app_queries::list_needs_review(&harness.pool).await.unwrap();
+        assert_eq!(review.len(), 1);
+        let candidates = &review[0].candidates;
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].provider_id, "603");
+        assert!(candidates[0].score >= candidates[1].score);
+        assert!(candidates[0].poster_url.as_deref().unwrap().ends_with(".jpg"));
+        assert!(candidates[0].overview.is_some());
+
+        let mut conn = harness.pool.acquire().await.unwrap();
+        queries::link_in_tx(&mut conn, "movie", harness.movie_id, "tmdb", "603")
+            .await
+            .unwrap();
+        drop(conn);
+        assert!(app_queries::list_needs_review(&harness.pool).await.unwrap().is_empty());
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM review_candidates")
+            .fetch_one(&harness.pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+
+        let aliases = aliases::list(&harness.pool).await.unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].normalized_title, "matrix revisited");
+        assert_eq!(aliases[0].provider_id, "603");
+    }
+
+    #[tokio::test]
+    async fn aliased_title_is_fetched_by_id_without_searching() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "tmdb_only").await;
+        // A scanned title no search would match, aliased by an earlier link.
+        sqlx::query("UPDATE movies SET title = 'Matrix Revisited' WHERE id = ?1")
+            .bind(harness.movie_id)
+            .execute(&harness.pool)
+            .await
+            .unwrap();
+        let mut conn = harness.pool.acquire().await.unwrap();
+        aliases::record_in_tx(&mut conn, "movie", "matrix revisited", Some(1999), "tmdb", "603")
+            .await
+            .unwrap();
+        drop(conn);
+
+        run_next(&harness).await;
+
+        assert_eq!(server.hits("/3/search/movie"), 0);
+        assert_eq!(server.hits("/3/movie/603"), 1);
+        let provider_id: Option<String> =
+            sqlx::query_scalar("SELECT provider_id FROM movies WHERE id = ?1")
+                .bind(harness.movie_id)
+                .fetch_one(&harness.pool)
+                .await
+                .unwrap();
+        assert_eq!(provider_id.as_deref(), Some("603"));
+        let log = job_log::list_for_media(&harness.pool, "movie", harness.movie_id, 5)
+            .await
+            .unwrap();
+        assert_eq!(log[0].query.as_deref(), Some("alias:603"));
+    }
+
+    #[tokio::test]
+    async fn unlinked_title_is_searched_again_instead_of_following_its_alias() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "tmdb_only").await;
+        let mut conn = harness.pool.acquire().await.unwrap();
+        queries::link_in_tx(&mut conn, "movie", harness.movie_id, "tmdb", "603")
+            .await
+            .unwrap();
+        drop(conn);
+        run_next(&harness).await;
+
+        let mut conn = harness.pool.acquire().await.unwrap();
+        queries::unlink_in_tx(&mut conn, "movie", harness.movie_id).await.unwrap();
+        drop(conn);
+        run_next(&harness).await;
+
+        assert!(aliases::list(&harness.pool).await.unwrap().is_empty());
+        assert_eq!(server.hits("/3/search/movie"), 1);
+        let log = job_log::list_for_media(&harness.pool, "movie", harness.movie_id, 5)
+            .await
+            .unwrap();
+        let query = log[0].query.as_deref().unwrap_or_default();
+        assert!(!query.starts_with("alias:"), "{query}");
+    }
+
+    #[tokio::test]
+    async fn scrape_language_is_sent_and_missing_overview_falls_back_to_english() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let mut german: serde_json::Value =
+            serde_json::from_str(crate::metadata::fake_server::TMDB_MOVIE_DETAILS).unwrap();
+        german["title"] = "Matrix (DE)".into();
+        german["overview"] = "".into();
+        server.route(
+            "GET",
+            "/3/movie/603?language=de-DE",
+            FakeResponse::json(german.to_string()),
+        );
+        let harness = harness(&server, "tmdb_only").await;
+        app_queries::set_app_setting(&harness.pool, "scrape_language", "de-DE")
+            .await
+            .unwrap();
+
+        run_next(&harness).await;
+
+        let searches: Vec<_> = server
+            .requests()
+            .into_iter()
+            .filter(|request| request.path() == "/3/search/movie")
+            .collect();
+        assert!(searches[0].query_pairs().contains(&"language=de-DE"));
+        assert_eq!(server.hits("/3/movie/603"), 2);
+
+        let movie = app_queries::get_movie(&harness.pool, harness.movie_id).await.unwrap();
+        assert_eq!(movie.localized_title.as_deref(), Some("Matrix (DE)"));
+        assert!(movie.overview.as_deref().is_some_and(|text| !text.is_empty()));
+
+        let stored: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT language, title, overview FROM localized_metadata ORDER BY language",
+        )
+        .fetch_all(&harness.pool)
+        .await
+        .unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].0, "de-DE");
+        assert_eq!(stored[0].2, None);
+        assert_eq!(stored[1].0, "en");
+        assert_eq!(stored[1].2, movie.overview);
+    }
+
+    #[tokio::test]
+    async fn a_job_that_errors_is_backed_off_before_its_claim_is_released() {
+        let server = FakeServer::with_provider_fixtures().await;
+        let harness = harness(&server, "prefer_tmdb").await;
+        let job = queries::claim_next(&harness.pool, CLAIM_TIMEOUT_SECS)
+            .await
+            .unwrap()
+            .expect("a due job");
+        // Reading the settings is the first thing a job does.
+        sqlx::query("DROP TABLE app_settings").execute(&harness.pool).await.unwrap();
+
+        run_claimed(&harness.pool, &harness.http, &harness.posters_dir, &job).await;
+
+        let (attempts, last_error) = job_row(&harness.pool, harness.movie_id).await.unwrap();
+        assert_eq!(attempts, 1);
+        assert!(last_error.is_some_and(|error| error.contains("app_settings")));
+        let next = queries::claim_next(&harness.pool, CLAIM_TIMEOUT_SECS).await.unwrap();
+        assert!(next.is_none(), "the failed job must not be due again at once");
+    }
+}
@@ -20,10 +20,21 @@ pub enum LibraryKind {
 pub struct Movie {
     pub id: i64,
     pub title: String,
+    /// Provider title in `scrape_language` (English if it has none).
+    pub localized_title: Option<String>,
     pub year: Option<i32>,
     pub path: String,
     pub poster_path: Option<String>,
     pub poster_origin: Option<String>,
+    /// Poster resized for small grids, cards and detail pages; `None` for
+    /// folder posters and until rendered.
+    pub poster_thumb_path: Option<String>,
+    pub poster_card_path: Option<String>,
+    pub poster_hero_path: Option<String>,
+    /// Placeholder shown while the poster loads, rendered with the variants.
+    pub poster_blurhash: Option<String>,
+    pub backdrop_path: Option<String>,
+    pub backdrop_origin: Option<String>,
     pub overview: Option<String>,
     pub duration_seconds: Option<i64>,
     pub progress_seconds: i64,
@@ -44,11 +55,22 @@ pub struct Show {
     pub id: i64,
     pub library_id: i64,
     pub title: String,
+    /// Provider title in `scrape_language` (English if it has none).
+    pub localized_title: Option<String>,
     pub year: Option<i32>,
     pub folder_path: String,
     pub fingerprint: String,
     pub poster_path: Option<String>,
     pub poster_origin: Option<String>,
+    /// Poster resized for small grids, cards and detail pages; `None` for
+    /// folder posters and until rendered.
+    pub poster_thumb_path: Option<String>,
+    pub poster_card_path: Option<String>,
+    pub poster_hero_path: Option<String>,
+    /// Placeholder shown while the poster loads, rendered with the variants.
+    pub poster_blurhash: Option<String>,
+    pub backdrop_path: Option<String>,
+    pub backdrop_origin: Option<String>,
     pub overview: Option<String>,
     pub episode_count: i64,
     pub watched_count: i64,
@@ -59,6 +81,12 @@ pub struct Show {
     pub genres: Option<String>,
     pub top_cast: Option<String>,
     pub first_air_date: Option<String>,
+    /// Provider airing status ("Returning Series", "Ended", …).
+    pub status: Option<String>,
+    pub end_year: Option<i32>,
+    /// JSON array of network names.
+    pub networks: Option<String>,
+    pub next_episode_air_date: Option<String>,
     pub metadata_synced_at: Option<i64>,
     pub metadata_locked: i64,
 }
@@ -85,8 +113,8 @@ pub struct Season {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "kind", rename_all = "lowercase")]
 pub enum ContinueWatchingItem {
-    Movie { movie: Movie },
-    Episode { show: Show, episode: Episode },
+    Movie { movie: Box<Movie> },
+    Episode { show: Box<Show>, episode: Episode },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -120,4 +148,20 @@ pub struct MetadataStatusCounts {
     pub no_provider_available: i64,
     pub dead_letter: i64,
     pub needs_review: i64,
+    /// Jobs pushed back because a provider throttled them. Not counted as
+    /// failures — `attempts` is untouched.
+    pub rate_limited: i64,
+    pub rate_limits: Vec<RateLimitStatus>,
+}
+
+/// Snapshot of one provider's token bucket.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RateLimitStatus {
+    pub provider: crate::metadata::dispatch::Provider,
+    pub capacity: f64,
+    pub available: f64,
+    pub refill_per_second: f64,
+    /// Unix seconds until which the provider asked us to back off.
+    pub blocked_until: Option<i64>,
+    pub throttle_count: u64,
 }
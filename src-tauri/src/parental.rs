@@ -0,0 +1,312 @@
+//! Parental controls. A single PIN-protected row in `parental_controls`
+//! sets the highest certification allowed; titles above it (in the
+//! ratings of the country the limit was set in) are left out of every
+//! library listing and can't be played. Filtering happens in SQL via
+//! `visible_clause`, so every query that lists titles applies the same
+//! rule without loading the settings first.
+
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use crate::metadata::certifications;
+
+/// What the settings page shows. Never includes the PIN.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ParentalControls {
+    pub enabled: bool,
+    pub max_rating: Option<String>,
+    pub max_age: Option<i64>,
+    pub hide_unrated: bool,
+    /// Country `max_rating` is read in: the one it was set in while the
+    /// controls are on, else `certification_country`.
+    pub country: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct Row {
+    country: String,
+    max_rating: String,
+    max_age: i64,
+    hide_unrated: bool,
+    pin_salt: String,
+    pin_hash: String,
+}
+
+/// SQL predicate that is true when the title `{alias}.id` of `kind`
+/// ('movie' or 'show') may be shown. Episodes go by their show.
+pub fn visible_clause(kind: &str, alias: &str) -> String {
+    format!(
+        "NOT EXISTS (
+           SELECT 1 FROM parental_controls pc
+           WHERE COALESCE(
+             (SELECT c.min_age FROM certifications c
+               WHERE c.kind = '{kind}' AND c.media_id = {alias}.id
+                 AND c.country = pc.country),
+             CASE WHEN pc.hide_unrated = 1 THEN 1000 ELSE -1 END
+           ) > pc.max_age
+         )"
+    )
+}
+
+pub async fn get(pool: &SqlitePool) -> AppResult<ParentalControls> {
+    let row = load(pool).await?;
+    let country = match row.as_ref() {
+        Some(row) => row.country.clone(),
+        None => certifications::country(pool).await?,
+    };
+
+    Ok(ParentalControls {
+        enabled: row.is_some(),
+        max_rating: row.as_ref().map(|row| row.max_rating.clone()),
+        max_age: row.as_ref().map(|row| row.max_age),
+        hide_unrated: row.as_ref().is_some_and(|row| row.hide_unrated),
+        country,
+    })
+}
+
+/// Turns parental controls on or changes them. Once they're on,
+/// `current_pin` must match; `pin` becomes the PIN from then on.
+/// `max_rating` is a certification in the `certification_country`
+/// ("PG-13") or a bare age ("12"); that country is locked in until the
+/// controls are set again.
+pub async fn set(
+    pool: &SqlitePool,
+    current_pin: Option<&str>,
+    pin: &str,
+    max_rating: &str,
+    hide_unrated: bool,
+) -> AppResult<ParentalControls> {
+    if let Some(row) = load(pool).await? {
+        verify(&row, current_pin.unwrap_or_default())?;
+    }
+    validate_pin(pin)?;
+
+    let country = certifications::country(pool).await?;
+    let max_rating = max_rating.trim();
+    let max_age = certifications::min_age(&country, max_rating).ok_or_else(|| {
+        AppError::Other(format!("unknown certification for {country}: '{max_rating}'"))
+    })?;
+
+    let salt = new_salt()?;
+    sqlx::query(
+        "INSERT INTO parental_controls
+             (id, country, max_rating, max_age, hide_unrated, pin_salt, pin_hash)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(id) DO UPDATE SET
+             country = excluded.country,
+             max_rating = excluded.max_rating,
+             max_age = excluded.max_age,
+             hide_unrated = excluded.hide_unrated,
+             pin_salt = excluded.pin_salt,
+             pin_hash = excluded.pin_hash,
+             updated_at = strftime('%s','now')",
+    )
+    .bind(&country)
+    .bind(max_rating)
+    .bind(max_age)
+    .bind(hide_unrated)
+    .bind(&salt)
+    .bind(hash_pin(&salt, pin))
+    .execute(pool)
+    .await?;
+
+    get(pool).await
+}
+
+/// Turns parental controls off and forgets the PIN.
+pub async fn disable(pool: &SqlitePool, pin: &str) -> AppResult<()> {
+    let Some(row) = load(pool).await? else {
+        return Ok(());
+    };
+    verify(&row, pin)?;
+
+    sqlx::query("DELETE FROM parental_controls").execute(pool).await?;
+    Ok(())
+}
+
+/// Errors with `ParentalBlocked` when `kind` ('movie' or 'episode') `id`
+/// is hidden by parental controls.
+pub async fn ensure_playable(pool: &SqlitePool, kind: &str, id: i64) -> AppResult<()> {
+    let sql = match kind {
+        "movie" => format!(
+            "SELECT COUNT(*) FROM movies m WHERE m.id = ?1 AND {}",
+            visible_clause("movie", "m")
+        ),
+        "episode" => format!(
+            "SELECT COUNT(*) FROM episodes e JOIN shows s ON s.id = e.show_id
+             WHERE e.id = ?1 AND {}",
+            visible_clause("show", "s")
+        ),
+        other => return Err(AppError::Other(format!("unknown kind: {other}"))),
+    };
+    let visible: i64 = sqlx::query_scalar(&sql).bind(id).fetch_one(pool).await?;
+
+    if visible == 0 {
+        return Err(AppError::ParentalBlocked);
+    }
+    Ok(())
+}
+
+async fn load(pool: &SqlitePool) -> AppResult<Option<Row>> {
+    let row = sqlx::query_as(
+        "SELECT country, max_rating, max_age, hide_unrated, pin_salt, pin_hash
+         FROM parental_controls WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+fn verify(row: &Row, pin: &str) -> AppResult<()> {
+    if hash_pin(&row.pin_salt, pin.trim()) != row.pin_hash {
+        return Err(AppError::WrongPin);
+    }
+    Ok(())
+}
+
+fn validate_pin(pin: &str) -> AppResult<()> {
+    if !(4..=8).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::Other("PIN must be 4 to 8 digits".into()));
+    }
+    Ok(())
+}
+
+fn new_salt() -> AppResult<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|error| AppError::Other(format!("random salt: {error}")))?;
+    Ok(to_hex(&bytes))
+}
+
+fn hash_pin(salt: &str, pin: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(pin.as_bytes());
+    to_hex(&hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    async fn seed(pool: &SqlitePool) {
+        sqlx::query(
+            "INSERT INTO libraries (id, path, kind) VALUES (1, '/movies', 'movies'), (2, '/tv', 'series')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO movies (id, library_id, title, path) VALUES
+                 (1, 1, 'Cartoon', '/movies/1.mkv'),
+                 (2, 1, 'Thriller', '/movies/2.mkv'),
+                 (3, 1, 'Home Video', '/movies/3.mkv')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO shows (id, library_id, title, folder_path, fingerprint)
+             VALUES (1, 2, 'Drama', '/tv/Drama', 'fp')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO episodes (id, show_id, season, episode, title, path)
+             VALUES (1, 1, 1, 1, 'Pilot', '/tv/Drama/s01e01.mkv')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO certifications (kind, media_id, country, certification, min_age) VALUES
+                 ('movie', 1, 'US', 'G', 0),
+                 ('movie', 2, 'US', 'R', 17),
+                 ('movie', 2, 'DE', '12', 12),
+                 ('show', 1, 'US', 'TV-MA', 17)",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn visible_movies(pool: &SqlitePool) -> Vec<i64> {
+        crate::queries::list_movies(pool, &[], &Default::default())
+            .await
+            .unwrap()
+            .iter()
+            .map(|movie| movie.id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn titles_above_the_limit_are_hidden_and_blocked() {
+        let pool = fresh_pool().await;
+        seed(&pool).await;
+        assert!(!get(&pool).await.unwrap().enabled);
+
+        set(&pool, None, "1234", "PG-13", false).await.unwrap();
+        assert_eq!(visible_movies(&pool).await, vec![1, 3]);
+        let shows = crate::queries::list_shows(&pool, &[], &Default::default()).await.unwrap();
+        assert!(shows.is_empty());
+        assert!(matches!(
+            ensure_playable(&pool, "movie", 2).await,
+            Err(AppError::ParentalBlocked)
+        ));
+        assert!(matches!(
+            ensure_playable(&pool, "episode", 1).await,
+            Err(AppError::ParentalBlocked)
+        ));
+        ensure_playable(&pool, "movie", 3).await.unwrap();
+
+        // Changing the country alone doesn't move the limit; setting the
+        // controls again reads ratings in the new one.
+        crate::queries::set_app_setting(&pool, "certification_country", "DE").await.unwrap();
+        assert_eq!(visible_movies(&pool).await, vec![1, 3]);
+        set(&pool, Some("1234"), "1234", "12", true).await.unwrap();
+        assert_eq!(visible_movies(&pool).await, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn changes_need_the_current_pin() {
+        let pool = fresh_pool().await;
+        seed(&pool).await;
+
+        assert!(set(&pool, None, "12", "PG", false).await.is_err());
+        assert!(set(&pool, None, "1234", "XYZ", false).await.is_err());
+        set(&pool, None, "1234", "PG", false).await.unwrap();
+
+        assert!(matches!(
+            set(&pool, None, "9999", "R", false).await,
+            Err(AppError::WrongPin)
+        ));
+        assert!(matches!(disable(&pool, "0000").await, Err(AppError::WrongPin)));
+        set(&pool, Some("1234"), "5678", "R", false).await.unwrap();
+        assert_eq!(get(&pool).await.unwrap().max_age, Some(17));
+
+        disable(&pool, "5678").await.unwrap();
+        assert!(!get(&pool).await.unwrap().enabled);
+        assert_eq!(visible_movies(&pool).await, vec![1, 3, 2]);
+    }
+}
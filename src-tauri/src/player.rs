@@ -1,12 +1,14 @@
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use sqlx::SqlitePool;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
 use crate::error::{AppError, AppResult};
+use crate::history::{self, NewSession};
 use crate::queries;
 
 static SESSION_COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -50,7 +52,15 @@ pub async fn check_mpv() -> AppResult<()> {
     }
 }
 
-/// Spawn mpv, await its exit, then persist the final playback position.
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Spawn mpv, await its exit, then persist the final playback position
+/// and append the run to the session log.
 ///
 /// We use mpv's `--term-status-msg` to emit `POS=<seconds> DUR=<seconds>` lines
 /// that we tail on stdout. The final values are what get written back to the DB.
@@ -71,13 +81,15 @@ pub async fn play(
         .arg("--really-quiet")
         .arg("--term-status-msg=POS=${=time-pos} DUR=${=duration}");
 
-    if resume_seconds > 5 {
-        cmd.arg(format!("--start=+{}", resume_seconds));
+    let start_position = if resume_seconds > 5 { resume_seconds } else { 0 };
+    if start_position > 0 {
+        cmd.arg(format!("--start=+{}", start_position));
     }
 
     cmd.stdout(Stdio::piped()).stderr(Stdio::null());
 
     let mut child = cmd.spawn().map_err(|_| AppError::MpvMissing)?;
+    let started_at = unix_now();
     let stdout = child.stdout.take();
 
     let parse_task = tokio::spawn(async move {
@@ -126,9 +138,21 @@ pub async fn play(
     };
 
     queries::upsert_progress(pool, kind, media_id, final_pos, final_dur, watched).await?;
+    let session = NewSession {
+        media_kind: kind,
+        media_id,
+        started_at,
+        ended_at: unix_now(),
+        start_position_seconds: start_position,
+        end_position_seconds: final_pos,
+        duration_seconds: final_dur,
+        completed: watched,
+        exit_status: wait_result.as_ref().ok().and_then(|status| status.code()),
+    };
+    history::record(pool, &session).await?;
 
-    // Surface the child wait error after we've already persisted progress,
-    // so a failed reap doesn't drop the last-known position.
+    // Surface the child wait error after we've already persisted progress
+    // and the session, so a failed reap doesn't drop the last-known position.
     wait_result?;
 
     Ok(PlayResult { session_id })
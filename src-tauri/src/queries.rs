@@ -1,10 +1,12 @@
 use sqlx::SqlitePool;
 
 use crate::error::{AppError, AppResult};
+use crate::metadata::{genres, ratings};
 use crate::models::{
     ContinueWatchingItem, Episode, EpisodeRef, Library, LibraryKind, MergeOutcome, Movie, Season,
     Show,
 };
+use crate::parental;
 
 pub async fn list_libraries(pool: &SqlitePool) -> AppResult<Vec<Library>> {
     let rows = sqlx::query_as::<_, Library>("SELECT id, path, kind FROM libraries ORDER BY id")
@@ -42,27 +44,90 @@ pub async fn remove_library(pool: &SqlitePool, id: i64) -> AppResult<()> {
     Ok(())
 }
 
-const MOVIE_SELECT: &str = "
-    SELECT m.id, m.title, m.year, m.path, m.poster_path, m.poster_origin, m.overview,
+// `localized_title`: the provider title in `scrape_language`, else English.
+// `rating`: SQL for the rating column, `m.rating` unless a list picks a
+// source.
+fn movie_select(rating: &str) -> String {
+    format!(
+        "
+    SELECT m.id, m.title,
+           COALESCE(
+             (SELECT l.title FROM localized_metadata l
+               WHERE l.kind = 'movie' AND l.media_id = m.id
+                 AND l.language = COALESCE(
+                       (SELECT value FROM app_settings WHERE key = 'scrape_language'), 'en')),
+             (SELECT l.title FROM localized_metadata l
+               WHERE l.kind = 'movie' AND l.media_id = m.id AND l.language = 'en')
+           ) AS localized_title,
+           m.year, m.path, m.poster_path, m.poster_origin,
+           m.poster_thumb_path, m.poster_card_path, m.poster_hero_path, m.poster_blurhash,
+           m.backdrop_path, m.backdrop_origin, m.overview,
            m.duration_seconds,
            COALESCE(w.progress_seconds, 0) AS progress_seconds,
            COALESCE(w.watched, 0) AS watched,
            m.added_at,
-           m.provider, m.provider_id, m.rating, m.genres, m.top_cast,
+           m.provider, m.provider_id, {rating} AS rating, m.genres, m.top_cast,
            m.runtime_minutes, m.metadata_synced_at, m.metadata_locked
     FROM movies m
     LEFT JOIN watch_history w
       ON w.media_kind = 'movie' AND w.media_id = m.id
-";
+"
+    )
+}
 
-pub async fn list_movies(pool: &SqlitePool) -> AppResult<Vec<Movie>> {
-    let sql = format!("{MOVIE_SELECT} ORDER BY m.title COLLATE NOCASE");
-    let rows = sqlx::query_as::<_, Movie>(&sql).fetch_all(pool).await?;
+/// Which rating list queries show and sort by.
+#[derive(Debug, Clone, Default)]
+pub struct RatingView {
+    /// `ratings.source` shown as `rating`; the linked provider's when
+    /// `None`.
+    pub source: Option<String>,
+    /// Best rated first (unrated last) instead of by title.
+    pub sort_by_rating: bool,
+}
+
+impl RatingView {
+    /// The rating column and ORDER BY for a list of `kind` aliased
+    /// `alias`. The source, if any, binds after the genre slugs.
+    fn sql(&self, kind: &str, alias: &str, genres: &[String]) -> (String, String) {
+        let rating = match self.source {
+            Some(_) => ratings::value_expr(kind, alias, genres.len() + 1),
+            None => format!("{alias}.rating"),
+        };
+        let order = if self.sort_by_rating {
+            format!("rating IS NULL, rating DESC, {alias}.title COLLATE NOCASE")
+        } else {
+            format!("{alias}.title COLLATE NOCASE")
+        };
+        (rating, order)
+    }
+}
+
+/// Every movie parental controls allow, or only those tagged with all
+/// of `genres` (slugs).
+pub async fn list_movies(
+    pool: &SqlitePool,
+    genres: &[String],
+    view: &RatingView,
+) -> AppResult<Vec<Movie>> {
+    let (rating, order) = view.sql("movie", "m", genres);
+    let sql = format!(
+        "{} {} ORDER BY {order}",
+        movie_select(&rating),
+        list_where("movie", "m", genres)?
+    );
+    let mut query = sqlx::query_as::<_, Movie>(&sql);
+    for slug in genres {
+        query = query.bind(slug);
+    }
+    if let Some(source) = &view.source {
+        query = query.bind(source);
+    }
+    let rows = query.fetch_all(pool).await?;
     Ok(rows)
 }
 
 pub async fn get_movie(pool: &SqlitePool, id: i64) -> AppResult<Movie> {
-    let sql = format!("{MOVIE_SELECT} WHERE m.id = ?1");
+    let sql = format!("{} WHERE m.id = ?1", movie_select("m.rating"));
     let row = sqlx::query_as::<_, Movie>(&sql)
         .bind(id)
         .fetch_optional(pool)
@@ -70,28 +135,71 @@ pub async fn get_movie(pool: &SqlitePool, id: i64) -> AppResult<Movie> {
     row.ok_or(AppError::MediaNotFound(id))
 }
 
-const SHOW_SELECT: &str = "
-    SELECT s.id, s.library_id, s.title, s.year, s.folder_path, s.fingerprint,
-           s.poster_path, s.poster_origin, s.overview,
+// `localized_title` and `rating` as in `movie_select`.
+fn show_select(rating: &str) -> String {
+    format!(
+        "
+    SELECT s.id, s.library_id, s.title,
+           COALESCE(
+             (SELECT l.title FROM localized_metadata l
+               WHERE l.kind = 'show' AND l.media_id = s.id
+                 AND l.language = COALESCE(
+                       (SELECT value FROM app_settings WHERE key = 'scrape_language'), 'en')),
+             (SELECT l.title FROM localized_metadata l
+               WHERE l.kind = 'show' AND l.media_id = s.id AND l.language = 'en')
+           ) AS localized_title,
+           s.year, s.folder_path, s.fingerprint,
+           s.poster_path, s.poster_origin,
+           s.poster_thumb_path, s.poster_card_path, s.poster_hero_path, s.poster_blurhash,
+           s.backdrop_path, s.backdrop_origin, s.overview,
            (SELECT COUNT(*) FROM episodes e WHERE e.show_id = s.id) AS episode_count,
            (SELECT COUNT(*) FROM episodes e
               LEFT JOIN watch_history w
                 ON w.media_kind = 'episode' AND w.media_id = e.id
              WHERE e.show_id = s.id AND COALESCE(w.watched, 0) = 1) AS watched_count,
            s.added_at,
-           s.provider, s.provider_id, s.rating, s.genres, s.top_cast,
-           s.first_air_date, s.metadata_synced_at, s.metadata_locked
+           s.provider, s.provider_id, {rating} AS rating, s.genres, s.top_cast,
+           s.first_air_date, s.status, s.end_year, s.networks, s.next_episode_air_date,
+           s.metadata_synced_at, s.metadata_locked
     FROM shows s
-";
+"
+    )
+}
 
-pub async fn list_shows(pool: &SqlitePool) -> AppResult<Vec<Show>> {
-    let sql = format!("{SHOW_SELECT} ORDER BY s.title COLLATE NOCASE");
-    let rows = sqlx::query_as::<_, Show>(&sql).fetch_all(pool).await?;
+/// Every show parental controls allow, or only those tagged with all
+/// of `genres` (slugs).
+pub async fn list_shows(
+    pool: &SqlitePool,
+    genres: &[String],
+    view: &RatingView,
+) -> AppResult<Vec<Show>> {
+    let (rating, order) = view.sql("show", "s", genres);
+    let sql = format!(
+        "{} {} ORDER BY {order}",
+        show_select(&rating),
+        list_where("show", "s", genres)?
+    );
+    let mut query = sqlx::query_as::<_, Show>(&sql);
+    for slug in genres {
+        query = query.bind(slug);
+    }
+    if let Some(source) = &view.source {
+        query = query.bind(source);
+    }
+    let rows = query.fetch_all(pool).await?;
     Ok(rows)
 }
 
+fn list_where(kind: &str, alias: &str, genres: &[String]) -> AppResult<String> {
+    let mut terms = vec![parental::visible_clause(kind, alias)];
+    if !genres.is_empty() {
+        terms.push(genres::filter_clause(kind, genres, 1)?);
+    }
+    Ok(format!("WHERE {}", terms.join(" AND ")))
+}
+
 pub async fn get_show(pool: &SqlitePool, id: i64) -> AppResult<Show> {
-    let sql = format!("{SHOW_SELECT} WHERE s.id = ?1");
+    let sql = format!("{} WHERE s.id = ?1", show_select("s.rating"));
     let row = sqlx::query_as::<_, Show>(&sql)
         .bind(id)
         .fetch_optional(pool)
@@ -168,74 +276,6 @@ pub async fn upsert_progress(
     Ok(())
 }
 
-pub async fn update_show_metadata(
-    pool: &SqlitePool,
-    id: i64,
-    title: Option<&str>,
-    year: Option<i32>,
-    overview: Option<&str>,
-) -> AppResult<()> {
-    update_metadata_row(pool, "shows", id, title, year, overview).await
-}
-
-pub async fn update_movie_metadata(
-    pool: &SqlitePool,
-    id: i64,
-    title: Option<&str>,
-    year: Option<i32>,
-    overview: Option<&str>,
-) -> AppResult<()> {
-    update_metadata_row(pool, "movies", id, title, year, overview).await
-}
-
-/// Shared body for `update_show_metadata` and `update_movie_metadata`. Only
-/// fields passed as `Some` are touched. There's no v1 way to *clear* a field
-/// to NULL through this API; that needs an explicit "reset" command if it
-/// turns out users want it.
-async fn update_metadata_row(
-    pool: &SqlitePool,
-    table: &str,
-    id: i64,
-    title: Option<&str>,
-    year: Option<i32>,
-    overview: Option<&str>,
-) -> AppResult<()> {
-    let mut assignments: Vec<&str> = Vec::new();
-    if title.is_some() {
-        assignments.push("title = ?");
-    }
-    if year.is_some() {
-        assignments.push("year = ?");
-    }
-    if overview.is_some() {
-        assignments.push("overview = ?");
-    }
-    if assignments.is_empty() {
-        return Ok(());
-    }
-
-    let sql = format!(
-        "UPDATE {table} SET {}, metadata_locked = 1 WHERE id = ?",
-        assignments.join(", ")
-    );
-
-    let mut query = sqlx::query(&sql);
-    if let Some(value) = title {
-        query = query.bind(value);
-    }
-    if let Some(value) = year {
-        query = query.bind(value);
-    }
-    if let Some(value) = overview {
-        query = query.bind(value);
-    }
-    let result = query.bind(id).execute(pool).await?;
-    if result.rows_affected() == 0 {
-        return Err(AppError::MediaNotFound(id));
-    }
-    Ok(())
-}
-
 pub async fn set_show_poster(
     pool: &SqlitePool,
     id: i64,
@@ -269,7 +309,13 @@ async fn set_poster_row(
     path: &str,
     origin: &str,
 ) -> AppResult<()> {
-    let sql = format!("UPDATE {table} SET poster_path = ?1, poster_origin = ?2 WHERE id = ?3");
+    // The previous poster's variants go until the new one is rendered.
+    let sql = format!(
+        "UPDATE {table} SET poster_path = ?1, poster_origin = ?2,
+             poster_thumb_path = NULL, poster_card_path = NULL, poster_hero_path = NULL,
+             poster_blurhash = NULL
+         WHERE id = ?3"
+    );
     let result = sqlx::query(&sql)
         .bind(path)
         .bind(origin)
@@ -283,7 +329,12 @@ async fn set_poster_row(
 }
 
 async fn reset_poster_row(pool: &SqlitePool, table: &str, id: i64) -> AppResult<()> {
-    let sql = format!("UPDATE {table} SET poster_path = NULL, poster_origin = NULL WHERE id = ?1");
+    let sql = format!(
+        "UPDATE {table} SET poster_path = NULL, poster_origin = NULL,
+             poster_thumb_path = NULL, poster_card_path = NULL, poster_hero_path = NULL,
+             poster_blurhash = NULL
+         WHERE id = ?1"
+    );
     let result = sqlx::query(&sql).bind(id).execute(pool).await?;
     if result.rows_affected() == 0 {
         return Err(AppError::MediaNotFound(id));
@@ -291,6 +342,42 @@ async fn reset_poster_row(pool: &SqlitePool, table: &str, id: i64) -> AppResult<
     Ok(())
 }
 
+/// Uploaded backdrops are `manual`; sync leaves them alone from then on.
+pub async fn set_show_backdrop(pool: &SqlitePool, id: i64, path: &str) -> AppResult<()> {
+    set_backdrop_row(pool, "shows", id, Some(path)).await
+}
+
+pub async fn set_movie_backdrop(pool: &SqlitePool, id: i64, path: &str) -> AppResult<()> {
+    set_backdrop_row(pool, "movies", id, Some(path)).await
+}
+
+/// Clears the backdrop; the next sync fills in the provider's.
+pub async fn reset_show_backdrop(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    set_backdrop_row(pool, "shows", id, None).await
+}
+
+pub async fn reset_movie_backdrop(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    set_backdrop_row(pool, "movies", id, None).await
+}
+
+async fn set_backdrop_row(
+    pool: &SqlitePool,
+    table: &str,
+    id: i64,
+    path: Option<&str>,
+) -> AppResult<()> {
+    let sql = format!(
+        "UPDATE {table}
+         SET backdrop_path = ?1, backdrop_origin = CASE WHEN ?1 IS NULL THEN NULL ELSE 'manual' END
+         WHERE id = ?2"
+    );
+    let result = sqlx::query(&sql).bind(path).bind(id).execute(pool).await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::MediaNotFound(id));
+    }
+    Ok(())
+}
+
 pub async fn update_episode_title(pool: &SqlitePool, id: i64, title: &str) -> AppResult<()> {
     let result = sqlx::query("UPDATE episodes SET title = ?1 WHERE id = ?2")
         .bind(title)
@@ -405,20 +492,26 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for EpisodeRef {
 
 pub async fn continue_watching(pool: &SqlitePool, limit: i64) -> AppResult<Vec<ContinueWatchingItem>> {
     // Pull recent in-progress entries for both kinds, merge by last_watched_at, then hydrate.
-    let movie_rows: Vec<(i64, i64)> = sqlx::query_as(
+    // Titles hidden by parental controls are skipped.
+    let movie_rows: Vec<(i64, i64)> = sqlx::query_as(&format!(
         "SELECT media_id, last_watched_at FROM watch_history
          WHERE media_kind = 'movie' AND watched = 0 AND progress_seconds > 30
+           AND media_id IN (SELECT m.id FROM movies m WHERE {})
          ORDER BY last_watched_at DESC LIMIT ?1",
-    )
+        parental::visible_clause("movie", "m")
+    ))
     .bind(limit)
     .fetch_all(pool)
     .await?;
 
-    let episode_rows: Vec<(i64, i64)> = sqlx::query_as(
+    let episode_rows: Vec<(i64, i64)> = sqlx::query_as(&format!(
         "SELECT media_id, last_watched_at FROM watch_history
          WHERE media_kind = 'episode' AND watched = 0 AND progress_seconds > 30
+           AND media_id IN (SELECT e.id FROM episodes e JOIN shows s ON s.id = e.show_id
+                             WHERE {})
          ORDER BY last_watched_at DESC LIMIT ?1",
-    )
+        parental::visible_clause("show", "s")
+    ))
     .bind(limit)
     .fetch_all(pool)
     .await?;
@@ -428,18 +521,18 @@ pub async fn continue_watching(pool: &SqlitePool, limit: i64) -> AppResult<Vec<C
         .map(|(id, ts)| (ts, 'm', id))
         .chain(episode_rows.into_iter().map(|(id, ts)| (ts, 'e', id)))
         .collect();
-    combined.sort_by(|a, b| b.0.cmp(&a.0));
+    combined.sort_by_key(|entry| std::cmp::Reverse(entry.0));
     combined.truncate(limit as usize);
 
     let mut out = Vec::new();
     for (_, kind, id) in combined {
         if kind == 'm' {
             if let Ok(m) = get_movie(pool, id).await {
-                out.push(ContinueWatchingItem::Movie { movie: m });
+                out.push(ContinueWatchingItem::Movie { movie: Box::new(m) });
             }
         } else if let Ok(ep) = get_episode(pool, id).await {
             if let Ok(show) = get_show(pool, ep.show_id).await {
-                out.push(ContinueWatchingItem::Episode { show, episode: ep });
+                out.push(ContinueWatchingItem::Episode { show: Box::new(show), episode: ep });
             }
         }
     }
@@ -452,10 +545,13 @@ pub struct NeedsReviewItem {
     pub id: i64,
     pub title: String,
     pub year: Option<i32>,
+    /// Candidates the worker saw last time it searched, best first.
+    #[sqlx(skip)]
+    pub candidates: Vec<crate::metadata::review::ReviewCandidate>,
 }
 
 pub async fn list_needs_review(pool: &SqlitePool) -> AppResult<Vec<NeedsReviewItem>> {
-    let items: Vec<NeedsReviewItem> = sqlx::query_as(
+    let mut items: Vec<NeedsReviewItem> = sqlx::query_as(
         "SELECT 'show' AS kind, id, title, year FROM shows
             WHERE provider IS NULL
               AND NOT EXISTS (SELECT 1 FROM metadata_jobs j
@@ -470,6 +566,13 @@ pub async fn list_needs_review(pool: &SqlitePool) -> AppResult<Vec<NeedsReviewIt
     .fetch_all(pool)
     .await?;
 
+    let mut candidates = crate::metadata::review::load_all(pool).await?;
+    for item in &mut items {
+        if let Some(found) = candidates.remove(&(item.kind.clone(), item.id)) {
+            item.candidates = found;
+        }
+    }
+
     Ok(items)
 }
 
@@ -511,7 +614,8 @@ pub async fn metadata_status_counts(
     let pending: i64 = sqlx::query_scalar(
         "SELECT COUNT(*) FROM metadata_jobs
          WHERE attempts = 0
-           AND COALESCE(last_error, '') NOT IN ('tmdb_auth_required', 'no_provider_available')",
+           AND COALESCE(last_error, '') NOT IN ('tmdb_auth_required', 'no_provider_available')
+           AND COALESCE(last_error, '') NOT LIKE 'rate_limited%'",
     )
     .fetch_one(pool)
     .await?;
@@ -542,6 +646,13 @@ pub async fn metadata_status_counts(
     .fetch_one(pool)
     .await?;
 
+    let rate_limited: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM metadata_jobs
+         WHERE attempts = 0 AND last_error LIKE 'rate_limited%'",
+    )
+    .fetch_one(pool)
+    .await?;
+
     let needs_review: i64 = sqlx::query_scalar(
         "SELECT
              (SELECT COUNT(*) FROM shows
@@ -563,6 +674,8 @@ pub async fn metadata_status_counts(
         no_provider_available,
         dead_letter,
         needs_review,
+        rate_limited,
+        rate_limits: Vec::new(),
     })
 }
 
@@ -581,21 +694,73 @@ pub fn validate(key: &str, value: Option<&str>) -> AppResult<()> {
                 None => Ok(()),
             }
         }
-        "scrape_language" | "ui_language" | "theme" | "tmdb_api_key" | "tmdb_auth_bad" => Ok(()),
+        "metadata_refresh_days" | "metadata_refresh_continuing_days" => match value {
+            Some(v) if v.parse::<u32>().is_ok_and(|days| days <= 3650) => Ok(()),
+            Some(other) => Err(AppError::Other(format!(
+                "{key}: expected a number of days between 0 and 3650, got '{other}'"
+            ))),
+            None => Ok(()),
+        },
+        "metadata_job_log_days" => match value {
+            Some(v) if v.parse::<u32>().is_ok_and(|days| (1..=3650).contains(&days)) => Ok(()),
+            Some(other) => Err(AppError::Other(format!(
+                "metadata_job_log_days: expected a number of days between 1 and 3650, got '{other}'"
+            ))),
+            None => Ok(()),
+        },
+        "certification_country" => match value {
+            Some(v) if v.len() == 2 && v.chars().all(|c| c.is_ascii_uppercase()) => Ok(()),
+            Some(other) => Err(AppError::Other(format!(
+                "certification_country: expected a two-letter country code like 'US', got '{other}'"
+            ))),
+            None => Ok(()),
+        },
+        "metadata_concurrency" => match value {
+            Some(v) if v.parse::<u32>().is_ok_and(|n| (1..=16).contains(&n)) => Ok(()),
+            Some(other) => Err(AppError::Other(format!(
+                "metadata_concurrency: expected a number between 1 and 16, got '{other}'"
+            ))),
+            None => Ok(()),
+        },
+        // Sent to providers as a query param and header: `de` or `de-AT`.
+        "scrape_language" => match value {
+            Some(v) if is_language_tag(v) => Ok(()),
+            Some(other) => Err(AppError::Other(format!(
+                "scrape_language: expected a language like 'de' or 'pt-BR', got '{other}'"
+            ))),
+            None => Ok(()),
+        },
+        "ui_language" | "theme" | "tmdb_api_key" | "tmdb_auth_bad" => Ok(()),
         // Unknown keys allowed (forward compat with future settings).
         _ => Ok(()),
     }
 }
 
+fn is_language_tag(value: &str) -> bool {
+    let (language, region) = match value.split_once('-') {
+        Some((language, region)) => (language, Some(region)),
+        None => (value, None),
+    };
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && region.is_none_or(|region| {
+            region.len() == 2 && region.chars().all(|c| c.is_ascii_uppercase())
+        })
+}
+
 /// Canonical default value for a known setting, as a string. Returned by
 /// `get_app_setting` callers when the row is missing. The TS wrapper has
 /// the parsed-type defaults; this is a parallel string version for the
 /// Rust read path.
-#[allow(dead_code)] // Reserved for a settings-defaults UI; consumers TBD.
 pub fn default_for(key: &str) -> Option<&'static str> {
     match key {
         "metadata_mode" => Some("prefer_tmdb"),
+        "metadata_refresh_days" => Some("30"),
+        "metadata_refresh_continuing_days" => Some("7"),
+        "metadata_concurrency" => Some("4"),
+        "metadata_job_log_days" => Some("30"),
         "scrape_language" => Some("en"),
+        "certification_country" => Some("US"),
         "ui_language" => Some("en"),
         "theme" => Some("system"),
         _ => None,
@@ -632,10 +797,51 @@ mod settings_tests {
         assert!(validate("future_setting", Some("anything")).is_ok());
     }
 
+    #[test]
+    fn validate_scrape_language_tags() {
+        for value in ["en", "de", "pt-BR", "fil"] {
+            assert!(validate("scrape_language", Some(value)).is_ok(), "{value}");
+        }
+        for value in ["", "EN", "en_US", "en-us", "english", "de-"] {
+            assert!(validate("scrape_language", Some(value)).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn validate_certification_country_codes() {
+        assert!(validate("certification_country", Some("GB")).is_ok());
+        assert!(validate("certification_country", Some("gb")).is_err());
+        assert!(validate("certification_country", Some("USA")).is_err());
+    }
+
+    #[test]
+    fn validate_refresh_days_accepts_whole_days_only() {
+        assert!(validate("metadata_refresh_days", Some("0")).is_ok());
+        assert!(validate("metadata_refresh_continuing_days", Some("14")).is_ok());
+        assert!(validate("metadata_refresh_days", Some("-1")).is_err());
+        assert!(validate("metadata_refresh_days", Some("1.5")).is_err());
+        assert!(validate("metadata_refresh_days", Some("9999")).is_err());
+    }
+
+    #[test]
+    fn validate_job_log_days_requires_at_least_one_day() {
+        assert!(validate("metadata_job_log_days", Some("1")).is_ok());
+        assert!(validate("metadata_job_log_days", Some("0")).is_err());
+    }
+
+    #[test]
+    fn validate_concurrency_range() {
+        assert!(validate("metadata_concurrency", Some("1")).is_ok());
+        assert!(validate("metadata_concurrency", Some("16")).is_ok());
+        assert!(validate("metadata_concurrency", Some("0")).is_err());
+        assert!(validate("metadata_concurrency", Some("17")).is_err());
+    }
+
     #[test]
     fn default_for_known_keys() {
         assert_eq!(default_for("metadata_mode"), Some("prefer_tmdb"));
         assert_eq!(default_for("scrape_language"), Some("en"));
+        assert_eq!(default_for("certification_country"), Some("US"));
         assert_eq!(default_for("theme"), Some("system"));
     }
 
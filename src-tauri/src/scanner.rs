@@ -95,8 +95,7 @@ fn clean_title(raw: &str) -> String {
     } else {
         lower_tags.to_string()
     };
-    cut.replace('.', " ")
-        .replace('_', " ")
+    cut.replace(['.', '_'], " ")
         .split_whitespace()
         .collect::<Vec<_>>()
         .join(" ")
@@ -269,7 +268,8 @@ async fn find_poster_in(dir: &Path) -> Option<PathBuf> {
 }
 
 /// Set `poster_path` + `poster_origin = 'auto'` on a show if and only if the
-/// row doesn't have a manual poster. The search visits the show's stored
+/// row doesn't have a manual poster. Synced poster variants are dropped
+/// when the poster changes, since a folder poster isn't resized. The search visits the show's stored
 /// `folder_path` first, then every distinct parent directory of its episodes
 /// — so per-season folders ("Breaking Bad S01/poster.jpg",
 /// "Breaking Bad S02/poster.jpg") all get a chance to provide artwork.
@@ -308,7 +308,10 @@ async fn maybe_set_show_poster(pool: &SqlitePool, show_id: i64) -> AppResult<()>
         if let Some(poster) = find_poster_in(&dir).await {
             let poster_str = poster.to_string_lossy().to_string();
             sqlx::query(
-                "UPDATE shows SET poster_path = ?1, poster_origin = 'auto' WHERE id = ?2",
+                "UPDATE shows SET poster_path = ?1, poster_origin = 'auto',
+                     poster_thumb_path = NULL, poster_card_path = NULL, poster_hero_path = NULL,
+                     poster_blurhash = NULL
+                 WHERE id = ?2 AND poster_path IS NOT ?1",
             )
             .bind(&poster_str)
             .bind(show_id)
@@ -340,7 +343,10 @@ async fn maybe_set_movie_poster(
     if let Some(poster) = find_poster_in(movie_dir).await {
         let poster_str = poster.to_string_lossy().to_string();
         sqlx::query(
-            "UPDATE movies SET poster_path = ?1, poster_origin = 'auto' WHERE id = ?2",
+            "UPDATE movies SET poster_path = ?1, poster_origin = 'auto',
+                 poster_thumb_path = NULL, poster_card_path = NULL, poster_hero_path = NULL,
+                 poster_blurhash = NULL
+             WHERE id = ?2 AND poster_path IS NOT ?1",
         )
         .bind(&poster_str)
         .bind(movie_id)
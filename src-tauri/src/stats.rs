@@ -0,0 +1,441 @@
+//! Viewing statistics over the playback session log. Every figure is an
+//! aggregate query over the same `watched` view of `history`'s sessions,
+//! so nothing loads individual rows. Time watched is how far playback
+//! moved through the file, capped by the wall-clock time of the session
+//! so seeking ahead doesn't count; a session counts toward the local day
+//! it started on.
+
+use sqlx::SqlitePool;
+
+use crate::error::AppResult;
+use crate::history;
+
+/// How many series `top_shows` lists.
+const TOP_SHOWS: i64 = 10;
+
+/// Longest pause between two episodes of one series that still counts
+/// as the same binge.
+const BINGE_GAP_SECONDS: i64 = 30 * 60;
+
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow, serde::Serialize)]
+pub struct Totals {
+    pub seconds: i64,
+    pub sessions: i64,
+    pub completed_sessions: i64,
+    /// Distinct movies and episodes played.
+    pub titles: i64,
+    /// Share of sessions played to the end; `None` without sessions.
+    pub completion_rate: Option<f64>,
+    /// Mean of how far into the file sessions stopped, 0 to 1, over
+    /// sessions whose length is known.
+    pub average_progress: Option<f64>,
+}
+
+/// Time watched in one day, week or month. `period` is the day, the
+/// Monday starting the week, or `YYYY-MM`.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct Bucket {
+    pub period: String,
+    pub seconds: i64,
+    pub sessions: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct ShowTime {
+    pub show_id: i64,
+    pub title: String,
+    pub seconds: i64,
+    pub sessions: i64,
+    pub episodes: i64,
+}
+
+/// Time watched per genre. A title in several genres counts toward
+/// each, so these don't sum to the total.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct GenreTime {
+    pub genre_id: i64,
+    pub slug: String,
+    pub name: String,
+    pub seconds: i64,
+    pub sessions: i64,
+}
+
+/// Episodes of one series played back to back, each starting within
+/// `BINGE_GAP_SECONDS` of the previous one ending.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct Binge {
+    pub show_id: i64,
+    pub title: String,
+    pub episodes: i64,
+    pub seconds: i64,
+    pub started_at: i64,
+    pub ended_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct YearSummary {
+    pub year: i64,
+    pub seconds: i64,
+    pub sessions: i64,
+    pub completed_sessions: i64,
+    pub movies: i64,
+    pub episodes: i64,
+    pub shows: i64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ViewingStats {
+    pub totals: Totals,
+    pub per_day: Vec<Bucket>,
+    pub per_week: Vec<Bucket>,
+    pub per_month: Vec<Bucket>,
+    pub top_shows: Vec<ShowTime>,
+    pub genres: Vec<GenreTime>,
+    pub longest_binge: Option<Binge>,
+    pub years: Vec<YearSummary>,
+}
+
+/// The sessions `history` would list between `?1` and `?2`, with what
+/// they played, time watched and the file's length.
+fn watched_cte() -> String {
+    format!(
+        "WITH watched AS (
+             SELECT p.id, p.media_kind, p.media_id, p.started_at, p.ended_at, p.completed,
+                    p.end_position_seconds, m.id AS movie_id, s.id AS show_id,
+                    s.title AS show_title,
+                    MAX(0, MIN(p.end_position_seconds - p.start_position_seconds,
+                               p.ended_at - p.started_at)) AS seconds,
+                    COALESCE(p.duration_seconds, m.duration_seconds, e.duration_seconds,
+                             m.runtime_minutes * 60) AS length
+             FROM playback_sessions p
+             {}
+             WHERE {}
+         )",
+        history::TITLE_JOINS,
+        history::filter_clause()
+    )
+}
+
+/// Statistics for sessions started between `from` and `to`, inclusive
+/// `YYYY-MM-DD` local dates (either end open when `None`).
+pub async fn viewing_stats(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<ViewingStats> {
+    Ok(ViewingStats {
+        totals: totals(pool, from, to).await?,
+        per_day: buckets(pool, from, to, "date(started_at, 'unixepoch', 'localtime')").await?,
+        per_week: buckets(
+            pool,
+            from,
+            to,
+            "date(started_at, 'unixepoch', 'localtime', 'weekday 0', '-6 days')",
+        )
+        .await?,
+        per_month: buckets(
+            pool,
+            from,
+            to,
+            "strftime('%Y-%m', started_at, 'unixepoch', 'localtime')",
+        )
+        .await?,
+        top_shows: top_shows(pool, from, to).await?,
+        genres: genres(pool, from, to).await?,
+        longest_binge: longest_binge(pool, from, to).await?,
+        years: years(pool, from, to).await?,
+    })
+}
+
+async fn totals(pool: &SqlitePool, from: Option<&str>, to: Option<&str>) -> AppResult<Totals> {
+    let totals = sqlx::query_as(&format!(
+        "{}
+         SELECT COALESCE(SUM(seconds), 0) AS seconds, COUNT(*) AS sessions,
+                COALESCE(SUM(completed), 0) AS completed_sessions,
+                COUNT(DISTINCT media_kind || ':' || media_id) AS titles,
+                CAST(SUM(completed) AS REAL) / COUNT(*) AS completion_rate,
+                AVG(CASE WHEN length > 0
+                         THEN MIN(1.0, CAST(end_position_seconds AS REAL) / length) END)
+                    AS average_progress
+         FROM watched",
+        watched_cte()
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await?;
+    Ok(totals)
+}
+
+/// Time watched grouped by `period`, an SQL expression over
+/// `started_at`, oldest first.
+async fn buckets(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+    period: &str,
+) -> AppResult<Vec<Bucket>> {
+    let buckets = sqlx::query_as(&format!(
+        "{}
+         SELECT {period} AS period, SUM(seconds) AS seconds, COUNT(*) AS sessions
+         FROM watched
+         GROUP BY 1
+         ORDER BY 1",
+        watched_cte()
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(buckets)
+}
+
+async fn top_shows(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<Vec<ShowTime>> {
+    let shows = sqlx::query_as(&format!(
+        "{}
+         SELECT show_id, show_title AS title, SUM(seconds) AS seconds, COUNT(*) AS sessions,
+                COUNT(DISTINCT media_id) AS episodes
+         FROM watched
+         WHERE show_id IS NOT NULL
+         GROUP BY show_id
+         ORDER BY seconds DESC, sessions DESC
+         LIMIT ?3",
+        watched_cte()
+    ))
+    .bind(from)
+    .bind(to)
+    .bind(TOP_SHOWS)
+    .fetch_all(pool)
+    .await?;
+    Ok(shows)
+}
+
+async fn genres(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<Vec<GenreTime>> {
+    let genres = sqlx::query_as(&format!(
+        "{},
+         tagged AS (
+             SELECT mg.genre_id, w.seconds
+             FROM watched w JOIN movie_genres mg ON mg.movie_id = w.movie_id
+             UNION ALL
+             SELECT sg.genre_id, w.seconds
+             FROM watched w JOIN show_genres sg ON sg.show_id = w.show_id
+         )
+         SELECT g.id AS genre_id, g.slug, g.name, SUM(t.seconds) AS seconds,
+                COUNT(*) AS sessions
+         FROM tagged t
+         JOIN genres g ON g.id = t.genre_id
+         GROUP BY g.id
+         ORDER BY seconds DESC, g.name",
+        watched_cte()
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(genres)
+}
+
+/// Splits episode sessions into runs, starting a new one whenever the
+/// series changes or the gap since the last episode is too long, and
+/// picks the run with the most distinct episodes.
+async fn longest_binge(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<Option<Binge>> {
+    let binge = sqlx::query_as(&format!(
+        "{},
+         marked AS (
+             SELECT show_id, show_title, media_id, seconds, started_at, ended_at, id,
+                    CASE WHEN LAG(show_id) OVER run_order = show_id
+                               AND started_at - LAG(ended_at) OVER run_order <= ?3
+                         THEN 0 ELSE 1 END AS fresh
+             FROM watched
+             WHERE media_kind = 'episode' AND show_id IS NOT NULL
+             WINDOW run_order AS (ORDER BY started_at, id)
+         ),
+         runs AS (
+             SELECT *, SUM(fresh) OVER (ORDER BY started_at, id) AS run
+             FROM marked
+         )
+         SELECT show_id, show_title AS title, COUNT(DISTINCT media_id) AS episodes,
+                SUM(seconds) AS seconds, MIN(started_at) AS started_at,
+                MAX(ended_at) AS ended_at
+         FROM runs
+         GROUP BY run
+         HAVING COUNT(DISTINCT media_id) > 1
+         ORDER BY episodes DESC, seconds DESC, started_at
+         LIMIT 1",
+        watched_cte()
+    ))
+    .bind(from)
+    .bind(to)
+    .bind(BINGE_GAP_SECONDS)
+    .fetch_optional(pool)
+    .await?;
+    Ok(binge)
+}
+
+async fn years(
+    pool: &SqlitePool,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<Vec<YearSummary>> {
+    let years = sqlx::query_as(&format!(
+        "{}
+         SELECT CAST(strftime('%Y', started_at, 'unixepoch', 'localtime') AS INTEGER) AS year,
+                SUM(seconds) AS seconds, COUNT(*) AS sessions,
+                SUM(completed) AS completed_sessions,
+                COUNT(DISTINCT movie_id) AS movies,
+                COUNT(DISTINCT CASE WHEN media_kind = 'episode' THEN media_id END) AS episodes,
+                COUNT(DISTINCT show_id) AS shows
+         FROM watched
+         GROUP BY year
+         ORDER BY year DESC",
+        watched_cte()
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(years)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{record, NewSession};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn fresh_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("memory pool");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+        pool
+    }
+
+    /// Unix time of `time` local on `date`.
+    async fn local(pool: &SqlitePool, date: &str, time: &str) -> i64 {
+        sqlx::query_scalar("SELECT CAST(strftime('%s', ?1 || ' ' || ?2, 'utc') AS INTEGER)")
+            .bind(date)
+            .bind(time)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    /// A session playing `seconds` from the start of a `length` file.
+    fn session(kind: &'static str, id: i64, started_at: i64, seconds: i64) -> NewSession {
+        NewSession {
+            media_kind: kind,
+            media_id: id,
+            started_at,
+            ended_at: started_at + seconds,
+            start_position_seconds: 0,
+            end_position_seconds: seconds,
+            duration_seconds: Some(2400),
+            completed: seconds >= 2400,
+            exit_status: Some(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn stats_aggregate_time_genres_and_binges() {
+        let pool = fresh_pool().await;
+        for statement in [
+            "INSERT INTO libraries (id, path, kind) VALUES
+                 (1, '/m', 'movies'),
+                 (2, '/tv', 'series')",
+            "INSERT INTO movies (id, library_id, title, path)
+             VALUES (1, 1, 'Heat', '/m/Heat.mkv')",
+            "INSERT INTO shows (id, library_id, title, folder_path, fingerprint)
+             VALUES (1, 2, 'Lost', '/tv/Lost', 'a')",
+            "INSERT INTO episodes (id, show_id, season, episode, title, path) VALUES
+                 (1, 1, 1, 1, 'Pilot', '/tv/Lost/s01e01.mkv'),
+                 (2, 1, 1, 2, 'Tabula Rasa', '/tv/Lost/s01e02.mkv'),
+                 (3, 1, 1, 3, 'Walkabout', '/tv/Lost/s01e03.mkv')",
+            "INSERT INTO movie_genres (movie_id, genre_id)
+             SELECT 1, id FROM genres WHERE slug = 'crime'",
+            "INSERT INTO show_genres (show_id, genre_id)
+             SELECT 1, id FROM genres WHERE slug = 'drama'",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        // Friday: three episodes back to back. Saturday: one more
+        // episode, then half of the movie a week later.
+        let friday = local(&pool, "2024-03-01", "20:00:00").await;
+        for (offset, episode) in [(0, 1), (2500, 2), (5000, 3)] {
+            record(&pool, &session("episode", episode, friday + offset, 2400))
+                .await
+                .unwrap();
+        }
+        let saturday = local(&pool, "2024-03-02", "20:00:00").await;
+        record(&pool, &session("episode", 1, saturday, 2400)).await.unwrap();
+        let next_week = local(&pool, "2024-03-09", "20:00:00").await;
+        record(&pool, &session("movie", 1, next_week, 1200)).await.unwrap();
+
+        let stats = viewing_stats(&pool, None, None).await.unwrap();
+        assert_eq!(stats.totals.seconds, 4 * 2400 + 1200);
+        assert_eq!(stats.totals.sessions, 5);
+        assert_eq!(stats.totals.completed_sessions, 4);
+        assert_eq!(stats.totals.titles, 4);
+        assert_eq!(stats.totals.completion_rate, Some(0.8));
+        assert_eq!(stats.totals.average_progress, Some(0.9));
+
+        let days: Vec<(&str, i64)> = stats
+            .per_day
+            .iter()
+            .map(|bucket| (bucket.period.as_str(), bucket.seconds))
+            .collect();
+        assert_eq!(
+            days,
+            vec![("2024-03-01", 7200), ("2024-03-02", 2400), ("2024-03-09", 1200)]
+        );
+        let weeks: Vec<&str> = stats
+            .per_week
+            .iter()
+            .map(|bucket| bucket.period.as_str())
+            .collect();
+        assert_eq!(weeks, vec!["2024-02-26", "2024-03-04"]);
+        assert_eq!(stats.per_month.len(), 1);
+        assert_eq!(stats.per_month[0].period, "2024-03");
+
+        assert_eq!(stats.top_shows.len(), 1);
+        assert_eq!((stats.top_shows[0].sessions, stats.top_shows[0].episodes), (4, 3));
+        let genres: Vec<(&str, i64)> = stats
+            .genres
+            .iter()
+            .map(|genre| (genre.slug.as_str(), genre.seconds))
+            .collect();
+        assert_eq!(genres, vec![("drama", 9600), ("crime", 1200)]);
+
+        let binge = stats.longest_binge.expect("a binge");
+        assert_eq!((binge.episodes, binge.seconds), (3, 7200));
+        assert_eq!((binge.started_at, binge.ended_at), (friday, friday + 7400));
+
+        assert_eq!(stats.years.len(), 1);
+        let year = &stats.years[0];
+        assert_eq!((year.year, year.movies, year.episodes, year.shows), (2024, 1, 3, 1));
+
+        let saturday_only = viewing_stats(&pool, Some("2024-03-02"), Some("2024-03-02"))
+            .await
+            .unwrap();
+        assert_eq!(saturday_only.totals.sessions, 1);
+        assert_eq!(saturday_only.longest_binge, None);
+    }
+}
@@ -50,6 +50,7 @@ export const TABLES: Record<TableId, TableConfig> = {
       { key: 'library_id', fkTable: 'libraries', fkLabel: 'path' },
       { key: 'provider' },
       { key: 'rating', readonly: true },
+      { key: 'status', hideInGrid: true },
       { key: 'metadata_locked', kind: 'boolean' },
       { key: 'genres', kind: 'json', hideInGrid: true },
       { key: 'top_cast', kind: 'json', hideInGrid: true },
@@ -120,8 +121,10 @@ export const TABLES: Record<TableId, TableConfig> = {
       { key: 'media_id' },
       { key: 'enqueued_at', kind: 'datetime', readonly: true },
       { key: 'attempts' },
+      { key: 'priority' },
       { key: 'last_error', hideInGrid: true },
       { key: 'next_attempt_at', kind: 'datetime' },
+      { key: 'claimed_at', kind: 'datetime', readonly: true, hideInGrid: true },
     ],
   },
   app_settings: {
@@ -1,5 +1,5 @@
 import { convertFileSrc, invoke } from '@tauri-apps/api/core';
-import { open } from '@tauri-apps/plugin-dialog';
+import { open, save } from '@tauri-apps/plugin-dialog';
 
 export type LibraryKind = 'movies' | 'series' | 'mixed';
 
@@ -10,14 +10,22 @@ export interface Library {
 }
 
 export type PosterOrigin = 'auto' | 'manual';
+export type BackdropOrigin = 'manual' | 'tmdb' | 'imdb';
 
 export interface Movie {
   id: number;
   title: string;
+  localized_title: string | null;
   year: number | null;
   path: string;
   poster_path: string | null;
   poster_origin: PosterOrigin | null;
+  poster_thumb_path: string | null;
+  poster_card_path: string | null;
+  poster_hero_path: string | null;
+  poster_blurhash: string | null;
+  backdrop_path: string | null;
+  backdrop_origin: BackdropOrigin | null;
   overview: string | null;
   duration_seconds: number | null;
   progress_seconds: number;
@@ -37,11 +45,18 @@ export interface Show {
   id: number;
   library_id: number;
   title: string;
+  localized_title: string | null;
   year: number | null;
   folder_path: string;
   fingerprint: string;
   poster_path: string | null;
   poster_origin: PosterOrigin | null;
+  poster_thumb_path: string | null;
+  poster_card_path: string | null;
+  poster_hero_path: string | null;
+  poster_blurhash: string | null;
+  backdrop_path: string | null;
+  backdrop_origin: BackdropOrigin | null;
   overview: string | null;
   episode_count: number;
   watched_count: number;
@@ -52,6 +67,11 @@ export interface Show {
   genres: string | null;
   top_cast: string | null;
   first_air_date: string | null;
+  status: string | null;
+  end_year: number | null;
+  /** JSON array of network names. */
+  networks: string | null;
+  next_episode_air_date: string | null;
   metadata_synced_at: number | null;
   metadata_locked: number;
 }
@@ -67,6 +87,17 @@ export interface MetadataStatusCounts {
   no_provider_available: number;
   dead_letter: number;
   needs_review: number;
+  rate_limited: number;
+  rate_limits: RateLimitStatus[];
+}
+
+export interface RateLimitStatus {
+  provider: 'tmdb' | 'imdb';
+  capacity: number;
+  available: number;
+  refill_per_second: number;
+  blocked_until: number | null;
+  throttle_count: number;
 }
 
 export interface MatchCandidate {
@@ -74,6 +105,18 @@ export interface MatchCandidate {
   provider_id: string;
   title: string;
   year: number | null;
+  poster_url: string | null;
+  overview: string | null;
+}
+
+export interface ReviewCandidate {
+  provider: 'tmdb' | 'imdb';
+  provider_id: string;
+  title: string;
+  year: number | null;
+  poster_url: string | null;
+  overview: string | null;
+  score: number;
 }
 
 export interface NeedsReviewItem {
@@ -81,6 +124,305 @@ export interface NeedsReviewItem {
   id: number;
   title: string;
   year: number | null;
+  candidates: ReviewCandidate[];
+}
+
+export interface ReviewApproval {
+  kind: 'show' | 'movie';
+  media_id: number;
+  provider: 'tmdb' | 'imdb';
+  provider_id: string;
+}
+
+export interface FailedJob {
+  kind: 'show' | 'movie';
+  media_id: number;
+  title: string | null;
+  year: number | null;
+  attempts: number;
+  last_error: string | null;
+  next_attempt_at: number;
+  dead_lettered: boolean;
+}
+
+export interface JobRef {
+  kind: 'show' | 'movie';
+  media_id: number;
+}
+
+export interface CollectionPart {
+  provider_id: string;
+  title: string;
+  release_date: string | null;
+  poster_url: string | null;
+  overview: string | null;
+  movie_id: number | null;
+  watched: boolean;
+}
+
+export interface Collection {
+  id: number;
+  name: string;
+  overview: string | null;
+  poster_path: string | null;
+  part_count: number;
+  owned_count: number;
+  parts: CollectionPart[];
+}
+
+export interface CalendarEntry {
+  show_id: number;
+  show_title: string;
+  poster_path: string | null;
+  season: number;
+  episode: number;
+  title: string | null;
+  air_date: string;
+}
+
+/** One mpv run, as logged when it exited. */
+export interface PlaybackSession {
+  id: number;
+  media_kind: 'movie' | 'episode';
+  media_id: number;
+  /** `null` once the title has left the library. */
+  title: string | null;
+  show_id: number | null;
+  show_title: string | null;
+  season: number | null;
+  episode: number | null;
+  started_at: number;
+  ended_at: number;
+  start_position_seconds: number;
+  end_position_seconds: number;
+  duration_seconds: number | null;
+  completed: boolean;
+  exit_status: number | null;
+}
+
+export interface PlayCount {
+  media_kind: 'movie' | 'episode';
+  media_id: number;
+  title: string | null;
+  show_title: string | null;
+  season: number | null;
+  episode: number | null;
+  plays: number;
+  completed_plays: number;
+  last_played_at: number;
+}
+
+export interface ViewingTotals {
+  seconds: number;
+  sessions: number;
+  completed_sessions: number;
+  titles: number;
+  completion_rate: number | null;
+  average_progress: number | null;
+}
+
+/** `period` is the day, the Monday starting the week, or `YYYY-MM`. */
+export interface StatsBucket {
+  period: string;
+  seconds: number;
+  sessions: number;
+}
+
+export interface ShowTime {
+  show_id: number;
+  title: string;
+  seconds: number;
+  sessions: number;
+  episodes: number;
+}
+
+export interface GenreTime {
+  genre_id: number;
+  slug: string;
+  name: string;
+  seconds: number;
+  sessions: number;
+}
+
+export interface Binge {
+  show_id: number;
+  title: string;
+  episodes: number;
+  seconds: number;
+  started_at: number;
+  ended_at: number;
+}
+
+export interface YearSummary {
+  year: number;
+  seconds: number;
+  sessions: number;
+  completed_sessions: number;
+  movies: number;
+  episodes: number;
+  shows: number;
+}
+
+export interface ViewingStats {
+  totals: ViewingTotals;
+  per_day: StatsBucket[];
+  per_week: StatsBucket[];
+  per_month: StatsBucket[];
+  top_shows: ShowTime[];
+  genres: GenreTime[];
+  longest_binge: Binge | null;
+  years: YearSummary[];
+}
+
+export interface ShowCalendar {
+  upcoming: CalendarEntry[];
+  missing: CalendarEntry[];
+}
+
+export interface GenreCount {
+  id: number;
+  slug: string;
+  name: string;
+  movie_count: number;
+  show_count: number;
+}
+
+/** One source's rating of a title; `value` is out of `scale`. */
+export interface Rating {
+  source: string;
+  value: number;
+  scale: number;
+  votes: number | null;
+  fetched_at: number;
+}
+
+/** An id the title is known by in another database. */
+export interface ExternalId {
+  source: string;
+  external_id: string;
+}
+
+/** A mapping-file entry that can't be linked; `line` is the CSV line or JSON entry number. */
+export interface LinkImportProblem {
+  line: number;
+  reason: string;
+}
+
+export interface LinkImportReport {
+  valid: number;
+  problems: LinkImportProblem[];
+  /** False on a dry run or when any entry has a problem. */
+  applied: boolean;
+}
+
+/** A provider title to preview or link. */
+export interface PreviewCandidate {
+  provider: 'tmdb' | 'imdb';
+  provider_id: string;
+}
+
+export type PreviewField = 'title' | 'year' | 'overview' | 'genres' | 'cast' | 'rating' | 'poster';
+
+/**
+ * One field of a metadata preview. `current` and `proposed` are the
+ * field's value as JSON: strings, numbers, name lists, or a poster path
+ * and URL.
+ */
+export interface FieldDiff {
+  field: PreviewField;
+  current: unknown;
+  proposed: unknown;
+  changed: boolean;
+}
+
+export interface MetadataPreview {
+  kind: 'show' | 'movie';
+  media_id: number;
+  candidate: PreviewCandidate;
+  fields: FieldDiff[];
+}
+
+/** List order: by title, or best rated first. */
+export type ListSort = 'title' | 'rating';
+
+export interface PosterCacheReport {
+  files_removed: number;
+  bytes_reclaimed: number;
+  broken: number;
+  requeued: number;
+  redownloaded: number;
+  rerendered: number;
+  not_requeued: number;
+}
+
+export interface ParentalControls {
+  enabled: boolean;
+  max_rating: string | null;
+  max_age: number | null;
+  hide_unrated: boolean;
+  country: string;
+}
+
+export type CreditRole = 'cast' | 'director' | 'writer';
+
+export interface TitleCredit {
+  person_id: number;
+  name: string;
+  photo_url: string | null;
+  role: CreditRole;
+  job: string | null;
+  character: string | null;
+  credit_order: number;
+}
+
+export interface Person {
+  id: number;
+  provider: 'tmdb' | 'imdb' | 'manual';
+  provider_id: string;
+  name: string;
+  photo_url: string | null;
+}
+
+export interface FilmographyEntry {
+  kind: 'movie' | 'show';
+  media_id: number;
+  title: string;
+  year: number | null;
+  poster_path: string | null;
+  role: CreditRole;
+  job: string | null;
+  character: string | null;
+}
+
+export interface Filmography {
+  person: Person;
+  credits: FilmographyEntry[];
+}
+
+export interface TitleAlias {
+  id: number;
+  kind: 'show' | 'movie';
+  normalized_title: string;
+  year: number | null;
+  provider: 'tmdb' | 'imdb';
+  provider_id: string;
+  source_title: string;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface JobLogEntry {
+  id: number;
+  kind: 'show' | 'movie';
+  media_id: number;
+  provider: 'tmdb' | 'imdb';
+  query: string | null;
+  candidate_count: number | null;
+  chosen_id: string | null;
+  outcome: 'matched' | 'no_match' | 'rate_limited' | 'auth_required' | 'error';
+  error: string | null;
+  duration_ms: number;
+  logged_at: number;
 }
 
 export interface Episode {
@@ -120,10 +462,31 @@ export interface MergeOutcome {
   conflicts: EpisodeRef[];
 }
 
+/** One cast member of a manual edit, in billing order. */
+export interface CastEntry {
+  name: string;
+  character: string | null;
+}
+
+/**
+ * A manual metadata edit; only the fields present or named in `clear`
+ * change. An empty `genres` or `cast` list clears it. Saving locks the
+ * title against sync.
+ */
 export interface MetadataPatch {
   title?: string;
   year?: number;
   overview?: string;
+  genres?: string[];
+  cast?: CastEntry[];
+  /** Out of 10. */
+  rating?: number;
+  /** Movies only. */
+  runtime_minutes?: number;
+  /** Shows only; `YYYY-MM-DD`. */
+  first_air_date?: string;
+  /** Fields to empty. */
+  clear?: ('year' | 'overview' | 'rating' | 'runtime_minutes' | 'first_air_date')[];
 }
 
 export function pickImageFile(): Promise<string | null> {
@@ -135,6 +498,23 @@ export function pickImageFile(): Promise<string | null> {
   }) as Promise<string | null>;
 }
 
+export function pickLinkMappingFile(): Promise<string | null> {
+  return open({
+    directory: false,
+    multiple: false,
+    title: 'Select a link mapping file',
+    filters: [{ name: 'CSV or JSON', extensions: ['csv', 'json'] }],
+  }) as Promise<string | null>;
+}
+
+export function pickExportFile(defaultPath: string): Promise<string | null> {
+  return save({
+    title: 'Export to file',
+    defaultPath,
+    filters: [{ name: 'JSON', extensions: ['json'] }],
+  });
+}
+
 export const api = {
   listLibraries: () => invoke<Library[]>('list_libraries'),
   addLibrary: (path: string, kind: LibraryKind = 'mixed') =>
@@ -142,11 +522,19 @@ export const api = {
   removeLibrary: (id: number) => invoke<void>('remove_library', { id }),
   scanLibraries: () => invoke<ScanReport>('scan_libraries'),
 
-  listMovies: () => invoke<Movie[]>('list_movies'),
+  listMovies: (genres?: string[], ratingSource?: string, sort?: ListSort) =>
+    invoke<Movie[]>('list_movies', { genres, ratingSource, sort }),
   getMovie: (id: number) => invoke<Movie>('get_movie', { id }),
 
-  listShows: () => invoke<Show[]>('list_shows'),
+  listShows: (genres?: string[], ratingSource?: string, sort?: ListSort) =>
+    invoke<Show[]>('list_shows', { genres, ratingSource, sort }),
   getShow: (id: number) => invoke<Show>('get_show', { id }),
+  listGenres: () => invoke<GenreCount[]>('list_genres'),
+  listTitleRatings: (kind: 'movie' | 'show', id: number) =>
+    invoke<Rating[]>('list_title_ratings', { kind, id }),
+  listRatingSources: () => invoke<string[]>('list_rating_sources'),
+  listExternalIds: (kind: 'movie' | 'show', id: number) =>
+    invoke<ExternalId[]>('list_external_ids', { kind, id }),
   getSeasons: (showId: number) => invoke<Season[]>('get_seasons', { showId }),
   getEpisode: (id: number) => invoke<Episode>('get_episode', { id }),
 
@@ -159,11 +547,40 @@ export const api = {
     invoke<PlayResult>('play_movie', { id, resume }),
   playEpisode: (id: number, resume?: number) =>
     invoke<PlayResult>('play_episode', { id, resume }),
+  showCalendar: (recentDays?: number) => invoke<ShowCalendar>('show_calendar', { recentDays }),
+  /** `from` and `to` are inclusive `YYYY-MM-DD` local dates. */
+  listPlaybackHistory: (from?: string, to?: string) =>
+    invoke<PlaybackSession[]>('list_playback_history', { from, to }),
+  listPlayCounts: (from?: string, to?: string) =>
+    invoke<PlayCount[]>('list_play_counts', { from, to }),
+  viewingStats: (from?: string, to?: string) =>
+    invoke<ViewingStats>('viewing_stats', { from, to }),
+  listCollections: () => invoke<Collection[]>('list_collections'),
+  getCollection: (id: number) => invoke<Collection>('get_collection', { id }),
+  playCollection: (id: number) => invoke<PlayResult>('play_collection', { id }),
+  listTitlePeople: (kind: 'movie' | 'show', id: number) =>
+    invoke<TitleCredit[]>('list_title_people', { kind, id }),
+  getPersonFilmography: (id: number) => invoke<Filmography>('get_person_filmography', { id }),
+  getParentalControls: () => invoke<ParentalControls>('get_parental_controls'),
+  setParentalControls: (
+    currentPin: string | null,
+    pin: string,
+    maxRating: string,
+    hideUnrated: boolean,
+  ) =>
+    invoke<ParentalControls>('set_parental_controls', {
+      currentPin,
+      pin,
+      maxRating,
+      hideUnrated,
+    }),
+  disableParentalControls: (pin: string) => invoke<void>('disable_parental_controls', { pin }),
+  listCertifications: () => invoke<string[]>('list_certifications'),
 
   updateShowMetadata: (id: number, patch: MetadataPatch) =>
-    invoke<Show>('update_show_metadata', { id, ...patch }),
+    invoke<Show>('update_show_metadata', { id, patch }),
   updateMovieMetadata: (id: number, patch: MetadataPatch) =>
-    invoke<Movie>('update_movie_metadata', { id, ...patch }),
+    invoke<Movie>('update_movie_metadata', { id, patch }),
   updateEpisodeTitle: (id: number, title: string) =>
     invoke<Episode>('update_episode_title', { id, title }),
   mergeShows: (targetId: number, sourceId: number) =>
@@ -175,6 +592,12 @@ export const api = {
     invoke<Movie>('set_movie_poster_from_file', { id, sourcePath }),
   resetShowPoster: (id: number) => invoke<Show>('reset_show_poster', { id }),
   resetMoviePoster: (id: number) => invoke<Movie>('reset_movie_poster', { id }),
+  setShowBackdropFromFile: (id: number, sourcePath: string) =>
+    invoke<Show>('set_show_backdrop_from_file', { id, sourcePath }),
+  setMovieBackdropFromFile: (id: number, sourcePath: string) =>
+    invoke<Movie>('set_movie_backdrop_from_file', { id, sourcePath }),
+  resetShowBackdrop: (id: number) => invoke<Show>('reset_show_backdrop', { id }),
+  resetMovieBackdrop: (id: number) => invoke<Movie>('reset_movie_backdrop', { id }),
 
   metadataStatusCounts: () =>
     invoke<MetadataStatusCounts>('metadata_status_counts'),
@@ -196,7 +619,42 @@ export const api = {
     providerId: string,
   ) =>
     invoke<void>('link_metadata', { kind, mediaId, provider, providerId }),
+  /** Without a candidate, previews a refresh of the current link. */
+  previewMetadata: (kind: 'show' | 'movie', mediaId: number, candidate?: PreviewCandidate) =>
+    invoke<MetadataPreview>('preview_metadata', { kind, mediaId, candidate: candidate ?? null }),
+  /** Without `fields`, links the candidate; otherwise writes those fields and locks the row. */
+  applyMetadataPreview: (
+    kind: 'show' | 'movie',
+    mediaId: number,
+    candidate: PreviewCandidate,
+    fields?: PreviewField[],
+  ) =>
+    invoke<void>('apply_metadata_preview', {
+      kind,
+      mediaId,
+      candidate,
+      fields: fields ?? null,
+    }),
   listNeedsReview: () => invoke<NeedsReviewItem[]>('list_needs_review'),
+  importMetadataLinks: (path: string, dryRun: boolean) =>
+    invoke<LinkImportReport>('import_metadata_links', { path, dryRun }),
+  approveReviewMatches: (approvals: ReviewApproval[]) =>
+    invoke<number>('approve_review_matches', { approvals }),
+  metadataJobLog: (kind: 'show' | 'movie', id: number, limit?: number) =>
+    invoke<JobLogEntry[]>('metadata_job_log', { kind, id, limit: limit ?? null }),
+  listFailedMetadataJobs: () => invoke<FailedJob[]>('list_failed_metadata_jobs'),
+  retryMetadataJobs: (jobs?: JobRef[]) =>
+    invoke<number>('retry_metadata_jobs', { jobs: jobs ?? null }),
+  dismissMetadataJobs: (jobs: JobRef[]) => invoke<number>('dismiss_metadata_jobs', { jobs }),
+  purgeOrphanedMetadataJobs: () => invoke<number>('purge_orphaned_metadata_jobs'),
+  listTitleAliases: () => invoke<TitleAlias[]>('list_title_aliases'),
+  updateTitleAlias: (id: number, provider: 'tmdb' | 'imdb', providerId: string) =>
+    invoke<void>('update_title_alias', { id, provider, providerId }),
+  deleteTitleAlias: (id: number) => invoke<void>('delete_title_alias', { id }),
+  exportTitleAliases: (path: string) => invoke<number>('export_title_aliases', { path }),
+  clearHttpCache: (provider?: 'tmdb' | 'imdb') =>
+    invoke<number>('clear_http_cache', { provider: provider ?? null }),
+  runPosterMaintenance: () => invoke<PosterCacheReport>('run_poster_maintenance'),
 
   adminListRows: (table: string, sortColumn?: string, direction?: 'asc' | 'desc') =>
     invoke<Record<string, unknown>[]>('admin_list_rows', {
@@ -231,6 +689,11 @@ export const api = {
     }) as Promise<string | null>,
 };
 
+/** Provider title in the scrape language, unless the user edited the title. */
+export function displayTitle(item: Movie | Show): string {
+  return item.metadata_locked ? item.title : (item.localized_title ?? item.title);
+}
+
 export function formatRuntime(seconds: number | null | undefined): string {
   if (!seconds || seconds <= 0) {
     return '';
@@ -258,6 +721,95 @@ export function posterUrl(posterPath: string | null | undefined): string | undef
   return convertFileSrc(posterPath);
 }
 
+export type PosterVariant = 'thumb' | 'card' | 'hero';
+
+type PosterPaths = Pick<
+  Movie,
+  'poster_path' | 'poster_thumb_path' | 'poster_card_path' | 'poster_hero_path'
+>;
+
+/** The poster resized for `variant`, or the full poster when there's none. */
+export function posterFor(item: PosterPaths, variant: PosterVariant): string | null {
+  const resized = {
+    thumb: item.poster_thumb_path,
+    card: item.poster_card_path,
+    hero: item.poster_hero_path,
+  }[variant];
+  return resized ?? item.poster_path;
+}
+
+const BASE83 = '0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~';
+
+/**
+ * The poster's average colour, painted while it loads. That's the DC
+ * component of its blurhash: characters 2–5, base 83, packed sRGB.
+ */
+export function posterPlaceholder(item: { poster_blurhash: string | null }): string | null {
+  const hash = item.poster_blurhash;
+  if (!hash || hash.length < 6) {
+    return null;
+  }
+  let value = 0;
+  for (const char of hash.slice(2, 6)) {
+    value = value * 83 + BASE83.indexOf(char);
+  }
+  return `#${value.toString(16).padStart(6, '0')}`;
+}
+
+/** Genre names from a title's `genres` JSON. */
+export function titleGenres(item: { genres: string | null }): string[] {
+  if (!item.genres) {
+    return [];
+  }
+  try {
+    const parsed = JSON.parse(item.genres);
+    return Array.isArray(parsed) ? parsed.map(String) : [];
+  } catch {
+    return [];
+  }
+}
+
+/** Network names from a show's `networks` JSON. */
+export function showNetworks(show: Pick<Show, 'networks'>): string[] {
+  if (!show.networks) {
+    return [];
+  }
+  try {
+    const parsed = JSON.parse(show.networks);
+    return Array.isArray(parsed) ? parsed.map(String) : [];
+  } catch {
+    return [];
+  }
+}
+
+const SOURCE_LABELS: Record<string, string> = {
+  tmdb: 'TMDB',
+  imdb: 'IMDb',
+  tvdb: 'TheTVDB',
+  wikidata: 'Wikidata',
+  manual: 'Yours',
+};
+
+/** Display name of a rating or external id source. */
+export function sourceLabel(source: string): string {
+  return SOURCE_LABELS[source] ?? source;
+}
+
+/** `8.2/10 · 25k votes`. */
+export function formatRating(rating: Rating): string {
+  const value = `${rating.value.toFixed(1)}/${rating.scale}`;
+  if (!rating.votes) {
+    return value;
+  }
+  const votes = new Intl.NumberFormat(undefined, { notation: 'compact' }).format(rating.votes);
+  return `${value} · ${votes} votes`;
+}
+
+/** Wide artwork for page headers; the hero poster stands in without one. */
+export function backdropFor(item: PosterPaths & Pick<Movie, 'backdrop_path'>): string | null {
+  return item.backdrop_path ?? posterFor(item, 'hero');
+}
+
 export function progressPct(
   playback: { progress_seconds: number; duration_seconds: number | null },
 ): number {
@@ -1,4 +1,6 @@
+export { default as CalendarDays } from '@lucide/svelte/icons/calendar-days';
 export { default as Clapperboard } from '@lucide/svelte/icons/clapperboard';
+export { default as History } from '@lucide/svelte/icons/history';
 export { default as House } from '@lucide/svelte/icons/house';
 export { default as User } from '@lucide/svelte/icons/user';
 export { default as Tv } from '@lucide/svelte/icons/tv';
@@ -23,3 +25,4 @@ export { default as RotateCcw } from '@lucide/svelte/icons/rotate-ccw';
 export { default as AlertTriangle } from '@lucide/svelte/icons/alert-triangle';
 export { default as GitMerge } from '@lucide/svelte/icons/git-merge';
 export { default as MoreHorizontal } from '@lucide/svelte/icons/more-horizontal';
+export { default as Plus } from '@lucide/svelte/icons/plus';
@@ -13,6 +13,11 @@ type SettingDef<T> = {
   encode: (value: T) => string | null;
 };
 
+function parseDays(raw: string | null, fallback: number): number {
+  const parsed = raw === null ? NaN : Number.parseInt(raw, 10);
+  return Number.isFinite(parsed) && parsed >= 0 ? parsed : fallback;
+}
+
 export const SETTINGS = {
   tmdb_api_key: {
     default: null as string | null,
@@ -42,11 +47,44 @@ export const SETTINGS = {
     encode: (value: MetadataMode): string => value,
   } satisfies SettingDef<MetadataMode>,
 
+  metadata_refresh_days: {
+    default: 30,
+    parse: (raw: string | null): number => parseDays(raw, 30),
+    encode: (value: number): string => String(value),
+  } satisfies SettingDef<number>,
+
+  metadata_refresh_continuing_days: {
+    default: 7,
+    parse: (raw: string | null): number => parseDays(raw, 7),
+    encode: (value: number): string => String(value),
+  } satisfies SettingDef<number>,
+
+  metadata_concurrency: {
+    default: 4,
+    parse: (raw: string | null): number => {
+      const parsed = raw === null ? NaN : Number.parseInt(raw, 10);
+      return Number.isFinite(parsed) && parsed >= 1 && parsed <= 16 ? parsed : 4;
+    },
+    encode: (value: number): string => String(value),
+  } satisfies SettingDef<number>,
+
+  metadata_job_log_days: {
+    default: 30,
+    parse: (raw: string | null): number => Math.max(1, parseDays(raw, 30)),
+    encode: (value: number): string => String(value),
+  } satisfies SettingDef<number>,
+
   scrape_language: {
     default: 'en',
     parse: (raw: string | null): string => raw ?? 'en',
     encode: (value: string): string => value,
   } satisfies SettingDef<string>,
+
+  certification_country: {
+    default: 'US',
+    parse: (raw: string | null): string => raw ?? 'US',
+    encode: (value: string): string => value,
+  } satisfies SettingDef<string>,
 } as const;
 
 export type SettingKey = keyof typeof SETTINGS;
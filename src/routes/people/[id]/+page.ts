@@ -0,0 +1,2 @@
+// Dynamic route — must be rendered client-side
+export const prerender = false;